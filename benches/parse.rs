@@ -0,0 +1,117 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use cmakefmt::format::{format_str, FormatOptions};
+use cmakefmt::incremental::{IncrementalDocument, TextEdit};
+use cmakefmt::parser::{cmake_parser, Input};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+/// Counts heap allocations, to report how many a parse makes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The number of allocations and reallocations made by `f`.
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+const SAMPLES: [(&str, &str); 3] = [
+    ("skia", include_str!("../samples/skia.input.cmake")),
+    ("opencv", include_str!("../samples/opencv.input.cmake")),
+    ("juce", include_str!("../samples/juce.input.cmake")),
+];
+
+/// `if()` with a condition of `terms` operands mixing `AND`, `OR`, `NOT`,
+/// binary tests and parentheses.
+fn long_condition(terms: usize) -> String {
+    let operands: Vec<String> = (0..terms)
+        .map(|i| match i % 4 {
+            0 => format!("V{i} STREQUAL \"value {i}\""),
+            1 => format!("NOT DEFINED V{i}"),
+            2 => format!("(A{i} OR B{i})"),
+            _ => format!("V{i}"),
+        })
+        .collect();
+    format!(
+        "if({})\n  message(STATUS yes)\nendif()\n",
+        operands.join(" AND ")
+    )
+}
+
+fn parse(c: &mut Criterion) {
+    for (name, source) in SAMPLES {
+        let parse = || cmake_parser(black_box(Input::new(source))).unwrap();
+        let format = || format_str(black_box(source), &FormatOptions::default()).unwrap();
+        println!(
+            "{name}: {} allocations to parse, {} to format",
+            allocations(parse),
+            allocations(format)
+        );
+        c.bench_function(&format!("parse {name}"), |b| b.iter(parse));
+        c.bench_function(&format!("format {name}"), |b| b.iter(format));
+
+        // Comment out a line in the middle and back, formatting after each
+        let middle = source.len() / 2 + source[source.len() / 2..].find('\n').unwrap() + 1;
+        let mut document = IncrementalDocument::new(source, FormatOptions::default());
+        c.bench_function(&format!("edit {name}"), |b| {
+            b.iter(|| {
+                document.edit(&TextEdit::new(middle..middle, "#"));
+                black_box(document.formatted());
+                document.edit(&TextEdit::new(middle..middle + 1, ""));
+                black_box(document.formatted());
+            })
+        });
+    }
+
+    let condition = long_condition(200);
+    c.bench_function("parse 200-term condition", |b| {
+        b.iter(|| cmake_parser(black_box(Input::new(&condition))).unwrap())
+    });
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+#ifndef CMAKEFMT_H
+#define CMAKEFMT_H
+
+/* cmakefmt C interface. Generated from src/capi.rs by cbindgen, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The outcome of `cmakefmt_format`.
+ */
+typedef enum cmakefmt_status {
+  CMAKEFMT_OK = 0,
+  /**
+   * Some statements failed to parse. The output keeps them as they were
+   * and the diagnostics say why.
+   */
+  CMAKEFMT_PARSE_ERROR = 1,
+  /**
+   * The source isn't UTF-8. There is no output and a single diagnostic
+   * points at the first invalid byte.
+   */
+  CMAKEFMT_INVALID_UTF8 = 2,
+  /**
+   * A required pointer was null, or a setting isn't one of its values.
+   */
+  CMAKEFMT_INVALID_ARGUMENT = 3,
+  /**
+   * The formatter panicked. Please report it.
+   */
+  CMAKEFMT_INTERNAL_ERROR = 4,
+} cmakefmt_status;
+
+/**
+ * A style preset to start from, see `cmakefmt_options_init`.
+ *
+ * Settings taking one of these values are `uint32_t`, so that a value
+ * missing from the enum can be rejected instead of being undefined
+ * behaviour.
+ */
+typedef enum cmakefmt_preset {
+  CMAKEFMT_PRESET_DEFAULT = 0,
+  CMAKEFMT_PRESET_CMAKE_FORMAT = 1,
+  CMAKEFMT_PRESET_GERSEMI_LIKE = 2,
+  CMAKEFMT_PRESET_COMPACT = 3,
+} cmakefmt_preset;
+
+typedef enum cmakefmt_command_case {
+  CMAKEFMT_COMMAND_CASE_UNCHANGED = 0,
+  CMAKEFMT_COMMAND_CASE_LOWER = 1,
+  CMAKEFMT_COMMAND_CASE_UPPER = 2,
+} cmakefmt_command_case;
+
+typedef enum cmakefmt_list_layout {
+  CMAKEFMT_LIST_LAYOUT_ONE_PER_LINE = 0,
+  CMAKEFMT_LIST_LAYOUT_FILL = 1,
+  CMAKEFMT_LIST_LAYOUT_AUTO = 2,
+} cmakefmt_list_layout;
+
+typedef enum cmakefmt_severity {
+  CMAKEFMT_SEVERITY_ERROR = 0,
+  CMAKEFMT_SEVERITY_WARNING = 1,
+} cmakefmt_severity;
+
+/**
+ * Formatting settings. Fill them with `cmakefmt_options_init` and change
+ * the fields of interest; per-command settings aren't available here.
+ */
+typedef struct cmakefmt_options {
+  /**
+   * The column limit.
+   */
+  size_t width;
+  /**
+   * Number of spaces used for each indentation level.
+   */
+  size_t indent;
+  /**
+   * Put the closing parenthesis of a broken command on its own line.
+   */
+  bool dangle_parens;
+  /**
+   * One of `cmakefmt_command_case`.
+   */
+  uint32_t command_case;
+  /**
+   * Group upper-case keywords with the arguments that follow them.
+   */
+  bool keyword_grouping;
+  /**
+   * Consecutive blank lines above this are removed.
+   */
+  size_t max_blank_lines;
+  /**
+   * One of `cmakefmt_list_layout`.
+   */
+  uint32_t list_layout;
+} cmakefmt_options;
+
+/**
+ * A location in the source. Lines and columns start at 1 and columns count
+ * characters.
+ */
+typedef struct cmakefmt_position {
+  /**
+   * Byte offset from the start of the source.
+   */
+  size_t offset;
+  size_t line;
+  size_t column;
+} cmakefmt_position;
+
+typedef struct cmakefmt_span {
+  cmakefmt_position start;
+  cmakefmt_position end;
+} cmakefmt_span;
+
+typedef struct cmakefmt_diagnostic {
+  cmakefmt_severity severity;
+  /**
+   * NUL-terminated message.
+   */
+  char *message;
+  cmakefmt_span span;
+} cmakefmt_diagnostic;
+
+/**
+ * What `cmakefmt_format` produced. Release it with `cmakefmt_result_free`.
+ */
+typedef struct cmakefmt_result {
+  /**
+   * The formatted source, NUL-terminated, or null when there is none.
+   */
+  char *output;
+  /**
+   * Length of `output` in bytes, without the terminator.
+   */
+  size_t output_len;
+  cmakefmt_diagnostic *diagnostics;
+  size_t diagnostics_len;
+} cmakefmt_result;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Fills `options` with the settings of `preset`, one of `cmakefmt_preset`,
+ * and a column limit of 80.
+ *
+ * # Safety
+ *
+ * `options` must be null or point to writable memory for a
+ * `cmakefmt_options`.
+ */
+cmakefmt_status cmakefmt_options_init(cmakefmt_options *options, uint32_t preset);
+
+/**
+ * Formats the `source_len` bytes at `source` into `result`, using the
+ * default settings when `options` is null. Settings out of range make it
+ * return `CMAKEFMT_INVALID_ARGUMENT`. Statements that fail to parse
+ * are kept as they were and reported in the diagnostics, along with any
+ * warnings. `result` must be released with `cmakefmt_result_free`, whatever
+ * the returned status.
+ *
+ * # Safety
+ *
+ * `source` must point to `source_len` readable bytes; it may be null when
+ * `source_len` is 0. `options` must be null or point to a valid
+ * `cmakefmt_options`. `result` must point to writable memory for a
+ * `cmakefmt_result`.
+ */
+cmakefmt_status cmakefmt_format(const char *source,
+                                size_t source_len,
+                                const cmakefmt_options *options,
+                                cmakefmt_result *result);
+
+/**
+ * Releases everything `cmakefmt_format` stored in `result` and clears it.
+ *
+ * # Safety
+ *
+ * `result` must be null or have been filled by `cmakefmt_format`, and not
+ * released since.
+ */
+void cmakefmt_result_free(cmakefmt_result *result);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CMAKEFMT_H */
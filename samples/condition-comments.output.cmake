@@ -0,0 +1,19 @@
+if(DEFINED ENV{CI} # running in CI
+   AND NOT DEFINED CACHE{SKIP_TESTS})
+  enable_testing()
+endif()
+
+if(IS_READABLE config.h
+   AND IS_WRITABLE build
+   # the generator must be newer
+   AND gen.py IS_NEWER_THAN config.h #[[ bracket ]]
+   OR IS_EXECUTABLE tool)
+  message(STATUS "regenerating")
+elseif(EXISTS #[=[ path ]=] ${ROOT}/x)
+endif()
+
+while(A
+      OR # first
+      B # second
+)
+endwhile()
\ No newline at end of file
@@ -0,0 +1,11 @@
+set(PATH_WITH_SPACES C:/Program\ Files\ \(x86\)/foo)
+add_definitions(-DFOO="a b" -DBAR=\"baz\")
+set(list a\;b\;c)
+message(STATUS \$HOME\ is\ ${HOME}\t\n)
+set(
+  items a b
+  #trailing comment
+  c
+)
+message("first line \
+second line")
\ No newline at end of file
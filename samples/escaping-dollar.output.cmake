@@ -1,5 +1,4 @@
 install(
-  CODE
-  "EXECUTE_PROCESS(\"\$ENV{DESTDIR}\${CMAKE_INSTALL_PREFIX}/samples/${sample_dir}\"
+  CODE "EXECUTE_PROCESS(\"\$ENV{DESTDIR}\${CMAKE_INSTALL_PREFIX}/samples/${sample_dir}\"
                                    )"
 )
\ No newline at end of file
@@ -67,10 +67,10 @@ include(extras/Build/CMake/JUCEUtils.cmake)
 
 set_directory_properties(
   PROPERTIES
-  JUCE_COMPANY_NAME "JUCE"
-  JUCE_COMPANY_WEBSITE "https://juce.com"
-  JUCE_COMPANY_EMAIL "info@juce.com"
-  JUCE_COMPANY_COPYRIGHT "Copyright (c) 2020 - Raw Material Software Limited"
+    JUCE_COMPANY_NAME      "JUCE"
+    JUCE_COMPANY_WEBSITE   "https://juce.com"
+    JUCE_COMPANY_EMAIL     "info@juce.com"
+    JUCE_COMPANY_COPYRIGHT "Copyright (c) 2020 - Raw Material Software Limited"
 )
 
 option(
@@ -81,7 +81,7 @@ option(
 set_property(
   GLOBAL
   PROPERTY
-  JUCE_COPY_PLUGIN_AFTER_BUILD ${JUCE_COPY_PLUGIN_AFTER_BUILD}
+    JUCE_COPY_PLUGIN_AFTER_BUILD ${JUCE_COPY_PLUGIN_AFTER_BUILD}
 )
 
 set(CMAKE_CXX_EXTENSIONS FALSE)
@@ -137,7 +137,7 @@ endif()
 include(CMakePackageConfigHelpers)
 write_basic_package_version_file(
   "${JUCE_BINARY_DIR}/JUCEConfigVersion.cmake"
-  VERSION ${JUCE_VERSION}
+  VERSION       ${JUCE_VERSION}
   COMPATIBILITY ExactVersion ${extra_version_arg}
 )
 
@@ -171,39 +171,39 @@ configure_package_config_file(
   UTILS_INSTALL_DIR
   JUCEAIDE_PATH
   JUCE_MODULE_PATH
-  INSTALL_PREFIX "${CMAKE_CURRENT_BINARY_DIR}"
+  INSTALL_PREFIX      "${CMAKE_CURRENT_BINARY_DIR}"
   INSTALL_DESTINATION "${CMAKE_CURRENT_BINARY_DIR}"
 )
 
 install(
-  FILES
-  "${JUCE_BINARY_DIR}/JUCEConfigVersion.cmake"
-  "${JUCE_BINARY_DIR}/JUCEConfig.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/JUCECheckAtomic.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/JUCEHelperTargets.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/JUCEModuleSupport.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/JUCEUtils.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/JuceLV2Defines.h.in"
-  "${JUCE_CMAKE_UTILS_DIR}/LaunchScreen.storyboard"
-  "${JUCE_CMAKE_UTILS_DIR}/PIPAudioProcessor.cpp.in"
-  "${JUCE_CMAKE_UTILS_DIR}/PIPAudioProcessorWithARA.cpp.in"
-  "${JUCE_CMAKE_UTILS_DIR}/PIPComponent.cpp.in"
-  "${JUCE_CMAKE_UTILS_DIR}/PIPConsole.cpp.in"
-  "${JUCE_CMAKE_UTILS_DIR}/RecentFilesMenuTemplate.nib"
-  "${JUCE_CMAKE_UTILS_DIR}/UnityPluginGUIScript.cs.in"
-  "${JUCE_CMAKE_UTILS_DIR}/checkBundleSigning.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/copyDir.cmake"
-  "${JUCE_CMAKE_UTILS_DIR}/juce_runtime_arch_detection.cpp"
-  "${JUCE_CMAKE_UTILS_DIR}/juce_LinuxSubprocessHelper.cpp"
+  FILES       "${JUCE_BINARY_DIR}/JUCEConfigVersion.cmake"
+              "${JUCE_BINARY_DIR}/JUCEConfig.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/JUCECheckAtomic.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/JUCEHelperTargets.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/JUCEModuleSupport.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/JUCEUtils.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/JuceLV2Defines.h.in"
+              "${JUCE_CMAKE_UTILS_DIR}/LaunchScreen.storyboard"
+              "${JUCE_CMAKE_UTILS_DIR}/PIPAudioProcessor.cpp.in"
+              "${JUCE_CMAKE_UTILS_DIR}/PIPAudioProcessorWithARA.cpp.in"
+              "${JUCE_CMAKE_UTILS_DIR}/PIPComponent.cpp.in"
+              "${JUCE_CMAKE_UTILS_DIR}/PIPConsole.cpp.in"
+              "${JUCE_CMAKE_UTILS_DIR}/RecentFilesMenuTemplate.nib"
+              "${JUCE_CMAKE_UTILS_DIR}/UnityPluginGUIScript.cs.in"
+              "${JUCE_CMAKE_UTILS_DIR}/checkBundleSigning.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/copyDir.cmake"
+              "${JUCE_CMAKE_UTILS_DIR}/juce_runtime_arch_detection.cpp"
+              "${JUCE_CMAKE_UTILS_DIR}/juce_LinuxSubprocessHelper.cpp"
   DESTINATION "${JUCE_INSTALL_DESTINATION}"
 )
 
-if(("${CMAKE_SOURCE_DIR}" STREQUAL "${JUCE_SOURCE_DIR}") AND (NOT JUCE_BUILD_HELPER_TOOLS))
+if(("${CMAKE_SOURCE_DIR}" STREQUAL "${JUCE_SOURCE_DIR}")
+   AND (NOT JUCE_BUILD_HELPER_TOOLS))
   _juce_add_lv2_manifest_helper_target()
   
   if(TARGET juce_lv2_helper)
     install(
-      TARGETS juce_lv2_helper
+      TARGETS     juce_lv2_helper
       EXPORT
       LV2_HELPER
       DESTINATION "bin/JUCE-${JUCE_VERSION}"
@@ -211,7 +211,7 @@ if(("${CMAKE_SOURCE_DIR}" STREQUAL "${JUCE_SOURCE_DIR}") AND (NOT JUCE_BUILD_HEL
     install(
       EXPORT
       LV2_HELPER
-      NAMESPACE juce::
+      NAMESPACE   juce::
       DESTINATION "${JUCE_INSTALL_DESTINATION}"
     )
   endif()
@@ -220,7 +220,7 @@ if(("${CMAKE_SOURCE_DIR}" STREQUAL "${JUCE_SOURCE_DIR}") AND (NOT JUCE_BUILD_HEL
   
   if(TARGET juce_vst3_helper)
     install(
-      TARGETS juce_vst3_helper
+      TARGETS     juce_vst3_helper
       EXPORT
       VST3_HELPER
       DESTINATION "bin/JUCE-${JUCE_VERSION}"
@@ -228,7 +228,7 @@ if(("${CMAKE_SOURCE_DIR}" STREQUAL "${JUCE_SOURCE_DIR}") AND (NOT JUCE_BUILD_HEL
     install(
       EXPORT
       VST3_HELPER
-      NAMESPACE juce::
+      NAMESPACE   juce::
       DESTINATION "${JUCE_INSTALL_DESTINATION}"
     )
   endif()
@@ -1,5 +1,5 @@
 set_property(
   TARGET test
   PROPERTY
-  INTERFACE_LINK_LIBRARIES "${DIR}/lib64/libcublas_static.a"
+    INTERFACE_LINK_LIBRARIES "${DIR}/lib64/libcublas_static.a"
 )
\ No newline at end of file
@@ -221,7 +221,8 @@ endif()
 
 include(cmake/OpenCVMinDepVersions.cmake)
 
-if(CMAKE_SYSTEM_NAME MATCHES WindowsPhone OR CMAKE_SYSTEM_NAME MATCHES WindowsStore)
+if(CMAKE_SYSTEM_NAME MATCHES WindowsPhone
+   OR CMAKE_SYSTEM_NAME MATCHES WindowsStore)
   cmake_minimum_required(VERSION 3.1 FATAL_ERROR)
   #Required to resolve linker error issues due to incompatibility with CMake v3.0+ policies.
   #CMake fails to find _fseeko() which leads to subsequent linker error.
@@ -336,7 +337,7 @@ if(DEFINED CMAKE_BUILD_TYPE)
     CACHE
     CMAKE_BUILD_TYPE
     PROPERTY
-    STRINGS "${CMAKE_CONFIGURATION_TYPES}"
+      STRINGS "${CMAKE_CONFIGURATION_TYPES}"
   )
 endif()
 
@@ -1771,7 +1772,9 @@ OCV_OPTION(
   "Enable compiler options for fast math optimizations on FP computations (not recommended)"
   OFF
 )
-if(NOT IOS AND (NOT ANDROID OR OPENCV_ANDROID_USE_LEGACY_FLAGS) AND CMAKE_CROSSCOMPILING)# Use CPU_BASELINE instead
+if(NOT IOS
+   AND (NOT ANDROID OR OPENCV_ANDROID_USE_LEGACY_FLAGS)
+   AND CMAKE_CROSSCOMPILING)# Use CPU_BASELINE instead
   OCV_OPTION(
     ENABLE_NEON "Enable NEON instructions" (
     NEON
@@ -1985,7 +1988,10 @@ if(DEFINED CMAKE_DEBUG_POSTFIX)
   set(OPENCV_DEBUG_POSTFIX "${CMAKE_DEBUG_POSTFIX}")
 endif()
 
-if((INSTALL_CREATE_DISTRIB AND BUILD_SHARED_LIBS AND NOT DEFINED BUILD_opencv_world) OR APPLE_FRAMEWORK)
+if((INSTALL_CREATE_DISTRIB
+    AND BUILD_SHARED_LIBS
+    AND NOT DEFINED BUILD_opencv_world)
+   OR APPLE_FRAMEWORK)
   set(BUILD_opencv_world ON CACHE INTERNAL "")
 endif()
 
@@ -2044,7 +2050,10 @@ ocv_cmake_hook(POST_COMPILER_OPTIONS)
 # ----------------------------------------------------------------------------
 if(UNIX OR MINGW)
   if(NOT APPLE_FRAMEWORK OR OPENCV_ENABLE_PKG_CONFIG)
-    if(CMAKE_CROSSCOMPILING AND NOT DEFINED ENV{PKG_CONFIG_LIBDIR} AND NOT DEFINED ENV{PKG_CONFIG_SYSROOT_DIR} AND NOT OPENCV_ENABLE_PKG_CONFIG)
+    if(CMAKE_CROSSCOMPILING
+       AND NOT DEFINED ENV{PKG_CONFIG_LIBDIR}
+       AND NOT DEFINED ENV{PKG_CONFIG_SYSROOT_DIR}
+       AND NOT OPENCV_ENABLE_PKG_CONFIG)
       if(NOT PkgConfig_FOUND)
         message(
           STATUS
@@ -2210,22 +2219,25 @@ if(ENABLE_FLAKE8 AND PYTHON_DEFAULT_AVAILABLE)
     )
     add_custom_target(
       check_flake8
-      COMMAND
-      "${FLAKE8_EXECUTABLE}"
-      .
-      --count
-      --select=E9,E901,E999,F821,F822,F823
-      --show-source
-      --statistics
-      --exclude='${OPENCV_FLAKE8_EXCLUDES_STR}'
+      COMMAND           "${FLAKE8_EXECUTABLE}"
+                        .
+                        --count
+                        --select=E9,E901,E999,F821,F822,F823
+                        --show-source
+                        --statistics
+                        --exclude='${OPENCV_FLAKE8_EXCLUDES_STR}'
       WORKING_DIRECTORY "${OpenCV_SOURCE_DIR}"
-      COMMENT "Running flake8"
+      COMMENT           "Running flake8"
     )
   endif()
 endif()
 
 
-if(ANDROID AND ANDROID_EXECUTABLE AND ANT_EXECUTABLE AND (ANT_VERSION VERSION_GREATER 1.7) AND (ANDROID_TOOLS_Pkg_Revision GREATER 13))
+if(ANDROID
+   AND ANDROID_EXECUTABLE
+   AND ANT_EXECUTABLE
+   AND (ANT_VERSION VERSION_GREATER 1.7)
+   AND (ANDROID_TOOLS_Pkg_Revision GREATER 13))
   SET(CAN_BUILD_ANDROID_PROJECTS TRUE)
 else()
   SET(CAN_BUILD_ANDROID_PROJECTS FALSE)
@@ -2408,7 +2420,11 @@ if(BUILD_opencv_apps)
 endif()
 
 # examples
-if(BUILD_EXAMPLES OR BUILD_ANDROID_EXAMPLES OR INSTALL_ANDROID_EXAMPLES OR INSTALL_PYTHON_EXAMPLES OR INSTALL_C_EXAMPLES)
+if(BUILD_EXAMPLES
+   OR BUILD_ANDROID_EXAMPLES
+   OR INSTALL_ANDROID_EXAMPLES
+   OR INSTALL_PYTHON_EXAMPLES
+   OR INSTALL_C_EXAMPLES)
   add_subdirectory(samples)
 endif()
 
@@ -2450,9 +2466,9 @@ if(INSTALL_TESTS AND OPENCV_TEST_DATA_PATH)
       @ONLY
     )
     install(
-      PROGRAMS "${CMAKE_BINARY_DIR}/unix-install/opencv_run_all_tests.sh"
+      PROGRAMS    "${CMAKE_BINARY_DIR}/unix-install/opencv_run_all_tests.sh"
       DESTINATION ./
-      COMPONENT tests
+      COMPONENT   tests
     )
   elseif(WIN32)
     configure_file(
@@ -2461,9 +2477,9 @@ if(INSTALL_TESTS AND OPENCV_TEST_DATA_PATH)
       @ONLY
     )
     install(
-      PROGRAMS "${CMAKE_BINARY_DIR}/win-install/opencv_run_all_tests.cmd"
+      PROGRAMS    "${CMAKE_BINARY_DIR}/win-install/opencv_run_all_tests.cmd"
       DESTINATION ${OPENCV_TEST_INSTALL_PATH}
-      COMPONENT tests
+      COMPONENT   tests
     )
   elseif(UNIX)
     configure_file(
@@ -2472,9 +2488,9 @@ if(INSTALL_TESTS AND OPENCV_TEST_DATA_PATH)
       @ONLY
     )
     install(
-      PROGRAMS "${CMAKE_BINARY_DIR}/unix-install/opencv_run_all_tests.sh"
+      PROGRAMS    "${CMAKE_BINARY_DIR}/unix-install/opencv_run_all_tests.sh"
       DESTINATION ${OPENCV_TEST_INSTALL_PATH}
-      COMPONENT tests
+      COMPONENT   tests
     )
   endif()
 endif()
@@ -2495,25 +2511,25 @@ endif()
 # for UNIX it does not make sense as LICENSE and readme will be part of the package automatically
 if(ANDROID OR NOT UNIX)
   install(
-    FILES ${OPENCV_LICENSE_FILE}
+    FILES       ${OPENCV_LICENSE_FILE}
     PERMISSIONS
     OWNER_READ
     OWNER_WRITE
     GROUP_READ
     WORLD_READ
     DESTINATION ./
-    COMPONENT libs
+    COMPONENT   libs
   )
   if(OPENCV_README_FILE)
     install(
-      FILES ${OPENCV_README_FILE}
+      FILES       ${OPENCV_README_FILE}
       PERMISSIONS
       OWNER_READ
       OWNER_WRITE
       GROUP_READ
       WORLD_READ
       DESTINATION ./
-      COMPONENT libs
+      COMPONENT   libs
     )
   endif()
 endif()
@@ -2718,7 +2734,8 @@ status("")
 status("  OpenCV modules:")
 set(OPENCV_MODULES_BUILD_ST "")
 foreach(the_module ${OPENCV_MODULES_BUILD})
-  if(NOT OPENCV_MODULE_${the_module}_CLASS STREQUAL "INTERNAL" OR the_module STREQUAL "opencv_ts")
+  if(NOT OPENCV_MODULE_${the_module}_CLASS STREQUAL "INTERNAL"
+     OR the_module STREQUAL "opencv_ts")
     list(APPEND OPENCV_MODULES_BUILD_ST "${the_module}")
   endif()
 endforeach()
@@ -3296,7 +3313,11 @@ if(OPENCV_DISABLE_THREAD_SUPPORT)
     ""
     "Multi thread code explicitly disabled with OPENCV_DISABLE_THREAD_SUPPORT."
   )
-  if(HAVE_PTHREADS_PF OR HAVE_HPX OR HAVE_OPENMP OR HAVE_GCD OR HAVE_CONCURRENCY)
+  if(HAVE_PTHREADS_PF
+     OR HAVE_HPX
+     OR HAVE_OPENMP
+     OR HAVE_GCD
+     OR HAVE_CONCURRENCY)
     message(
       FATAL_ERROR
       "Not all parallel frameworks have been disabled (using ${parallel_status})."
@@ -3369,7 +3390,8 @@ if(WITH_HALIDE OR HAVE_HALIDE)
   )
 endif()
 
-if(HAVE_OPENVINO OR (WITH_OPENVINO AND NOT WITH_INF_ENGINE AND NOT INF_ENGINE_TARGET))
+if(HAVE_OPENVINO
+   OR (WITH_OPENVINO AND NOT WITH_INF_ENGINE AND NOT INF_ENGINE_TARGET))
   status(
     "    OpenVINO:"
     TARGET openvino::runtime
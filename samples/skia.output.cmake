@@ -1153,15 +1153,14 @@ if(NOT ARM)
     set_source_files_properties(
       third_party/skcms/skcms.cc
       PROPERTIES
-      COMPILE_DEFINITIONS
-      SKCMS_PORTABLE
+        COMPILE_DEFINITIONS SKCMS_PORTABLE
     )
   endif()
   if((NOT MSVC) OR (${CMAKE_CXX_COMPILER_ID} MATCHES "Clang"))
     set_source_files_properties(
       src/core/SkCpu.cpp
       PROPERTIES
-      COMPILE_FLAGS -mavx
+        COMPILE_FLAGS -mavx
     )
   endif()
 elseif(ARM)
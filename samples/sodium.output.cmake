@@ -101,8 +101,8 @@ add_library(${PROJECT_NAME} ${sodium_headers} ${sodium_sources})
 set_target_properties(
   ${PROJECT_NAME}
   PROPERTIES
-  PREFIX ""
-  OUTPUT_NAME "lib${PROJECT_NAME}"
+    PREFIX      ""
+    OUTPUT_NAME "lib${PROJECT_NAME}"
 )
 
 test_big_endian(IS_BIG_ENDIAN)
@@ -203,7 +203,8 @@ else()
     -Wl,-z,noexecstack
   )
   
-  if(CMAKE_C_COMPILER_ID STREQUAL "Clang" OR CMAKE_C_COMPILER_ID STREQUAL "AppleClang")
+  if(CMAKE_C_COMPILER_ID STREQUAL "Clang"
+     OR CMAKE_C_COMPILER_ID STREQUAL "AppleClang")
     target_compile_options(
       ${PROJECT_NAME}
       PRIVATE -Wno-unknown-warning-option -Wshorten-64-to-32
@@ -680,14 +681,13 @@ if(BUILD_TESTING)
     target_link_libraries(${test_name} PRIVATE ${PROJECT_NAME})
     
     add_custom_command(
-      TARGET ${test_name}
+      TARGET  ${test_name}
       POST_BUILD
-      COMMAND
-      ${CMAKE_COMMAND}
-      -E
-      copy_if_different
-      "${CMAKE_CURRENT_LIST_DIR}/test/default/${test_name}.exp"
-      $<TARGET_FILE_DIR:${test_name}>
+      COMMAND ${CMAKE_COMMAND}
+              -E
+              copy_if_different
+              "${CMAKE_CURRENT_LIST_DIR}/test/default/${test_name}.exp"
+              $<TARGET_FILE_DIR:${test_name}>
     )
     
     add_test(
@@ -699,21 +699,21 @@ if(BUILD_TESTING)
 endif()
 
 install(
-  DIRECTORY src/libsodium/include/
+  DIRECTORY   src/libsodium/include/
   DESTINATION include/
   USE_SOURCE_PERMISSIONS
-  PATTERN "*.h"
-  PATTERN "*.h.in"
+  PATTERN     "*.h"
+  PATTERN     "*.h.in"
   EXCLUDE
-  REGEX "private($|/)"
+  REGEX       "private($|/)"
   EXCLUDE
 )
 
 install(FILES ${CMAKE_BINARY_DIR}/sodium/version.h DESTINATION include/sodium)
 
 install(
-  TARGETS ${PROJECT_NAME}
-  EXPORT ${PROJECT_NAME}-targets
+  TARGETS     ${PROJECT_NAME}
+  EXPORT      ${PROJECT_NAME}-targets
   ARCHIVE
   DESTINATION ${CMAKE_INSTALL_LIBDIR}
   LIBRARY
@@ -725,9 +725,9 @@ install(
 )
 
 install(
-  EXPORT ${PROJECT_NAME}-targets
-  FILE unofficial-${PROJECT_NAME}Targets.cmake
-  NAMESPACE unofficial-${PROJECT_NAME}::
+  EXPORT      ${PROJECT_NAME}-targets
+  FILE        unofficial-${PROJECT_NAME}Targets.cmake
+  NAMESPACE   unofficial-${PROJECT_NAME}::
   DESTINATION ${CMAKE_INSTALL_LIBDIR}/cmake/unofficial-${PROJECT_NAME}
 )
 
@@ -741,16 +741,15 @@ configure_package_config_file(
 
 write_basic_package_version_file(
   ${CMAKE_CURRENT_BINARY_DIR}/unofficial-${PROJECT_NAME}ConfigVersion.cmake
-  VERSION ${VERSION}
+  VERSION       ${VERSION}
   COMPATIBILITY AnyNewerVersion
 )
 
 unset(TARGET_NAME)
 
 install(
-  FILES
-  ${CMAKE_CURRENT_BINARY_DIR}/unofficial-${PROJECT_NAME}Config.cmake
-  ${CMAKE_CURRENT_BINARY_DIR}/unofficial-${PROJECT_NAME}ConfigVersion.cmake
+  FILES       ${CMAKE_CURRENT_BINARY_DIR}/unofficial-${PROJECT_NAME}Config.cmake
+              ${CMAKE_CURRENT_BINARY_DIR}/unofficial-${PROJECT_NAME}ConfigVersion.cmake
   DESTINATION ${CMAKE_INSTALL_LIBDIR}/cmake/unofficial-${PROJECT_NAME}
 )
 
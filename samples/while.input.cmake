@@ -0,0 +1,8 @@
+set(i 0)
+WHILE (i LESS 10 AND NOT DEFINED STOP)
+  math(EXPR i "${i} + 1")
+if(i EQUAL 5)
+continue()
+endif()
+   break()
+ENDWHILE()
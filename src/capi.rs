@@ -0,0 +1,478 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! A C interface to the formatter, built with the `capi` feature. The
+//! matching declarations are in `include/cmakefmt.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/cmakefmt.h`.
+
+#![allow(non_camel_case_types)]
+
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::format::{format_with_diagnostics, FormatOptions};
+use crate::parser::diagnostics::{Diagnostic, Severity};
+use crate::parser::types::{Position, Span};
+use crate::pretty_printer::style::{CommandCase, ListLayout, Preset};
+
+/// The outcome of `cmakefmt_format`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum cmakefmt_status {
+    CMAKEFMT_OK = 0,
+    /// Some statements failed to parse. The output keeps them as they were
+    /// and the diagnostics say why.
+    CMAKEFMT_PARSE_ERROR = 1,
+    /// The source isn't UTF-8. There is no output and a single diagnostic
+    /// points at the first invalid byte.
+    CMAKEFMT_INVALID_UTF8 = 2,
+    /// A required pointer was null, or a setting isn't one of its values.
+    CMAKEFMT_INVALID_ARGUMENT = 3,
+    /// The formatter panicked. Please report it.
+    CMAKEFMT_INTERNAL_ERROR = 4,
+}
+
+/// A style preset to start from, see `cmakefmt_options_init`.
+///
+/// Settings taking one of these values are `uint32_t`, so that a value
+/// missing from the enum can be rejected instead of being undefined
+/// behaviour.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum cmakefmt_preset {
+    CMAKEFMT_PRESET_DEFAULT = 0,
+    CMAKEFMT_PRESET_CMAKE_FORMAT = 1,
+    CMAKEFMT_PRESET_GERSEMI_LIKE = 2,
+    CMAKEFMT_PRESET_COMPACT = 3,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum cmakefmt_command_case {
+    CMAKEFMT_COMMAND_CASE_UNCHANGED = 0,
+    CMAKEFMT_COMMAND_CASE_LOWER = 1,
+    CMAKEFMT_COMMAND_CASE_UPPER = 2,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum cmakefmt_list_layout {
+    CMAKEFMT_LIST_LAYOUT_ONE_PER_LINE = 0,
+    CMAKEFMT_LIST_LAYOUT_FILL = 1,
+    CMAKEFMT_LIST_LAYOUT_AUTO = 2,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum cmakefmt_severity {
+    CMAKEFMT_SEVERITY_ERROR = 0,
+    CMAKEFMT_SEVERITY_WARNING = 1,
+}
+
+/// Formatting settings. Fill them with `cmakefmt_options_init` and change
+/// the fields of interest; per-command settings aren't available here.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct cmakefmt_options {
+    /// The column limit.
+    pub width: usize,
+    /// Number of spaces used for each indentation level.
+    pub indent: usize,
+    /// Put the closing parenthesis of a broken command on its own line.
+    pub dangle_parens: bool,
+    /// One of `cmakefmt_command_case`.
+    pub command_case: u32,
+    /// Group upper-case keywords with the arguments that follow them.
+    pub keyword_grouping: bool,
+    /// Consecutive blank lines above this are removed.
+    pub max_blank_lines: usize,
+    /// One of `cmakefmt_list_layout`.
+    pub list_layout: u32,
+}
+
+/// A location in the source. Lines and columns start at 1 and columns count
+/// characters.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct cmakefmt_position {
+    /// Byte offset from the start of the source.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct cmakefmt_span {
+    pub start: cmakefmt_position,
+    pub end: cmakefmt_position,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct cmakefmt_diagnostic {
+    pub severity: cmakefmt_severity,
+    /// NUL-terminated message.
+    pub message: *mut c_char,
+    pub span: cmakefmt_span,
+}
+
+/// What `cmakefmt_format` produced. Release it with `cmakefmt_result_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct cmakefmt_result {
+    /// The formatted source, NUL-terminated, or null when there is none.
+    pub output: *mut c_char,
+    /// Length of `output` in bytes, without the terminator.
+    pub output_len: usize,
+    pub diagnostics: *mut cmakefmt_diagnostic,
+    pub diagnostics_len: usize,
+}
+
+impl cmakefmt_result {
+    fn empty() -> Self {
+        cmakefmt_result {
+            output: ptr::null_mut(),
+            output_len: 0,
+            diagnostics: ptr::null_mut(),
+            diagnostics_len: 0,
+        }
+    }
+}
+
+impl cmakefmt_preset {
+    fn from_c(value: u32) -> Option<Preset> {
+        use cmakefmt_preset::*;
+        match value {
+            v if v == CMAKEFMT_PRESET_DEFAULT as u32 => Some(Preset::Default),
+            v if v == CMAKEFMT_PRESET_CMAKE_FORMAT as u32 => Some(Preset::CmakeFormat),
+            v if v == CMAKEFMT_PRESET_GERSEMI_LIKE as u32 => Some(Preset::GersemiLike),
+            v if v == CMAKEFMT_PRESET_COMPACT as u32 => Some(Preset::Compact),
+            _ => None,
+        }
+    }
+}
+
+impl cmakefmt_command_case {
+    fn from_c(value: u32) -> Option<CommandCase> {
+        use cmakefmt_command_case::*;
+        match value {
+            v if v == CMAKEFMT_COMMAND_CASE_UNCHANGED as u32 => Some(CommandCase::Unchanged),
+            v if v == CMAKEFMT_COMMAND_CASE_LOWER as u32 => Some(CommandCase::Lower),
+            v if v == CMAKEFMT_COMMAND_CASE_UPPER as u32 => Some(CommandCase::Upper),
+            _ => None,
+        }
+    }
+}
+
+impl cmakefmt_list_layout {
+    fn from_c(value: u32) -> Option<ListLayout> {
+        use cmakefmt_list_layout::*;
+        match value {
+            v if v == CMAKEFMT_LIST_LAYOUT_ONE_PER_LINE as u32 => Some(ListLayout::OnePerLine),
+            v if v == CMAKEFMT_LIST_LAYOUT_FILL as u32 => Some(ListLayout::Fill),
+            v if v == CMAKEFMT_LIST_LAYOUT_AUTO as u32 => Some(ListLayout::Auto),
+            _ => None,
+        }
+    }
+}
+
+impl From<&FormatOptions> for cmakefmt_options {
+    fn from(options: &FormatOptions) -> Self {
+        let style = &options.style;
+        cmakefmt_options {
+            width: options.width,
+            indent: style.indent,
+            dangle_parens: style.dangle_parens,
+            command_case: match style.command_case {
+                CommandCase::Unchanged => cmakefmt_command_case::CMAKEFMT_COMMAND_CASE_UNCHANGED,
+                CommandCase::Lower => cmakefmt_command_case::CMAKEFMT_COMMAND_CASE_LOWER,
+                CommandCase::Upper => cmakefmt_command_case::CMAKEFMT_COMMAND_CASE_UPPER,
+            } as u32,
+            keyword_grouping: style.keyword_grouping,
+            max_blank_lines: style.max_blank_lines,
+            list_layout: match style.list_layout {
+                ListLayout::OnePerLine => cmakefmt_list_layout::CMAKEFMT_LIST_LAYOUT_ONE_PER_LINE,
+                ListLayout::Fill => cmakefmt_list_layout::CMAKEFMT_LIST_LAYOUT_FILL,
+                ListLayout::Auto => cmakefmt_list_layout::CMAKEFMT_LIST_LAYOUT_AUTO,
+            } as u32,
+        }
+    }
+}
+
+impl TryFrom<&cmakefmt_options> for FormatOptions {
+    type Error = cmakefmt_status;
+
+    fn try_from(options: &cmakefmt_options) -> Result<Self, cmakefmt_status> {
+        let invalid = cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT;
+        let mut format_options = FormatOptions {
+            width: options.width,
+            ..FormatOptions::default()
+        };
+        let style = &mut format_options.style;
+        style.indent = options.indent;
+        style.dangle_parens = options.dangle_parens;
+        style.command_case = cmakefmt_command_case::from_c(options.command_case).ok_or(invalid)?;
+        style.keyword_grouping = options.keyword_grouping;
+        style.max_blank_lines = options.max_blank_lines;
+        style.list_layout = cmakefmt_list_layout::from_c(options.list_layout).ok_or(invalid)?;
+        Ok(format_options)
+    }
+}
+
+impl From<Position> for cmakefmt_position {
+    fn from(position: Position) -> Self {
+        cmakefmt_position {
+            offset: position.offset,
+            line: position.line,
+            column: position.column,
+        }
+    }
+}
+
+impl From<Span> for cmakefmt_span {
+    fn from(span: Span) -> Self {
+        cmakefmt_span {
+            start: span.start.into(),
+            end: span.end.into(),
+        }
+    }
+}
+
+impl From<Diagnostic> for cmakefmt_diagnostic {
+    fn from(diagnostic: Diagnostic) -> Self {
+        cmakefmt_diagnostic {
+            severity: match diagnostic.severity {
+                Severity::Error => cmakefmt_severity::CMAKEFMT_SEVERITY_ERROR,
+                Severity::Warning => cmakefmt_severity::CMAKEFMT_SEVERITY_WARNING,
+            },
+            message: CString::new(diagnostic.message.replace('\0', ""))
+                .expect("NULs were removed")
+                .into_raw(),
+            span: diagnostic.span.into(),
+        }
+    }
+}
+
+/// Moves `text` to a NUL-terminated buffer released by `free_c_string`.
+/// Unlike `CString`, this keeps interior NULs, so the length is returned as
+/// well.
+fn into_c_string(text: String) -> (*mut c_char, usize) {
+    let len = text.len();
+    let mut bytes = text.into_bytes();
+    bytes.push(0);
+    (Box::into_raw(bytes.into_boxed_slice()) as *mut c_char, len)
+}
+
+/// # Safety
+///
+/// `text` must come from `into_c_string` with the same `len`, or be null.
+unsafe fn free_c_string(text: *mut c_char, len: usize) {
+    if !text.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            text as *mut u8,
+            len + 1,
+        )));
+    }
+}
+
+/// The position just after `text`.
+fn end_position(text: &str) -> Position {
+    let line_start = text.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        offset: text.len(),
+        line: text.matches('\n').count() + 1,
+        column: text[line_start..].chars().count() + 1,
+    }
+}
+
+fn format(source: &[u8], options: &FormatOptions, result: &mut cmakefmt_result) -> cmakefmt_status {
+    let (output, status, diagnostics) = match std::str::from_utf8(source) {
+        Ok(source) => {
+            let formatted = format_with_diagnostics(source, options);
+            let status = if formatted.has_errors() {
+                cmakefmt_status::CMAKEFMT_PARSE_ERROR
+            } else {
+                cmakefmt_status::CMAKEFMT_OK
+            };
+            (Some(formatted.output), status, formatted.diagnostics)
+        }
+        Err(error) => {
+            let valid = &source[..error.valid_up_to()];
+            let start = end_position(std::str::from_utf8(valid).expect("checked above"));
+            let end = Position {
+                offset: start.offset + error.error_len().unwrap_or(source.len() - valid.len()),
+                ..start
+            };
+            let diagnostic = Diagnostic::error("source is not valid UTF-8", Span::new(start, end));
+            (
+                None,
+                cmakefmt_status::CMAKEFMT_INVALID_UTF8,
+                vec![diagnostic],
+            )
+        }
+    };
+    if let Some(output) = output {
+        (result.output, result.output_len) = into_c_string(output);
+    }
+    let diagnostics: Box<[cmakefmt_diagnostic]> = diagnostics.into_iter().map(Into::into).collect();
+    result.diagnostics_len = diagnostics.len();
+    result.diagnostics = Box::into_raw(diagnostics) as *mut cmakefmt_diagnostic;
+    status
+}
+
+/// Fills `options` with the settings of `preset`, one of `cmakefmt_preset`,
+/// and a column limit of 80.
+///
+/// # Safety
+///
+/// `options` must be null or point to writable memory for a
+/// `cmakefmt_options`.
+#[no_mangle]
+pub unsafe extern "C" fn cmakefmt_options_init(
+    options: *mut cmakefmt_options,
+    preset: u32,
+) -> cmakefmt_status {
+    let Some(preset) = cmakefmt_preset::from_c(preset) else {
+        return cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT;
+    };
+    if options.is_null() {
+        return cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT;
+    }
+    let format_options = FormatOptions {
+        style: preset.style(),
+        ..FormatOptions::default()
+    };
+    options.write((&format_options).into());
+    cmakefmt_status::CMAKEFMT_OK
+}
+
+/// Formats the `source_len` bytes at `source` into `result`, using the
+/// default settings when `options` is null. Settings out of range make it
+/// return `CMAKEFMT_INVALID_ARGUMENT`. Statements that fail to parse
+/// are kept as they were and reported in the diagnostics, along with any
+/// warnings. `result` must be released with `cmakefmt_result_free`, whatever
+/// the returned status.
+///
+/// # Safety
+///
+/// `source` must point to `source_len` readable bytes; it may be null when
+/// `source_len` is 0. `options` must be null or point to a valid
+/// `cmakefmt_options`. `result` must point to writable memory for a
+/// `cmakefmt_result`.
+#[no_mangle]
+pub unsafe extern "C" fn cmakefmt_format(
+    source: *const c_char,
+    source_len: usize,
+    options: *const cmakefmt_options,
+    result: *mut cmakefmt_result,
+) -> cmakefmt_status {
+    if result.is_null() {
+        return cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT;
+    }
+    result.write(cmakefmt_result::empty());
+    if source.is_null() && source_len > 0 {
+        return cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT;
+    }
+    let source = if source_len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(source as *const u8, source_len)
+    };
+    let options = match options.as_ref().map(FormatOptions::try_from) {
+        Some(Ok(options)) => options,
+        Some(Err(status)) => return status,
+        None => FormatOptions::default(),
+    };
+    let result = &mut *result;
+    // Unwinding into C is undefined behaviour
+    catch_unwind(AssertUnwindSafe(|| format(source, &options, result)))
+        .unwrap_or(cmakefmt_status::CMAKEFMT_INTERNAL_ERROR)
+}
+
+/// Releases everything `cmakefmt_format` stored in `result` and clears it.
+///
+/// # Safety
+///
+/// `result` must be null or have been filled by `cmakefmt_format`, and not
+/// released since.
+#[no_mangle]
+pub unsafe extern "C" fn cmakefmt_result_free(result: *mut cmakefmt_result) {
+    let Some(result) = result.as_mut() else {
+        return;
+    };
+    free_c_string(result.output, result.output_len);
+    if !result.diagnostics.is_null() {
+        let diagnostics = Box::from_raw(ptr::slice_from_raw_parts_mut(
+            result.diagnostics,
+            result.diagnostics_len,
+        ));
+        for diagnostic in diagnostics.iter() {
+            drop(CString::from_raw(diagnostic.message));
+        }
+    }
+    *result = cmakefmt_result::empty();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_end_position() {
+        let position = end_position("set(x)\n# é\n  ");
+        assert_eq!(position.offset, 14);
+        assert_eq!(position.line, 3);
+        assert_eq!(position.column, 3);
+
+        let position = end_position("# é");
+        assert_eq!((position.line, position.column), (1, 4));
+    }
+
+    #[test]
+    fn test_options_round_trip() {
+        let mut options = FormatOptions::default();
+        options.style.command_case = CommandCase::Upper;
+        options.style.list_layout = ListLayout::Fill;
+        let c_options = cmakefmt_options::from(&options);
+        let back = FormatOptions::try_from(&c_options).unwrap();
+        assert_eq!(back.style.command_case, CommandCase::Upper);
+        assert_eq!(back.style.list_layout, ListLayout::Fill);
+
+        for c_options in [
+            cmakefmt_options {
+                command_case: 3,
+                ..c_options
+            },
+            cmakefmt_options {
+                list_layout: u32::MAX,
+                ..c_options
+            },
+        ] {
+            assert_eq!(
+                FormatOptions::try_from(&c_options).err(),
+                Some(cmakefmt_status::CMAKEFMT_INVALID_ARGUMENT)
+            );
+        }
+    }
+}
@@ -0,0 +1,266 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! The `.cmakefmt.toml` configuration file.
+//!
+//! Every setting is optional. A style is resolved by starting from the
+//! selected preset and then applying each setting that is present.
+//! Per-command settings go in `[command.<name>]` tables.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::pretty_printer::style::{CommandCase, CommandStyle, ListLayout, Preset, Style};
+
+pub const CONFIG_FILE_NAME: &str = ".cmakefmt.toml";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub style: Option<Preset>,
+    pub max_width: Option<usize>,
+    pub indent: Option<usize>,
+    pub dangle_parens: Option<bool>,
+    pub command_case: Option<CommandCase>,
+    pub keyword_grouping: Option<bool>,
+    pub max_blank_lines: Option<usize>,
+    pub list_layout: Option<ListLayout>,
+    pub command: HashMap<String, CommandStyle>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "failed to read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => {
+                write!(f, "failed to parse {}: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    pub fn parse(path: &Path, contents: &str) -> Result<Config, ConfigError> {
+        toml::from_str(contents).map_err(|err| ConfigError::Parse(path.to_path_buf(), err))
+    }
+
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        Config::parse(path, &contents)
+    }
+
+    /// Looks for a configuration file in `start` and each of its parents.
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    /// Returns a config with the settings of `other` taking precedence over
+    /// the settings of `self`. Command names are matched case-insensitively.
+    pub fn merge(self, other: Config) -> Config {
+        let mut command: HashMap<String, CommandStyle> = HashMap::new();
+        for (name, command_style) in self.command.into_iter().chain(other.command) {
+            let entry = command.entry(name.to_lowercase()).or_default();
+            *entry = entry.clone().merge(command_style);
+        }
+        Config {
+            style: other.style.or(self.style),
+            max_width: other.max_width.or(self.max_width),
+            indent: other.indent.or(self.indent),
+            dangle_parens: other.dangle_parens.or(self.dangle_parens),
+            command_case: other.command_case.or(self.command_case),
+            keyword_grouping: other.keyword_grouping.or(self.keyword_grouping),
+            max_blank_lines: other.max_blank_lines.or(self.max_blank_lines),
+            list_layout: other.list_layout.or(self.list_layout),
+            command,
+        }
+    }
+
+    pub fn to_style(&self) -> Style {
+        let mut style = self.style.unwrap_or_default().style();
+        if let Some(indent) = self.indent {
+            style.indent = indent;
+        }
+        if let Some(dangle_parens) = self.dangle_parens {
+            style.dangle_parens = dangle_parens;
+        }
+        if let Some(command_case) = self.command_case {
+            style.command_case = command_case;
+        }
+        if let Some(keyword_grouping) = self.keyword_grouping {
+            style.keyword_grouping = keyword_grouping;
+        }
+        if let Some(max_blank_lines) = self.max_blank_lines {
+            style.max_blank_lines = max_blank_lines;
+        }
+        if let Some(list_layout) = self.list_layout {
+            style.list_layout = list_layout;
+        }
+        for (name, command) in self.command.iter() {
+            let command_style = style.command_style_mut(name);
+            *command_style = command_style.clone().merge(command.clone());
+        }
+        style
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            Path::new(CONFIG_FILE_NAME),
+            r#"
+style = "gersemi-like"
+max_width = 100
+dangle_parens = false
+list_layout = "fill"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                style: Some(Preset::GersemiLike),
+                max_width: Some(100),
+                dangle_parens: Some(false),
+                list_layout: Some(ListLayout::Fill),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_per_command_config() {
+        let config = Config::parse(
+            Path::new(CONFIG_FILE_NAME),
+            r#"
+[command.target_sources]
+layout = "one_per_line"
+
+[command.message]
+never_break = true
+
+[command.set]
+keyword_grouping = false
+
+[command.my_add_library]
+align_keywords = true
+            "#,
+        )
+        .unwrap();
+        let style = config.to_style();
+        assert_eq!(
+            style.list_layout_for("target_sources"),
+            ListLayout::OnePerLine
+        );
+        assert!(style.never_break_for("MESSAGE"));
+        assert!(!style.never_break_for("set"));
+        assert!(!style.keyword_grouping_for("set"));
+        assert!(style.keyword_grouping_for("message"));
+        assert!(style.command_spec_for("my_add_library").align_keywords);
+    }
+
+    #[test]
+    fn test_parse_config_rejects_unknown_settings() {
+        let result = Config::parse(Path::new(CONFIG_FILE_NAME), "indentation = 4");
+        assert!(matches!(result, Err(ConfigError::Parse(_, _))));
+    }
+
+    #[test]
+    fn test_settings_override_the_preset() {
+        let config = Config {
+            style: Some(Preset::GersemiLike),
+            indent: Some(3),
+            ..Default::default()
+        };
+        let style = config.to_style();
+        assert_eq!(
+            style,
+            Style {
+                indent: 3,
+                ..Preset::GersemiLike.style()
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_prefers_the_other_config() {
+        let file = Config {
+            style: Some(Preset::Compact),
+            indent: Some(4),
+            ..Default::default()
+        };
+        let cli = Config {
+            style: Some(Preset::CmakeFormat),
+            ..Default::default()
+        };
+        let merged = file.merge(cli);
+        assert_eq!(merged.style, Some(Preset::CmakeFormat));
+        assert_eq!(merged.indent, Some(4));
+
+        let file = Config {
+            command: HashMap::from([(
+                String::from("message"),
+                CommandStyle {
+                    never_break: Some(true),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let cli = Config {
+            command: HashMap::from([(
+                String::from("message"),
+                CommandStyle {
+                    list_layout: Some(ListLayout::Fill),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let merged = file.merge(cli);
+        assert_eq!(
+            merged.command["message"],
+            CommandStyle {
+                list_layout: Some(ListLayout::Fill),
+                never_break: Some(true),
+                ..Default::default()
+            }
+        );
+    }
+}
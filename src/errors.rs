@@ -20,110 +20,231 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-use std::error::Error;
+//! Renders diagnostics as snippets of the source text, colored with the
+//! `color` feature.
 
+use std::fmt::Write;
+
+#[cfg(feature = "color")]
 use colored::{ColoredString, Colorize};
-use nom_supreme::error::{BaseErrorKind, ErrorTree, StackContext};
-use nom_supreme::final_parser::Location;
-
-fn print_alternative(file_path: &str, input_file: &str, errors: &[ErrorTree<Location>]) {
-    eprintln!("tried alternatives:\n");
-    for (i, error) in errors.iter().enumerate() {
-        eprintln!("======================================================================");
-        eprintln!("alternative {}:", i + 1);
-        print_error(file_path, input_file, &error);
-        eprintln!();
-    }
-}
+#[cfg(not(feature = "color"))]
+use plain::{ColoredString, Colorize};
 
-fn print_stack(
-    file_path: &str,
-    input_file: &str,
-    base: &ErrorTree<Location>,
-    contexts: &[(Location, StackContext<&str>)],
-) {
-    if contexts.len() > 1 {
-        eprintln!(
-            "base error for STACK len={}: ===========================",
-            contexts.len()
-        );
-    }
-    print_error(file_path, input_file, base);
-    for context in contexts.iter() {
-        let (location, context) = context;
-        match context {
-            StackContext::Kind(err) => {
-                let message = format!(
-                    "{}: {}",
-                    "error_kind".bright_yellow(),
-                    format!("{:?}", err).red().bold()
-                );
-                print_message_at_location(file_path, input_file, location, &message);
+use crate::parser::diagnostics::{Diagnostic, Severity};
+use crate::parser::types::Position;
+
+/// Stands in for `colored` without the `color` feature, leaving text as is.
+#[cfg(not(feature = "color"))]
+mod plain {
+    pub type ColoredString = String;
+
+    macro_rules! colors {
+        ($($color:ident),*) => {
+            pub trait Colorize {
+                $(fn $color(&self) -> ColoredString;)*
             }
-            StackContext::Context(context) => {
-                let message = format!("{}: {}", "context".bright_yellow(), context.cyan().bold());
-                print_message_at_location(file_path, input_file, location, &message);
+
+            impl Colorize for str {
+                $(fn $color(&self) -> ColoredString {
+                    self.to_string()
+                })*
             }
-        }
-        eprintln!("  ---> stack error: {}:{}", location.line, location.column);
+        };
     }
+
+    colors!(
+        red,
+        yellow,
+        cyan,
+        white,
+        bright_red,
+        bright_yellow,
+        bright_purple,
+        bold
+    );
 }
 
-fn format_error(error: &BaseErrorKind<&str, Box<dyn Error + Send + Sync>>) -> ColoredString {
-    format!("{}", error).bright_yellow()
+/// A marker under a position in a snippet.
+struct Marker {
+    position: Position,
+    marker: ColoredString,
+    message: String,
 }
 
-fn print_message_at_location(
+/// Writes the lines around the first of `markers`, with every marker on
+/// those lines under its position. Returns the markers that weren't shown.
+fn write_snippet(
+    output: &mut String,
     file_path: &str,
-    input_file: &str,
-    location: &Location,
-    message: &str,
-) {
-    let lines = input_file.lines();
-    let start = if location.line > 3 {
-        location.line - 3
-    } else {
-        0
-    };
-    let lines = lines.enumerate().skip(start).map(|(i, l)| (i + 1, l));
-    eprintln!("  ---> {}:{}:{}", file_path, location.line, location.column);
-    eprintln!("{}", "      | ".bright_purple());
-    for (line_num, line) in lines.take(6) {
-        eprintln!(
+    source: &str,
+    markers: Vec<Marker>,
+) -> Vec<Marker> {
+    let position = markers[0].position;
+    let start = position.line.saturating_sub(3);
+    let mut lines: Vec<&str> = source.lines().collect();
+    // A position at the end of a file that ends with a newline is on the
+    // line after the last one
+    let after_last = lines.len() + 1;
+    if markers
+        .iter()
+        .any(|marker| marker.position.line == after_last)
+    {
+        lines.push("");
+    }
+    let lines: Vec<(usize, &str)> = lines
+        .into_iter()
+        .enumerate()
+        .skip(start)
+        .take(6)
+        .map(|(i, l)| (i + 1, l))
+        .collect();
+    let _ = writeln!(
+        output,
+        "  ---> {}:{}:{}",
+        file_path, position.line, position.column
+    );
+    let _ = writeln!(output, "{}", "      | ".bright_purple());
+    for &(line_num, line) in &lines {
+        let _ = writeln!(
+            output,
             "{} {}",
             format!("{:05} |", line_num.to_string()).bright_purple(),
-            if line_num == location.line {
+            if line_num == position.line {
                 line.bright_red()
             } else {
                 line.white()
             }
         );
-        if line_num == location.line {
-            eprint!("{}", "      | ".bright_purple());
-            for _ in 0..location.column - 1 {
-                eprint!(" ");
-            }
-            eprint!("{}", "^".yellow());
-            eprint!(" ");
-            eprint!("{} ({}:{})", message, location.line, location.column);
-            eprint!("\n");
+        for marker in markers
+            .iter()
+            .filter(|marker| marker.position.line == line_num)
+        {
+            let _ = writeln!(
+                output,
+                "{}{}{} {} ({}:{})",
+                "      | ".bright_purple(),
+                " ".repeat(marker.position.column - 1),
+                marker.marker,
+                marker.message,
+                marker.position.line,
+                marker.position.column
+            );
         }
     }
-    eprintln!("{}", "      | ".bright_purple());
+    let _ = writeln!(output, "{}", "      | ".bright_purple());
+    // The first marker is dropped even if its line is past the end of
+    // `source`, so that every call makes progress
+    markers
+        .into_iter()
+        .skip(1)
+        .filter(|marker| {
+            !lines
+                .iter()
+                .any(|(line_num, _)| *line_num == marker.position.line)
+        })
+        .collect()
 }
 
-pub fn print_error(file_path: &str, input_file: &str, error_tree: &ErrorTree<Location>) {
-    match error_tree {
-        ErrorTree::Base { location, kind } => {
-            let error_message = format_error(kind);
-            let message = format!("{}: {}", "error".yellow(), error_message);
-            print_message_at_location(file_path, input_file, location, &message);
-        }
-        ErrorTree::Stack { base, contexts } => {
-            print_stack(file_path, input_file, &*base, contexts);
-        }
-        ErrorTree::Alt(errors) => {
-            print_alternative(file_path, input_file, errors);
-        }
+/// Renders `diagnostic`, which refers to `source` read from `file_path`.
+pub fn render_diagnostic(file_path: &str, source: &str, diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error".red(),
+        Severity::Warning => "warning".yellow(),
+    };
+    let mut markers = vec![Marker {
+        position: diagnostic.span.start,
+        marker: "^".yellow(),
+        message: format!("{}: {}", severity, diagnostic.message.bright_yellow()),
+    }];
+    markers.extend(diagnostic.labels.iter().map(|label| Marker {
+        position: label.span.start,
+        marker: "-".cyan(),
+        message: label.message.cyan().to_string(),
+    }));
+
+    let mut output = String::new();
+    while !markers.is_empty() {
+        markers = write_snippet(&mut output, file_path, source, markers);
+    }
+    for note in &diagnostic.notes {
+        let _ = writeln!(output, "      = {}: {}", "note".bold(), note);
+    }
+    if let Some(help) = &diagnostic.help {
+        let _ = writeln!(output, "      = {}: {}", "help".bold(), help);
+    }
+    output
+}
+
+pub fn print_diagnostic(file_path: &str, source: &str, diagnostic: &Diagnostic) {
+    eprint!("{}", render_diagnostic(file_path, source, diagnostic));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::types::Span;
+
+    #[test]
+    fn test_render_diagnostic() {
+        #[cfg(feature = "color")]
+        colored::control::set_override(false);
+        let source = "foo(\n  a\nbar()\n";
+        let at = |line, column| Position {
+            offset: 0,
+            line,
+            column,
+        };
+        let diagnostic = Diagnostic::error("expected ')'", Span::new(at(3, 1), at(3, 1)))
+            .with_label(Span::new(at(1, 4), at(1, 4)), "opened here")
+            .with_note("a note")
+            .with_help("add `)`");
+        assert_eq!(
+            render_diagnostic("CMakeLists.txt", source, &diagnostic),
+            [
+                "  ---> CMakeLists.txt:3:1",
+                "      | ",
+                "1     | foo(",
+                "      |    - opened here (1:4)",
+                "2     |   a",
+                "3     | bar()",
+                "      | ^ error: expected ')' (3:1)",
+                "      | ",
+                "      = note: a note",
+                "      = help: add `)`",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_render_diagnostic_at_end_of_file() {
+        #[cfg(feature = "color")]
+        colored::control::set_override(false);
+        let source = "foo(a b\n";
+        let at = |line, column| Position {
+            offset: 0,
+            line,
+            column,
+        };
+        let diagnostic = Diagnostic::error(
+            "unexpected end of file, expected ')'",
+            Span::new(at(2, 1), at(2, 1)),
+        )
+        .with_label(Span::new(at(1, 1), at(1, 1)), "while parsing this command");
+        assert_eq!(
+            render_diagnostic("CMakeLists.txt", source, &diagnostic),
+            [
+                "  ---> CMakeLists.txt:2:1",
+                "      | ",
+                "1     | foo(a b",
+                "      | - while parsing this command (1:1)",
+                "2     | ",
+                "      | ^ error: unexpected end of file, expected ')' (2:1)",
+                "      | ",
+                "",
+            ]
+            .join("\n")
+        );
     }
 }
@@ -0,0 +1,183 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Formatting CMake source held in memory, without any I/O.
+
+use std::fmt;
+
+use nom_supreme::error::ErrorTree;
+use nom_supreme::final_parser::final_parser;
+
+use crate::parser::diagnostics::{self, Diagnostic, Severity};
+use crate::parser::types::CMakeDocument;
+use crate::parser::{cmake_parser_recovering, Input};
+use crate::pretty_printer::style::Style;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    /// The column limit.
+    pub width: usize,
+    pub style: Style,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            width: 80,
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// Some statements failed to parse. Holds every error found.
+    Parse(Vec<Diagnostic>),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Parse(errors) => {
+                let Some(first) = errors.first() else {
+                    return write!(f, "failed to parse");
+                };
+                let start = first.span.start;
+                write!(f, "{}:{}: {}", start.line, start.column, first.message)?;
+                if errors.len() > 1 {
+                    write!(f, " (and {} more errors)", errors.len() - 1)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// The formatted source, along with the errors and warnings found in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formatted {
+    /// The formatted source. Statements that failed to parse are kept as they
+    /// were.
+    pub output: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Formatted {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// Parses `source`, keeping statements that fail to parse as error
+/// statements with their diagnostics.
+pub fn parse_document(source: &str) -> Result<CMakeDocument<'_>, Box<Diagnostic>> {
+    let mut parser = final_parser(cmake_parser_recovering);
+    let result: Result<CMakeDocument, ErrorTree<Input>> = parser(Input::new(source));
+    result.map_err(|error| Box::new(Diagnostic::from_parse_error(&error)))
+}
+
+/// Prints `document` laid out as set by `options`.
+pub fn print_document(document: &CMakeDocument, options: &FormatOptions) -> String {
+    let mut output = String::new();
+    document
+        .print_with_style(&options.style)
+        .render_fmt(options.width, &mut output)
+        .expect("rendering to a string can't fail");
+    output.replace('\r', "")
+}
+
+/// Formats `source`, recovering from statements that fail to parse.
+pub fn format_with_diagnostics(source: &str, options: &FormatOptions) -> Formatted {
+    match parse_document(source) {
+        Ok(document) => Formatted {
+            output: print_document(&document, options),
+            diagnostics: diagnostics::check(&document),
+        },
+        // The recovering parser keeps anything it can't parse as text
+        Err(diagnostic) => Formatted {
+            output: source.to_string(),
+            diagnostics: vec![*diagnostic],
+        },
+    }
+}
+
+/// Formats `source`, failing if any statement fails to parse.
+pub fn format_str(source: &str, options: &FormatOptions) -> Result<String, FormatError> {
+    let formatted = format_with_diagnostics(source, options);
+    if formatted.has_errors() {
+        let errors = formatted
+            .diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .collect();
+        return Err(FormatError::Parse(errors));
+    }
+    Ok(formatted.output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pretty_printer::style::Preset;
+
+    #[test]
+    fn test_format_str() {
+        let output = format_str("PROJECT(  foo )\n", &FormatOptions::default()).unwrap();
+        assert_eq!(output, "PROJECT(foo)");
+    }
+
+    #[test]
+    fn test_format_str_with_options() {
+        let options = FormatOptions {
+            width: 20,
+            style: Preset::GersemiLike.style(),
+        };
+        let output = format_str("SET(FOO a_long_value another_value)", &options).unwrap();
+        assert_eq!(
+            output,
+            "set(\n    FOO\n    a_long_value\n    another_value\n)"
+        );
+    }
+
+    #[test]
+    fn test_format_str_reports_every_error() {
+        let source = "foo(a\nbar()\nendif()\nbaz(\n";
+        let Err(FormatError::Parse(errors)) = format_str(source, &FormatOptions::default()) else {
+            panic!("expected parse errors");
+        };
+        assert_eq!(errors.len(), 3);
+        for error in &errors {
+            assert_eq!(error.notes, vec!["the statement is kept unformatted"]);
+        }
+    }
+
+    #[test]
+    fn test_format_with_diagnostics_keeps_bad_statements() {
+        let formatted = format_with_diagnostics("foo(a\nbar(  b )", &FormatOptions::default());
+        assert!(formatted.has_errors());
+        assert_eq!(formatted.output, "foo(a\nbar(b)");
+    }
+}
@@ -0,0 +1,557 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+//! Formatting a document again after small edits, as editors do while
+//! typing.
+//!
+//! [`IncrementalDocument`] keeps the parsed document and the printed output
+//! of each top-level line. An edit parses again only the lines it touches,
+//! keeps the statements of the other lines, moving their spans, and prints
+//! only the new lines. Lines don't affect how each other are parsed or laid
+//! out, except for lines with parse errors, unclosed bracket arguments or
+//! unclosed variable references, which depend on the text after them. An
+//! edit after such a line parses everything from that line on again.
+
+use std::ops::Range;
+
+use crate::format::{parse_document, FormatOptions, Formatted};
+use crate::parser::diagnostics::{self, Diagnostic, Severity};
+use crate::parser::types::{CMakeDocument, CMakeStatement, Position, Span};
+use crate::parser::visit::VisitMut;
+use crate::pretty_printer::print_line;
+
+/// A change to the source: the bytes in `range` are replaced with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, text: impl Into<String>) -> Self {
+        TextEdit {
+            range,
+            text: text.into(),
+        }
+    }
+}
+
+/// The statements of the document up to and including a top-level newline,
+/// or the ones after the last newline.
+#[derive(Debug)]
+struct Line {
+    /// Number of statements in the line.
+    len: usize,
+    /// Whether the line is only a newline, which may be dropped by the
+    /// `max_blank_lines` limit.
+    blank: bool,
+    /// Whether the line may parse differently if the text after it changes:
+    /// it has parse errors, whose recovery looks ahead, or something open.
+    open: bool,
+    output: String,
+}
+
+/// A parsed and formatted document that is updated with [`TextEdit`]s.
+///
+/// The document, output and diagnostics are always the same as those of
+/// [`crate::format::format_with_diagnostics`] on the current source.
+#[derive(Debug)]
+pub struct IncrementalDocument {
+    source: String,
+    options: FormatOptions,
+    document: CMakeDocument<'static>,
+    lines: Vec<Line>,
+    /// Why the source couldn't be parsed at all.
+    failure: Option<Diagnostic>,
+}
+
+impl IncrementalDocument {
+    pub fn new(source: impl Into<String>, options: FormatOptions) -> Self {
+        let mut document = IncrementalDocument {
+            source: source.into(),
+            options,
+            document: CMakeDocument { statements: vec![] },
+            lines: vec![],
+            failure: None,
+        };
+        document.parse_all();
+        document
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn document(&self) -> &CMakeDocument<'static> {
+        &self.document
+    }
+
+    /// The formatted source, with the errors and warnings found in it.
+    pub fn formatted(&self) -> Formatted {
+        if let Some(failure) = &self.failure {
+            return Formatted {
+                output: self.source.clone(),
+                diagnostics: vec![failure.clone()],
+            };
+        }
+        // The blank line limit of `print_statements`. Every line but the
+        // last ends with a newline, which counts as the first one
+        let mut output = String::new();
+        let mut newline_count = 0;
+        for line in &self.lines {
+            if line.blank {
+                if newline_count > self.options.style.max_blank_lines {
+                    continue;
+                }
+                newline_count += 1;
+            } else {
+                newline_count = 1;
+            }
+            output.push_str(&line.output);
+        }
+        Formatted {
+            output,
+            diagnostics: diagnostics::check(&self.document),
+        }
+    }
+
+    /// Applies `edit` to the source and updates the document. Returns the
+    /// indices of the top-level statements that were parsed again.
+    ///
+    /// # Panics
+    ///
+    /// If the range of the edit is out of bounds or doesn't fall on `char`
+    /// boundaries, like [`String::replace_range`].
+    pub fn edit(&mut self, edit: &TextEdit) -> Range<usize> {
+        let range = edit.range.clone();
+        let shift = Shift {
+            offset: edit.text.len() as isize - range.len() as isize,
+            lines: edit.text.matches('\n').count() as isize
+                - self.source[range.clone()].matches('\n').count() as isize,
+        };
+        let starts = self.line_starts();
+        self.source.replace_range(range.clone(), &edit.text);
+        if self.failure.is_some() {
+            return self.parse_all();
+        }
+
+        let first = starts.partition_point(|start| start.offset <= range.start) - 1;
+        let last = starts.partition_point(|start| start.offset <= range.end) - 1;
+        match self.lines[..first].iter().position(|line| line.open) {
+            Some(open) => self.reparse(open..self.lines.len(), &starts, shift),
+            None => self.reparse(first..last + 1, &starts, shift),
+        }
+    }
+
+    /// Where each line starts.
+    fn line_starts(&self) -> Vec<Position> {
+        let mut starts = Vec::with_capacity(self.lines.len());
+        let mut start = START;
+        let mut statement = 0;
+        for line in &self.lines {
+            starts.push(start);
+            statement += line.len;
+            start = self.document.statements[statement - 1].span().end;
+        }
+        starts
+    }
+
+    fn parse_all(&mut self) -> Range<usize> {
+        match parse_document(&self.source) {
+            Ok(document) => {
+                self.lines = split_lines(&document.statements, &self.source, &self.options);
+                self.document = document.into_owned();
+                self.failure = None;
+            }
+            Err(diagnostic) => {
+                self.document.statements.clear();
+                self.lines.clear();
+                self.failure = Some(*diagnostic);
+            }
+        }
+        0..self.document.statements.len()
+    }
+
+    /// Parses `lines` of the edited source again. `starts` and the spans in
+    /// the document are from before the edit, and `shift` moves the spans
+    /// after it.
+    fn reparse(
+        &mut self,
+        mut lines: Range<usize>,
+        starts: &[Position],
+        shift: Shift,
+    ) -> Range<usize> {
+        let at_end = lines.end == self.lines.len();
+        // Blank lines at the end of a file are dropped, along with the
+        // newline before them, so take in the last line with statements
+        while at_end && lines.start > 0 && is_blank(&self.source[starts[lines.start].offset..]) {
+            lines.start -= 1;
+        }
+        if lines.start == 0 && at_end {
+            return self.parse_all();
+        }
+
+        let start = starts[lines.start];
+        let end = if at_end {
+            self.source.len()
+        } else {
+            starts[lines.end].offset.wrapping_add_signed(shift.offset)
+        };
+        let text = &self.source[start.offset..end];
+        let Ok(document) = parse_document(text) else {
+            return self.parse_all();
+        };
+        let mut statements = document.statements;
+        if !at_end {
+            // The newlines at the end of `text` were dropped as if it was the
+            // end of the file
+            let last = statements
+                .last()
+                .map_or(START, |statement| statement.span().end);
+            match trailing_newlines(text, last) {
+                Some(newlines) => statements.extend(newlines),
+                None => return self.reparse(lines.start..self.lines.len(), starts, shift),
+            }
+        }
+        let new_lines = split_lines(&statements, text, &self.options);
+        if !at_end && new_lines.iter().any(|line| line.open) {
+            return self.reparse(lines.start..self.lines.len(), starts, shift);
+        }
+
+        let mut to_start = Shift {
+            offset: start.offset as isize,
+            lines: start.line as isize - 1,
+        };
+        let statements = statements.into_iter().map(|statement| {
+            let mut statement = statement.into_owned();
+            to_start.visit_statement_mut(&mut statement);
+            statement
+        });
+        let first: usize = self.lines[..lines.start].iter().map(|line| line.len).sum();
+        let removed: usize = self.lines[lines.clone()].iter().map(|line| line.len).sum();
+        let mut shift = shift;
+        for statement in &mut self.document.statements[first + removed..] {
+            shift.visit_statement_mut(statement);
+        }
+        let added = new_lines.iter().map(|line| line.len).sum::<usize>();
+        self.document
+            .statements
+            .splice(first..first + removed, statements);
+        self.lines.splice(lines, new_lines);
+        first..first + added
+    }
+}
+
+const START: Position = Position {
+    offset: 0,
+    line: 1,
+    column: 1,
+};
+
+/// Splits top-level `statements`, parsed from `text`, into lines and prints
+/// them.
+fn split_lines(
+    statements: &[CMakeStatement<'_>],
+    text: &str,
+    options: &FormatOptions,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut text_start = 0;
+    for (index, statement) in statements.iter().enumerate() {
+        let is_newline = matches!(statement, CMakeStatement::Newline(_));
+        if !is_newline && index + 1 < statements.len() {
+            continue;
+        }
+        let line = &statements[start..=index];
+        let text_end = if is_newline {
+            statement.span().end.offset
+        } else {
+            text.len()
+        };
+        let mut diagnostics = Vec::new();
+        diagnostics::check_statements(line, &mut diagnostics);
+        let mut output = String::new();
+        print_line(line, &options.style)
+            .render_fmt(options.width, &mut output)
+            .expect("rendering to a string can't fail");
+        lines.push(Line {
+            len: line.len(),
+            blank: line.len() == 1 && is_newline,
+            open: diagnostics
+                .iter()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+                || !is_closed(&text[text_start..text_end]),
+            output: output.replace('\r', ""),
+        });
+        start = index + 1;
+        text_start = text_end;
+    }
+    lines
+}
+
+/// Whether `text` is only whitespace, which is dropped at the end of a file.
+fn is_blank(text: &str) -> bool {
+    text.chars().all(|c| matches!(c, ' ' | '\t' | '\r' | '\n'))
+}
+
+/// The newline statements the whitespace in `text` after `from` would parse
+/// to if more statements followed it, or `None` if it isn't made of spaces,
+/// tabs and line endings.
+fn trailing_newlines(text: &str, from: Position) -> Option<Vec<CMakeStatement<'static>>> {
+    let mut newlines = Vec::new();
+    let mut position = from;
+    let mut rest = &text[from.offset..];
+    while let Some(c) = rest.chars().next() {
+        let len = match c {
+            ' ' | '\t' => {
+                position.offset += 1;
+                position.column += 1;
+                rest = &rest[1..];
+                continue;
+            }
+            '\n' => 1,
+            '\r' if rest.starts_with("\r\n") => 2,
+            _ => return None,
+        };
+        let end = Position {
+            offset: position.offset + len,
+            line: position.line + 1,
+            column: 1,
+        };
+        newlines.push(CMakeStatement::Newline(Span::new(position, end)));
+        position = end;
+        rest = &rest[len..];
+    }
+    Some(newlines)
+}
+
+/// Whether every bracket argument or comment, such as `[[` or `#[==[`, and
+/// every variable reference opened in `text` is closed in it. Left open,
+/// they parse without errors but take in the lines after them once those
+/// have the closing bracket or `}`. Brackets and braces in quotes or
+/// comments count too, which only means parsing more than needed.
+fn is_closed(text: &str) -> bool {
+    brackets_closed(text) && braces_closed(text)
+}
+
+fn brackets_closed(text: &str) -> bool {
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let equals = after.len() - after.trim_start_matches('=').len();
+        rest = after;
+        if let Some(contents) = after[equals..].strip_prefix('[') {
+            let close = format!("]{}]", "=".repeat(equals));
+            match contents.find(&close) {
+                Some(end) => rest = &contents[end + close.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// Escaped braces don't close a variable reference.
+fn braces_closed(text: &str) -> bool {
+    let mut depth = 0usize;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+/// Moves spans by a number of bytes and lines. Only used on statements that
+/// start a line, so columns stay the same.
+struct Shift {
+    offset: isize,
+    lines: isize,
+}
+
+impl Shift {
+    fn apply(&self, position: &mut Position) {
+        position.offset = position.offset.wrapping_add_signed(self.offset);
+        position.line = position.line.wrapping_add_signed(self.lines);
+    }
+}
+
+impl VisitMut for Shift {
+    fn visit_span_mut(&mut self, span: &mut Span) {
+        self.apply(&mut span.start);
+        self.apply(&mut span.end);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::format::format_with_diagnostics;
+
+    /// Checks that `document` is what parsing its source from scratch gives.
+    fn assert_same_as_full_parse(document: &IncrementalDocument) {
+        let source = document.source();
+        let expected = format_with_diagnostics(source, &document.options);
+        let formatted = document.formatted();
+        assert_eq!(formatted, expected, "source: {source:?}");
+        if let Ok(parsed) = parse_document(source) {
+            assert_eq!(document.document(), &parsed, "source: {source:?}");
+        }
+    }
+
+    fn edit(document: &mut IncrementalDocument, range: Range<usize>, text: &str) -> Range<usize> {
+        let reparsed = document.edit(&TextEdit::new(range, text));
+        assert_same_as_full_parse(document);
+        reparsed
+    }
+
+    #[test]
+    fn test_edit_parses_only_touched_lines() {
+        let mut document =
+            IncrementalDocument::new("set(a 1)\nset( b 2 )\nset(c 3)\n", FormatOptions::default());
+        assert_same_as_full_parse(&document);
+        // Statements are set(a), newline, set(b), newline, set(c)
+        assert_eq!(edit(&mut document, 16..17, "22"), 2..4);
+        assert_eq!(document.source(), "set(a 1)\nset( b 22 )\nset(c 3)\n");
+        assert_eq!(document.formatted().output, "set(a 1)\nset(b 22)\nset(c 3)");
+
+        assert_eq!(edit(&mut document, 9..9, "message(x)\n"), 2..6);
+        assert_eq!(
+            document.document().statements[6].span().start,
+            Position {
+                offset: 32,
+                line: 4,
+                column: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_edit_across_lines() {
+        let mut document = IncrementalDocument::new(
+            "if(a)\n  set(x)\nendif()\nset(y)\n",
+            FormatOptions::default(),
+        );
+        edit(&mut document, 5..6, " ");
+        edit(&mut document, 0..0, "foreach(i 1 2)\n");
+        edit(&mut document, 15..15, "endforeach()\n");
+        let len = document.source().len();
+        edit(&mut document, 0..len - 7, "");
+        assert_eq!(document.source(), "set(y)\n");
+    }
+
+    #[test]
+    fn test_edit_at_end_of_file() {
+        let mut document = IncrementalDocument::new("set(a)\nset(b)", FormatOptions::default());
+        edit(&mut document, 7..13, "");
+        edit(&mut document, 7..7, "\n\n  \n");
+        edit(&mut document, 10..10, "set(c)");
+        let len = document.source().len();
+        edit(&mut document, 0..len, "");
+        assert!(document.formatted().has_errors());
+        edit(&mut document, 0..0, "set(d)\n");
+    }
+
+    #[test]
+    fn test_edit_opens_brackets_and_blocks() {
+        let source = "set(a)\n\n\n\n\nset(b) # ]]\nset(c \"d\")\n";
+        let mut document = IncrementalDocument::new(source, FormatOptions::default());
+        // The bracket comment takes in the comment on the line after it
+        edit(&mut document, 0..0, "#[[ x\n");
+        edit(&mut document, 0..4, "");
+        edit(&mut document, 7..7, "if(a)\n");
+        edit(&mut document, 35..35, "endif()\n");
+        edit(&mut document, 39..39, "\"");
+        edit(&mut document, 0..0, "\r\n");
+        // So does a variable reference
+        edit(&mut document, 0..0, "set(d \"${e\")\n");
+        let len = document.source().len();
+        edit(&mut document, len..len, "message(}\")\n");
+    }
+
+    #[test]
+    fn test_random_edits() {
+        const SNIPPETS: &[&str] = &[
+            "(",
+            ")",
+            "\n",
+            " ",
+            "x",
+            "#",
+            "[[",
+            "]]",
+            "[=[",
+            "\"",
+            "\\",
+            "$",
+            "if(a)\n",
+            "endif()\n",
+            "set(",
+            "\r\n",
+            "\n\n\n",
+            "function(f)\n",
+            "endfunction()",
+        ];
+        let mut seed: u64 = 0x5eed;
+        let mut random = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound.max(1)
+        };
+        for entry in std::fs::read_dir("./samples").unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".input.cmake") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            // Each edit is checked against a full parse, too slow for the
+            // large samples in debug builds
+            if source.len() > 10_000 {
+                continue;
+            }
+            let mut document = IncrementalDocument::new(source, FormatOptions::default());
+            for _ in 0..40 {
+                let source = document.source();
+                let boundary = |offset: usize| {
+                    (offset..=source.len())
+                        .find(|offset| source.is_char_boundary(*offset))
+                        .unwrap()
+                };
+                let start = boundary(random(source.len() + 1));
+                let end = boundary((start + random(6)).min(source.len()));
+                let text = if random(3) == 0 {
+                    ""
+                } else {
+                    SNIPPETS[random(SNIPPETS.len())]
+                };
+                edit(&mut document, start..end, text);
+            }
+        }
+    }
+}
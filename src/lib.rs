@@ -1,6 +1,18 @@
-mod errors;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "cli")]
+pub mod config;
+pub mod errors;
+pub mod format;
+pub mod incremental;
+#[cfg(feature = "cli")]
 pub mod options;
 pub mod parser;
 pub mod pretty_printer;
+#[cfg(feature = "cli")]
 pub mod run;
+#[cfg(feature = "cli")]
 pub mod writer;
+
+pub use format::{format_str, FormatError, FormatOptions};
+pub use parser::diagnostics::{Diagnostic, Label, Severity};
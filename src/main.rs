@@ -20,12 +20,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-mod errors;
-mod options;
-mod parser;
-mod pretty_printer;
-mod run;
-mod writer;
+use cmakefmt::{options, run};
 
 fn main() {
     let opts = options::parse_options();
@@ -37,12 +32,13 @@ mod test {
     use std::collections::HashMap;
     use std::fs::DirEntry;
 
+    use cmakefmt::parser::visit::Node;
+    use cmakefmt::parser::Input;
     use nom::combinator::all_consuming;
 
     #[test]
     fn test_smoke_tests() {
-        let input_output: Vec<&str> = vec![
-            r#"
+        let input_output: Vec<&str> = [r#"
 cmake_minimum_required(VERSION 3.26)
 project(pyramid_envelope VERSION 0.0.1 LANGUAGES CXX)
 add_subdirectory(
@@ -50,15 +46,15 @@ add_subdirectory(
   EXCLUDE_FROM_ALL
   # don't build examples etc, also don't install
 )
-            "#,
-        ]
+            "#]
         .iter()
         .map(|s| s.trim())
         .collect();
 
         for input in input_output.iter() {
-            let output = all_consuming(super::parser::cmake_parser)(input);
-            let output = output.unwrap().1.print();
+            let output = all_consuming(cmakefmt::parser::cmake_parser)(Input::new(input));
+            let document = output.unwrap().1;
+            let output = document.print();
             let mut writer = vec![];
             {
                 output.render(80, &mut writer).unwrap();
@@ -110,7 +106,7 @@ add_subdirectory(
             },
         ) in groups.iter()
         {
-            let output = all_consuming(super::parser::cmake_parser)(&input_file);
+            let output = all_consuming(cmakefmt::parser::cmake_parser)(Input::new(input_file));
             let (_, input_document) = output.unwrap();
             let output = input_document.print();
             let mut writer = vec![];
@@ -121,8 +117,8 @@ add_subdirectory(
             assert_eq!(&output, output_file);
 
             let (_, round_trip_document) =
-                all_consuming(super::parser::cmake_parser)(&output).unwrap();
-            assert_eq!(&input_document, &round_trip_document);
+                all_consuming(cmakefmt::parser::cmake_parser)(Input::new(&output)).unwrap();
+            assert!(input_document.same_tree(&round_trip_document));
         }
     }
 }
@@ -20,13 +20,128 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-use clap::{arg, command, Arg, ArgAction};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::{arg, command, value_parser, Arg, ArgAction, ArgMatches};
+
+use crate::config::Config;
+use crate::format::FormatOptions;
+use crate::pretty_printer::style::{CommandCase, CommandStyle, ListLayout, Preset};
+
+/// What is written to the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Emit {
+    /// The formatted source.
+    #[default]
+    Formatted,
+    /// The syntax tree as JSON, see [`crate::parser::json`].
+    AstJson,
+    /// The tokens, see [`crate::parser::lexer::to_text`].
+    Tokens,
+    /// The syntax tree as indented text, see [`crate::parser::tree`].
+    AstTree,
+    /// The layout document as indented text, see [`crate::pretty_printer::tree`].
+    Doc,
+}
+
+impl FromStr for Emit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "formatted" => Ok(Emit::Formatted),
+            "ast-json" => Ok(Emit::AstJson),
+            "tokens" => Ok(Emit::Tokens),
+            "ast-tree" => Ok(Emit::AstTree),
+            "doc" => Ok(Emit::Doc),
+            _ => Err(format!(
+                "invalid output `{}`, expected one of: formatted, ast-json, tokens, ast-tree, doc",
+                s
+            )),
+        }
+    }
+}
+
+/// How the input file is read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    #[default]
+    Cmake,
+    /// A syntax tree written by `--emit ast-json`.
+    AstJson,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cmake" => Ok(InputFormat::Cmake),
+            "ast-json" => Ok(InputFormat::AstJson),
+            _ => Err(format!(
+                "invalid input format `{}`, expected one of: cmake, ast-json",
+                s
+            )),
+        }
+    }
+}
 
 pub struct Options {
-    pub verbose: bool,
     pub inplace: bool,
+    pub emit: Emit,
+    pub input_format: InputFormat,
     pub input_file: String,
-    pub width: usize,
+    pub format_options: FormatOptions,
+}
+
+fn parse_command_list_layout(value: &str) -> Result<(String, ListLayout), String> {
+    let (name, layout) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected COMMAND=LAYOUT, got `{}`", value))?;
+    Ok((name.to_string(), ListLayout::from_str(layout)?))
+}
+
+/// The settings passed as flags, which take precedence over the config file.
+fn config_from_matches(matches: &ArgMatches) -> Config {
+    Config {
+        style: matches.get_one::<Preset>("style").copied(),
+        max_width: matches.get_one::<usize>("max-width").copied(),
+        indent: matches.get_one::<usize>("indent").copied(),
+        dangle_parens: matches.get_one::<bool>("dangle-parens").copied(),
+        command_case: matches.get_one::<CommandCase>("command-case").copied(),
+        keyword_grouping: matches.get_one::<bool>("keyword-grouping").copied(),
+        max_blank_lines: matches.get_one::<usize>("max-blank-lines").copied(),
+        list_layout: matches.get_one::<ListLayout>("list-layout").copied(),
+        command: matches
+            .get_many::<(String, ListLayout)>("command-list-layout")
+            .into_iter()
+            .flatten()
+            .map(|(name, layout)| {
+                let command = CommandStyle {
+                    list_layout: Some(*layout),
+                    ..Default::default()
+                };
+                (name.to_lowercase(), command)
+            })
+            .collect(),
+    }
+}
+
+fn load_config_file(matches: &ArgMatches, input_file: &str) -> Config {
+    let path = matches.get_one::<PathBuf>("config").cloned().or_else(|| {
+        let input_dir = Path::new(input_file).parent()?;
+        let input_dir = std::fs::canonicalize(input_dir).ok()?;
+        Config::find(&input_dir)
+    });
+    match path.map(|path| Config::load(&path)) {
+        None => Config::default(),
+        Some(Ok(config)) => config,
+        Some(Err(err)) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
 }
 
 pub fn parse_options() -> Options {
@@ -38,35 +153,115 @@ pub fn parse_options() -> Options {
                 .help("Write to the input file after formatting")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .num_args(1)
+                .value_parser(value_parser!(PathBuf))
+                .help("Path to the config file, by default .cmakefmt.toml is searched for from the input file's directory upwards"),
+        )
+        .arg(
+            Arg::new("style")
+                .long("style")
+                .num_args(1)
+                .value_parser(Preset::from_str)
+                .help("Style preset: default, cmake-format, gersemi-like or compact"),
+        )
         .arg(
             Arg::new("max-width")
                 .long("max-width")
                 .num_args(1)
-                .default_value("80")
-                .help("The column limit to be used"),
+                .value_parser(value_parser!(usize))
+                .help("The column limit to be used [default: 80]"),
         )
         .arg(
-            Arg::new("verbose")
-                .long("verbose")
-                .help("Print debug logs")
-                .action(ArgAction::SetTrue),
+            Arg::new("indent")
+                .long("indent")
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Number of spaces per indentation level"),
+        )
+        .arg(
+            Arg::new("dangle-parens")
+                .long("dangle-parens")
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .help("Put the closing parenthesis of broken commands on its own line"),
+        )
+        .arg(
+            Arg::new("command-case")
+                .long("command-case")
+                .num_args(1)
+                .value_parser(CommandCase::from_str)
+                .help("Casing of command names: unchanged, lower or upper"),
+        )
+        .arg(
+            Arg::new("keyword-grouping")
+                .long("keyword-grouping")
+                .num_args(1)
+                .value_parser(value_parser!(bool))
+                .help("Group upper-case keywords with the arguments that follow them"),
+        )
+        .arg(
+            Arg::new("max-blank-lines")
+                .long("max-blank-lines")
+                .num_args(1)
+                .value_parser(value_parser!(usize))
+                .help("Maximum number of consecutive blank lines"),
+        )
+        .arg(
+            Arg::new("list-layout")
+                .long("list-layout")
+                .num_args(1)
+                .value_parser(ListLayout::from_str)
+                .help("How argument lists are broken: one_per_line, fill or auto"),
+        )
+        .arg(
+            Arg::new("command-list-layout")
+                .long("command-list-layout")
+                .value_name("COMMAND=LAYOUT")
+                .action(ArgAction::Append)
+                .value_parser(parse_command_list_layout)
+                .help("Override --list-layout for a single command, e.g. target_sources=fill"),
+        )
+        .arg(
+            Arg::new("emit")
+                .long("emit")
+                .num_args(1)
+                .value_parser(Emit::from_str)
+                .conflicts_with("inplace")
+                .help("What to print: formatted (default), ast-json, tokens, ast-tree or doc"),
+        )
+        .arg(
+            Arg::new("input-format")
+                .long("input-format")
+                .num_args(1)
+                .value_parser(InputFormat::from_str)
+                .conflicts_with("inplace")
+                .help("How to read the file: cmake (the default) or ast-json, a syntax tree written by --emit ast-json"),
         )
         .arg(arg!([file] "Target file").required(true))
         .get_matches();
 
-    let verbose = matches.get_flag("verbose");
     let inplace = matches.get_flag("inplace");
+    let emit = matches.get_one::<Emit>("emit").copied().unwrap_or_default();
+    let input_format = matches
+        .get_one::<InputFormat>("input-format")
+        .copied()
+        .unwrap_or_default();
     let input_file: &String = matches.get_one("file").expect("No input file provided");
-    let width = matches
-        .get_one("max-width")
-        .cloned()
-        .map(|s: String| s.parse::<usize>().ok())
-        .flatten()
-        .unwrap_or(80);
+
+    let config = load_config_file(&matches, input_file).merge(config_from_matches(&matches));
+    let format_options = FormatOptions {
+        width: config.max_width.unwrap_or(80),
+        style: config.to_style(),
+    };
+
     Options {
-        verbose,
         inplace,
+        emit,
+        input_format,
         input_file: input_file.clone(),
-        width,
+        format_options,
     }
 }
@@ -0,0 +1,804 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Building syntax trees in code, to generate CMake files.
+//!
+//! Values are quoted as needed and escaped so that they mean exactly the
+//! given text, and the trees are laid out the way the parser would produce
+//! them, so printing them with [`CMakeDocument::print`] gives formatted
+//! code that parses back to the same tree.
+//!
+//! ```
+//! use cmakefmt::parser::builder::{FunctionBuilder, IfBuilder};
+//! use cmakefmt::parser::types::{CMakeArgument, CMakeCommand, CMakeCondition, CMakeDocument};
+//!
+//! let document: CMakeDocument = [
+//!     CMakeCommand::new("add_library").args(["foo", "foo.cpp"]).into(),
+//!     IfBuilder::new(CMakeCondition::value("WIN32"))
+//!         .statement(
+//!             CMakeCommand::new("target_link_libraries")
+//!                 .arg("foo")
+//!                 .keyword("PRIVATE")
+//!                 .arg("ws2_32"),
+//!         )
+//!         .build()
+//!         .into(),
+//!     FunctionBuilder::new("greet")
+//!         .param("name")
+//!         .statement(
+//!             CMakeCommand::new("message")
+//!                 .arg(CMakeArgument::new().text("Hello, ").variable("name")),
+//!         )
+//!         .build()
+//!         .into(),
+//! ]
+//! .into_iter()
+//! .collect();
+//!
+//! let mut output = String::new();
+//! document.print().render_fmt(80, &mut output).unwrap();
+//! assert_eq!(
+//!     output,
+//!     "add_library(foo foo.cpp)\n\
+//!      if(WIN32)\n  target_link_libraries(foo PRIVATE ws2_32)\nendif()\n\
+//!      function(greet name)\n  message(\"Hello, ${name}\")\nendfunction()"
+//! );
+//! ```
+
+use crate::parser::parse_condition::{is_operator, BINARY_TESTS, UNARY_TESTS};
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeBracketLiteral, CMakeCommand, CMakeCommandGroup,
+    CMakeCondition, CMakeDocument, CMakeFunctionStatement, CMakeIfBase, CMakeIfStatement,
+    CMakeStatement, CMakeValue, Span, VariableKind, VariableReference,
+};
+use crate::parser::visit::Node;
+use crate::parser::{strings, Input, RESERVED_WORDS};
+
+impl CMakeArgument<'static> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends literal text, which is escaped when printed.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        match self.parts.last_mut() {
+            _ if text.is_empty() => {}
+            Some(ArgumentPart::Text(last)) => last.to_mut().push_str(&text),
+            _ => self.parts.push(ArgumentPart::Text(text.into())),
+        }
+        self
+    }
+
+    /// Appends a `${name}` reference.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is empty or has characters other than letters, digits and
+    /// `/_.+-`, which CMake only accepts escaped in variable names.
+    pub fn variable(mut self, name: &str) -> Self {
+        assert!(
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "/_.+-".contains(c)),
+            "invalid variable name {name:?}"
+        );
+        self.parts.push(ArgumentPart::Variable(VariableReference {
+            kind: VariableKind::Normal,
+            name: vec![ArgumentPart::Text(name.to_string().into())],
+            span: Span::default(),
+        }));
+        self
+    }
+}
+
+/// Whether `text` can be written as part of an unquoted argument without
+/// changing its meaning.
+fn is_unquoted_safe(text: &str) -> bool {
+    !text
+        .chars()
+        .any(|c| c.is_whitespace() || "()#\"\\;$@[]".contains(c))
+}
+
+/// Writes `text` inside a quoted argument, escaping the characters that
+/// would otherwise end the argument or start a variable reference.
+fn escape_quoted(text: &str, output: &mut String) {
+    for (i, c) in text.char_indices() {
+        let rest = &text[i + 1..];
+        match c {
+            '"' | '\\' => output.push('\\'),
+            '\n' | '\r' | '\t' => {
+                output.push('\\');
+                output.push(match c {
+                    '\n' => 'n',
+                    '\r' => 'r',
+                    _ => 't',
+                });
+                continue;
+            }
+            '$' if ["{", "ENV{", "CACHE{"]
+                .iter()
+                .any(|prefix| rest.starts_with(prefix)) =>
+            {
+                output.push('\\')
+            }
+            _ => {}
+        }
+        output.push(c);
+    }
+}
+
+/// A bracket argument holding `text`, with enough `=` in its delimiter that
+/// the text can't close it. The text starts on a new line, since CMake drops
+/// a newline right after the opening bracket.
+fn bracket_quoted(text: &str) -> CMakeBracketLiteral<'static> {
+    let contents = format!("\n{text}");
+    let delimiter = (0..)
+        .map(|n| "=".repeat(n))
+        .find(|delimiter| {
+            let close = format!("]{delimiter}]");
+            format!("{contents}{close}").find(&close) == Some(contents.len())
+        })
+        .unwrap();
+    CMakeBracketLiteral {
+        is_comment: false,
+        delimiter: delimiter.into(),
+        contents: contents.into(),
+    }
+}
+
+/// Picks the simplest form that keeps the meaning of the argument: unquoted
+/// when nothing needs escaping, a bracket argument for literal text with
+/// newlines or backslashes, and a quoted argument otherwise.
+impl From<CMakeArgument<'static>> for CMakeValue<'static> {
+    fn from(argument: CMakeArgument<'static>) -> Self {
+        let span = Span::default();
+        let texts = || {
+            argument.parts.iter().filter_map(|part| match part {
+                ArgumentPart::Text(text) => Some(text),
+                _ => None,
+            })
+        };
+        if !argument.parts.is_empty() && texts().all(|text| is_unquoted_safe(text)) {
+            let is_keyword = argument.parts.iter().all(|part| match part {
+                ArgumentPart::Text(text) => text
+                    .chars()
+                    .all(|c| c.is_uppercase() || c == '_' || c.is_numeric()),
+                _ => false,
+            });
+            let source = argument.to_string();
+            return if is_keyword {
+                CMakeValue::ArgumentSpecifier(source.into(), span)
+            } else {
+                CMakeValue::StringLiteral(
+                    strings::parse_argument(Input::new(&source))
+                        .into_owned()
+                        .without_spans(),
+                    span,
+                )
+            };
+        }
+        if let [ArgumentPart::Text(text)] = argument.parts.as_slice() {
+            if text.contains(['\n', '\\']) {
+                return CMakeValue::BracketQuotedString(bracket_quoted(text), span);
+            }
+        }
+        // Parse the escaped text back, so the parts are the ones the parser
+        // finds, such as generator expressions written as text.
+        let mut source = String::from("\"");
+        for part in &argument.parts {
+            match part {
+                ArgumentPart::Text(text) => escape_quoted(text, &mut source),
+                part => source.push_str(&part.to_string()),
+            }
+        }
+        source.push('"');
+        let (_, argument) = strings::parse_string(Input::new(&source))
+            .unwrap_or_else(|_| panic!("can't parse {source}"));
+        CMakeValue::QuotedString(argument.into_owned().without_spans(), span)
+    }
+}
+
+/// The literal text, quoted as needed.
+impl From<&str> for CMakeValue<'static> {
+    fn from(text: &str) -> Self {
+        CMakeArgument::new().text(text).into()
+    }
+}
+
+impl From<String> for CMakeValue<'static> {
+    fn from(text: String) -> Self {
+        CMakeArgument::new().text(text).into()
+    }
+}
+
+/// Asserts that `name` can be written as a command name.
+fn check_command_name(name: &str) {
+    assert!(
+        name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        "invalid command name {name:?}"
+    );
+}
+
+impl CMakeCommand<'static> {
+    /// A command without arguments.
+    ///
+    /// # Panics
+    ///
+    /// If `name` isn't a valid command name, or is a block keyword such as
+    /// `if` or `endfunction`. Blocks are built with [`IfBuilder`] and
+    /// [`FunctionBuilder`].
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        check_command_name(&name);
+        assert!(
+            !RESERVED_WORDS.contains(&&*name.to_lowercase()),
+            "`{name}` starts or ends a block"
+        );
+        CMakeCommand {
+            name: name.into(),
+            args: Vec::new(),
+            span: Span::default(),
+        }
+    }
+
+    /// Appends an argument. Strings are taken literally and quoted as needed.
+    pub fn arg(mut self, value: impl Into<CMakeValue<'static>>) -> Self {
+        self.args.push(value.into());
+        self
+    }
+
+    pub fn args<V: Into<CMakeValue<'static>>>(
+        mut self,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.args.extend(values.into_iter().map(Into::into));
+        self
+    }
+
+    /// Appends a keyword such as `PRIVATE`, which starts a group of arguments
+    /// when the command breaks.
+    ///
+    /// # Panics
+    ///
+    /// If `keyword` has characters other than upper-case letters, digits and
+    /// `_`.
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        assert!(
+            !keyword.is_empty()
+                && keyword
+                    .chars()
+                    .all(|c| c.is_uppercase() || c == '_' || c.is_numeric()),
+            "invalid keyword {keyword:?}"
+        );
+        self.args.push(CMakeValue::ArgumentSpecifier(
+            keyword.to_string().into(),
+            Span::default(),
+        ));
+        self
+    }
+}
+
+/// How tightly a condition binds, from `OR` to single operands.
+fn precedence(condition: &CMakeCondition<'_>) -> u8 {
+    match condition {
+        CMakeCondition::BinaryLogicalOperator { operator, .. } if operator == "OR" => 1,
+        CMakeCondition::BinaryLogicalOperator { .. } => 2,
+        CMakeCondition::UnaryLogicalOperator { .. } => 3,
+        CMakeCondition::UnaryTest { .. } | CMakeCondition::BinaryTest { .. } => 4,
+        _ => 5,
+    }
+}
+
+/// Wraps `condition` in parentheses if it binds looser than `precedence`.
+fn operand(condition: CMakeCondition<'_>, precedence: u8) -> Box<CMakeCondition<'_>> {
+    if self::precedence(&condition) < precedence {
+        Box::new(CMakeCondition::Parentheses {
+            value: Box::new(condition),
+            span: Span::default(),
+        })
+    } else {
+        Box::new(condition)
+    }
+}
+
+/// Conditions are built from values and operators. Operands are put in
+/// parentheses where CMake's precedence would otherwise group them
+/// differently.
+impl<'a> CMakeCondition<'a> {
+    /// A single operand, such as a variable name or a string. Words that
+    /// would be read as an operator, such as `AND`, are quoted.
+    pub fn value(value: impl Into<CMakeValue<'a>>) -> Self {
+        CMakeCondition::Value(match value.into() {
+            CMakeValue::ArgumentSpecifier(text, span) if is_operator(&text) => {
+                CMakeValue::QuotedString(
+                    CMakeArgument {
+                        parts: vec![ArgumentPart::Text(text)],
+                    },
+                    span,
+                )
+            }
+            CMakeValue::StringLiteral(argument, span)
+                if matches!(
+                    argument.parts.as_slice(),
+                    [ArgumentPart::Text(text)] if is_operator(text)
+                ) =>
+            {
+                CMakeValue::QuotedString(argument, span)
+            }
+            value => value,
+        })
+    }
+
+    /// A unary test such as `DEFINED` or `EXISTS`.
+    ///
+    /// # Panics
+    ///
+    /// If `operator` isn't a unary test.
+    pub fn unary(operator: &str, value: CMakeCondition<'a>) -> Self {
+        assert!(
+            UNARY_TESTS.contains(&operator),
+            "invalid unary test {operator:?}"
+        );
+        CMakeCondition::UnaryTest {
+            operator: operator.to_string().into(),
+            value: operand(value, 5),
+            span: Span::default(),
+        }
+    }
+
+    /// A binary test such as `STREQUAL` or `VERSION_LESS`.
+    ///
+    /// # Panics
+    ///
+    /// If `operator` isn't a binary test.
+    pub fn binary(left: CMakeCondition<'a>, operator: &str, right: CMakeCondition<'a>) -> Self {
+        assert!(
+            BINARY_TESTS.contains(&operator),
+            "invalid binary test {operator:?}"
+        );
+        CMakeCondition::BinaryTest {
+            operator: operator.to_string().into(),
+            left: operand(left, 5),
+            right: operand(right, 5),
+            span: Span::default(),
+        }
+    }
+
+    pub fn and(self, right: CMakeCondition<'a>) -> Self {
+        CMakeCondition::BinaryLogicalOperator {
+            operator: "AND".into(),
+            left: operand(self, 2),
+            right: operand(right, 3),
+            span: Span::default(),
+        }
+    }
+
+    pub fn or(self, right: CMakeCondition<'a>) -> Self {
+        CMakeCondition::BinaryLogicalOperator {
+            operator: "OR".into(),
+            left: operand(self, 1),
+            right: operand(right, 2),
+            span: Span::default(),
+        }
+    }
+}
+
+/// `NOT condition`.
+impl<'a> std::ops::Not for CMakeCondition<'a> {
+    type Output = CMakeCondition<'a>;
+
+    fn not(self) -> Self::Output {
+        CMakeCondition::UnaryLogicalOperator {
+            operator: "NOT".into(),
+            value: operand(self, 3),
+            span: Span::default(),
+        }
+    }
+}
+
+/// The body of a block: every statement on its own line.
+fn block_body(statements: Vec<CMakeStatement<'_>>) -> Vec<CMakeStatement<'_>> {
+    let mut body = vec![CMakeStatement::Newline(Span::default())];
+    for statement in statements {
+        body.push(statement);
+        body.push(CMakeStatement::Newline(Span::default()));
+    }
+    body
+}
+
+/// Builds an `if()` block. Statements go to the branch started last.
+pub struct IfBuilder {
+    branches: Vec<(CMakeCondition<'static>, Vec<CMakeStatement<'static>>)>,
+    else_body: Option<Vec<CMakeStatement<'static>>>,
+}
+
+impl IfBuilder {
+    pub fn new(condition: CMakeCondition<'static>) -> Self {
+        IfBuilder {
+            branches: vec![(condition, Vec::new())],
+            else_body: None,
+        }
+    }
+
+    pub fn statement(mut self, statement: impl Into<CMakeStatement<'static>>) -> Self {
+        let body = match &mut self.else_body {
+            Some(body) => body,
+            None => &mut self.branches.last_mut().unwrap().1,
+        };
+        body.push(statement.into());
+        self
+    }
+
+    pub fn statements<S: Into<CMakeStatement<'static>>>(
+        self,
+        statements: impl IntoIterator<Item = S>,
+    ) -> Self {
+        statements
+            .into_iter()
+            .fold(self, |builder, statement| builder.statement(statement))
+    }
+
+    /// Starts an `elseif()` branch.
+    ///
+    /// # Panics
+    ///
+    /// After [`IfBuilder::otherwise`].
+    pub fn else_if(mut self, condition: CMakeCondition<'static>) -> Self {
+        assert!(self.else_body.is_none(), "elseif() after else()");
+        self.branches.push((condition, Vec::new()));
+        self
+    }
+
+    /// Starts the `else()` branch.
+    pub fn otherwise(mut self) -> Self {
+        self.else_body.get_or_insert_with(Vec::new);
+        self
+    }
+
+    pub fn build(self) -> CMakeIfStatement<'static> {
+        let mut branches = self
+            .branches
+            .into_iter()
+            .map(|(condition, body)| CMakeIfBase {
+                condition,
+                body: block_body(body),
+            });
+        CMakeIfStatement {
+            base: branches.next().unwrap(),
+            else_ifs: branches.collect(),
+            else_body: self.else_body.map(block_body),
+            span: Span::default(),
+        }
+    }
+}
+
+/// Builds a `function()` block.
+pub struct FunctionBuilder {
+    name: String,
+    params: Vec<String>,
+    body: Vec<CMakeStatement<'static>>,
+}
+
+impl FunctionBuilder {
+    /// # Panics
+    ///
+    /// If `name` isn't a valid command name.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        check_command_name(&name);
+        FunctionBuilder {
+            name,
+            params: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Appends a named parameter.
+    ///
+    /// # Panics
+    ///
+    /// If `name` isn't a valid variable name.
+    pub fn param(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        CMakeArgument::new().variable(&name);
+        self.params.push(name);
+        self
+    }
+
+    pub fn params<S: Into<String>>(self, names: impl IntoIterator<Item = S>) -> Self {
+        names
+            .into_iter()
+            .fold(self, |builder, name| builder.param(name))
+    }
+
+    pub fn statement(mut self, statement: impl Into<CMakeStatement<'static>>) -> Self {
+        self.body.push(statement.into());
+        self
+    }
+
+    pub fn statements<S: Into<CMakeStatement<'static>>>(
+        mut self,
+        statements: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.body.extend(statements.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn build(self) -> CMakeFunctionStatement<'static> {
+        CMakeFunctionStatement {
+            group: CMakeCommandGroup {
+                clause: std::iter::once(self.name)
+                    .chain(self.params)
+                    .map(CMakeValue::from)
+                    .collect(),
+                body: block_body(self.body),
+                end_clause: Vec::new(),
+                span: Span::default(),
+            },
+        }
+    }
+}
+
+impl<'a> From<CMakeCommand<'a>> for CMakeStatement<'a> {
+    fn from(command: CMakeCommand<'a>) -> Self {
+        CMakeStatement::Command(command)
+    }
+}
+
+impl<'a> From<CMakeIfStatement<'a>> for CMakeStatement<'a> {
+    fn from(statement: CMakeIfStatement<'a>) -> Self {
+        CMakeStatement::If(statement)
+    }
+}
+
+impl<'a> From<CMakeFunctionStatement<'a>> for CMakeStatement<'a> {
+    fn from(statement: CMakeFunctionStatement<'a>) -> Self {
+        CMakeStatement::Function(statement)
+    }
+}
+
+/// A document with every statement on its own line.
+impl<'a> FromIterator<CMakeStatement<'a>> for CMakeDocument<'a> {
+    fn from_iter<I: IntoIterator<Item = CMakeStatement<'a>>>(statements: I) -> Self {
+        let mut document = CMakeDocument {
+            statements: Vec::new(),
+        };
+        for statement in statements {
+            if !document.statements.is_empty() {
+                document
+                    .statements
+                    .push(CMakeStatement::Newline(Span::default()));
+            }
+            document.statements.push(statement);
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nom_supreme::final_parser::final_parser;
+
+    use super::*;
+    use crate::format::{format_str, FormatOptions};
+    use crate::parser::{cmake_parser, Input};
+
+    /// Prints `document`, checking that it parses back to the same tree and
+    /// that formatting leaves it unchanged.
+    fn print(document: CMakeDocument<'_>) -> String {
+        let mut output = String::new();
+        document.print().render_fmt(80, &mut output).unwrap();
+        let parsed: CMakeDocument = final_parser(cmake_parser)(Input::new(&output))
+            .map_err(|err: nom_supreme::error::ErrorTree<Input>| err.to_string())
+            .unwrap();
+        assert_eq!(parsed.without_spans(), document, "{output}");
+        assert_eq!(
+            format_str(&output, &FormatOptions::default()).unwrap(),
+            output
+        );
+        output
+    }
+
+    fn print_value(value: impl Into<CMakeValue<'static>>) -> String {
+        let output = print(
+            [CMakeCommand::new("set").arg(value).into()]
+                .into_iter()
+                .collect(),
+        );
+        output["set(".len()..output.len() - 1].to_string()
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(print_value("foo.cpp"), "foo.cpp");
+        assert_eq!(print_value("PRIVATE"), "PRIVATE");
+        assert_eq!(print_value(""), "\"\"");
+        assert_eq!(print_value("a b"), "\"a b\"");
+        assert_eq!(print_value("a;b"), "\"a;b\"");
+        assert_eq!(print_value("(x)"), "\"(x)\"");
+        assert_eq!(print_value("#x"), "\"#x\"");
+        assert_eq!(print_value("@ONLY@"), "\"@ONLY@\"");
+        assert_eq!(print_value("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(print_value("tab\tcr\r"), "\"tab\\tcr\\r\"");
+        assert_eq!(
+            print_value("${A} $ENV{B} $CACHE{C} $<D> $E"),
+            "\"\\${A} \\$ENV{B} \\$CACHE{C} $<D> $E\""
+        );
+        assert_eq!(print_value("C:\\Program Files"), "[[\nC:\\Program Files]]");
+        assert_eq!(print_value("one\ntwo"), "[[\none\ntwo]]");
+        assert_eq!(print_value("\\]]"), "[=[\n\\]]]=]");
+        assert_eq!(print_value("\\]=]]"), "[==[\n\\]=]]]==]");
+    }
+
+    #[test]
+    fn test_variables() {
+        let argument = || CMakeArgument::new().variable("PREFIX").text("/lib");
+        assert_eq!(print_value(argument()), "${PREFIX}/lib");
+        assert_eq!(
+            print_value(argument().text("/my dir\\")),
+            "\"${PREFIX}/lib/my dir\\\\\""
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid variable name")]
+    fn test_invalid_variable() {
+        CMakeArgument::new().variable("a}b");
+    }
+
+    #[test]
+    #[should_panic(expected = "starts or ends a block")]
+    fn test_reserved_command() {
+        CMakeCommand::new("endif");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid keyword")]
+    fn test_invalid_keyword() {
+        CMakeCommand::new("install").keyword("Targets");
+    }
+
+    #[test]
+    fn test_conditions() {
+        let value = CMakeCondition::value;
+        let print_condition = |condition| {
+            print(
+                [IfBuilder::new(condition).build().into()]
+                    .into_iter()
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            print_condition(value("A").or(value("B")).and(!value("C"))),
+            "if((A OR B) AND NOT C)\nendif()"
+        );
+        assert_eq!(
+            print_condition((!value("A").and(value("B"))).or(value("C"))),
+            "if(NOT (A AND B) OR C)\nendif()"
+        );
+        assert_eq!(
+            print_condition(CMakeCondition::unary("DEFINED", value("X")).and(
+                CMakeCondition::binary(
+                    CMakeCondition::value(CMakeArgument::new().variable("X")),
+                    "STREQUAL",
+                    value("a b"),
+                )
+            )),
+            "if(DEFINED X AND ${X} STREQUAL \"a b\")\nendif()"
+        );
+        assert_eq!(
+            print_condition(value("AND").or(CMakeCondition::binary(
+                value("exists"),
+                "STREQUAL",
+                value("NOT")
+            ))),
+            "if(\"AND\" OR \"exists\" STREQUAL \"NOT\")\nendif()"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid binary test")]
+    fn test_invalid_binary_test() {
+        CMakeCondition::binary(
+            CMakeCondition::value("A"),
+            "BOGUS OP",
+            CMakeCondition::value("x"),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid unary test")]
+    fn test_invalid_unary_test() {
+        CMakeCondition::unary("AND", CMakeCondition::value("x"));
+    }
+
+    #[test]
+    fn test_document() {
+        let link = |library| {
+            CMakeCommand::new("target_link_libraries")
+                .arg("app")
+                .keyword("PRIVATE")
+                .arg(library)
+        };
+        let document = [
+            CMakeCommand::new("project").arg("demo").into(),
+            IfBuilder::new(CMakeCondition::value("WIN32"))
+                .statement(link("ws2_32"))
+                .else_if(CMakeCondition::value("APPLE"))
+                .statements([link("-framework Cocoa"), link("objc")])
+                .otherwise()
+                .statement(link("pthread"))
+                .build()
+                .into(),
+            FunctionBuilder::new("add_tool")
+                .params(["name", "source"])
+                .statement(
+                    CMakeCommand::new("add_executable")
+                        .arg(CMakeArgument::new().variable("name"))
+                        .arg(CMakeArgument::new().variable("source")),
+                )
+                .build()
+                .into(),
+            FunctionBuilder::new("nothing").build().into(),
+        ]
+        .into_iter()
+        .collect::<CMakeDocument>();
+        assert_eq!(
+            print(document),
+            [
+                "project(demo)",
+                "if(WIN32)",
+                "  target_link_libraries(app PRIVATE ws2_32)",
+                "elseif(APPLE)",
+                "  target_link_libraries(app PRIVATE \"-framework Cocoa\")",
+                "  target_link_libraries(app PRIVATE objc)",
+                "else()",
+                "  target_link_libraries(app PRIVATE pthread)",
+                "endif()",
+                "function(add_tool name source)",
+                "  add_executable(${name} ${source})",
+                "endfunction()",
+                "function(nothing)",
+                "endfunction()",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_long_command_breaks() {
+        let sources = (0..8).map(|i| format!("source_file_{i}.cpp"));
+        let output = print(
+            [CMakeCommand::new("add_library")
+                .arg("a_library")
+                .keyword("STATIC")
+                .args(sources)
+                .into()]
+            .into_iter()
+            .collect(),
+        );
+        assert!(output.lines().count() > 1, "{output}");
+    }
+}
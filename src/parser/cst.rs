@@ -0,0 +1,278 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! A lossless concrete syntax tree.
+//!
+//! Unlike [`CMakeDocument`](crate::parser::types::CMakeDocument), the CST
+//! keeps every byte of the input: whitespace, line endings, the spelling of
+//! command names and any malformed input. Printing a CST gives back exactly
+//! the text it was parsed from, so tools can edit tokens in place and print
+//! the file without reformatting it.
+//!
+//! Commands are not nested into blocks; a file is a flat list of commands and
+//! the trivia between them.
+
+use std::fmt::{Display, Formatter};
+
+use crate::parser::lexer::{tokenize, Token, TokenKind};
+use crate::parser::types::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    File,
+    /// A command name, optional whitespace and its `Arguments`.
+    Command,
+    /// Parenthesized arguments, including the parentheses.
+    Arguments,
+    /// Tokens that can't start a command, or a command name without
+    /// arguments.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub kind: TokenKind,
+    pub text: String,
+    /// Where the token was in the parsed input. Edited tokens keep the span
+    /// they were parsed with.
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    fn new(kind: NodeKind) -> Self {
+        SyntaxNode {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// All tokens under this node, in source order.
+    pub fn tokens(&self) -> Box<dyn Iterator<Item = &SyntaxToken> + '_> {
+        Box::new(self.children.iter().flat_map(|child| match child {
+            SyntaxElement::Token(token) => Box::new(std::iter::once(token)),
+            SyntaxElement::Node(node) => node.tokens(),
+        }))
+    }
+
+    pub fn tokens_mut(&mut self) -> Box<dyn Iterator<Item = &mut SyntaxToken> + '_> {
+        Box::new(self.children.iter_mut().flat_map(|child| match child {
+            SyntaxElement::Token(token) => Box::new(std::iter::once(token)),
+            SyntaxElement::Node(node) => node.tokens_mut(),
+        }))
+    }
+
+    /// The direct children of this node that are nodes.
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The name of a `Command` node.
+    pub fn command_name(&self) -> Option<&SyntaxToken> {
+        if self.kind != NodeKind::Command {
+            return None;
+        }
+        self.tokens().next()
+    }
+
+    /// The span covering all the tokens under this node.
+    pub fn span(&self) -> Span {
+        let mut tokens = self.tokens();
+        let first = tokens.next().map(|token| token.span).unwrap_or_default();
+        let last = tokens.last().map_or(first, |token| token.span);
+        Span::new(first.start, last.end)
+    }
+}
+
+impl Display for SyntaxNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.tokens().try_for_each(|token| f.write_str(&token.text))
+    }
+}
+
+struct Builder<'a> {
+    input: &'a str,
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+}
+
+impl<'a> Builder<'a> {
+    fn token(&self, token: Token) -> SyntaxElement {
+        SyntaxElement::Token(SyntaxToken {
+            kind: token.kind,
+            text: token.text(self.input).to_string(),
+            span: token.span,
+        })
+    }
+
+    fn peek_kind(&mut self) -> Option<TokenKind> {
+        self.tokens.peek().map(|token| token.kind)
+    }
+
+    /// Builds `Arguments` after its opening parenthesis has been consumed.
+    /// Unterminated arguments run until the end of the input.
+    fn arguments(&mut self, open: Token) -> SyntaxNode {
+        let mut node = SyntaxNode::new(NodeKind::Arguments);
+        node.children.push(self.token(open));
+        while let Some(token) = self.tokens.next() {
+            match token.kind {
+                TokenKind::LeftParen => {
+                    let nested = self.arguments(token);
+                    node.children.push(SyntaxElement::Node(nested));
+                }
+                TokenKind::RightParen => {
+                    node.children.push(self.token(token));
+                    break;
+                }
+                _ => node.children.push(self.token(token)),
+            }
+        }
+        node
+    }
+
+    fn command(&mut self, name: Token) -> SyntaxNode {
+        let mut node = SyntaxNode::new(NodeKind::Command);
+        node.children.push(self.token(name));
+        while self.peek_kind() == Some(TokenKind::Whitespace) {
+            let token = self.tokens.next().unwrap();
+            node.children.push(self.token(token));
+        }
+        match self.tokens.peek().copied() {
+            Some(open) if open.kind == TokenKind::LeftParen => {
+                self.tokens.next();
+                let arguments = self.arguments(open);
+                node.children.push(SyntaxElement::Node(arguments));
+            }
+            _ => node.kind = NodeKind::Error,
+        }
+        node
+    }
+
+    fn file(&mut self) -> SyntaxNode {
+        let mut file = SyntaxNode::new(NodeKind::File);
+        while let Some(token) = self.tokens.next() {
+            let element = match token.kind {
+                TokenKind::Whitespace
+                | TokenKind::Newline
+                | TokenKind::Comment
+                | TokenKind::BracketComment => self.token(token),
+                TokenKind::Identifier => SyntaxElement::Node(self.command(token)),
+                _ => {
+                    let mut error = SyntaxNode::new(NodeKind::Error);
+                    error.children.push(self.token(token));
+                    SyntaxElement::Node(error)
+                }
+            };
+            file.children.push(element);
+        }
+        file
+    }
+}
+
+/// Parses `input` into a `File` node. This never fails; input that isn't
+/// valid CMake ends up in `Error` nodes.
+pub fn parse(input: &str) -> SyntaxNode {
+    Builder {
+        input,
+        tokens: tokenize(input).into_iter().peekable(),
+    }
+    .file()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_samples() {
+        let cargo_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        for entry in std::fs::read_dir(format!("{}/samples", cargo_dir)).unwrap() {
+            let contents = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            assert_eq!(parse(&contents).to_string(), contents);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_every_byte() {
+        let inputs = [
+            "",
+            "IF (X)\r\n\tfoo( a\"b\",  )  # c\r\nENDIF ( )\n",
+            "message(\"a\",\"b\",)\n\n\n",
+            "#[=[ bracket\n]=] set(x [[y]] (nested (parens)))",
+            "foo(unterminated \"string",
+            ") stray ( parens",
+            "name_without_args\nfoo(\\) \\( \\\n)",
+        ];
+        for input in inputs {
+            assert_eq!(parse(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_commands_are_nodes() {
+        let file = parse("if (X)\n  foo(a (b))\nendif()\n");
+        let names: Vec<&str> = file
+            .child_nodes()
+            .filter_map(|node| node.command_name())
+            .map(|token| token.text.as_str())
+            .collect();
+        assert_eq!(names, vec!["if", "foo", "endif"]);
+
+        let foo = file.child_nodes().nth(1).unwrap();
+        assert_eq!(foo.to_string(), "foo(a (b))");
+        assert_eq!(foo.span().start.line, 2);
+        let arguments = foo.child_nodes().next().unwrap();
+        assert_eq!(arguments.kind, NodeKind::Arguments);
+        assert_eq!(arguments.child_nodes().next().unwrap().to_string(), "(b)");
+    }
+
+    #[test]
+    fn test_edit_tokens_in_place() {
+        let mut file = parse("add_library(foo  STATIC a.cpp)  # keep\n");
+        for token in file.tokens_mut() {
+            if token.text == "foo" {
+                token.text = String::from("bar");
+            }
+        }
+        assert_eq!(file.to_string(), "add_library(bar  STATIC a.cpp)  # keep\n");
+    }
+
+    #[test]
+    fn test_malformed_input_is_kept_in_error_nodes() {
+        let file = parse(") foo\n");
+        let kinds: Vec<NodeKind> = file.child_nodes().map(|node| node.kind).collect();
+        assert_eq!(kinds, vec![NodeKind::Error, NodeKind::Error]);
+    }
+}
@@ -0,0 +1,552 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Errors and warnings about a document, with the source locations they
+//! refer to. Parse errors are converted into the same [`Diagnostic`] type as
+//! the problems found in documents that parse.
+
+use nom_supreme::error::{BaseErrorKind, ErrorTree, Expectation, StackContext};
+use serde::{Deserialize, Serialize};
+
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeCommandGroup, CMakeCondition, CMakeDocument, CMakeStatement,
+    CMakeValue, Span,
+};
+use crate::parser::{position, ErrorType, Input};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A secondary location related to a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub struct Label {
+    #[serde(default)]
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Where the problem is.
+    #[serde(default)]
+    pub span: Span,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub notes: Vec<String>,
+    /// A suggestion on how to fix the problem.
+    #[serde(default)]
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            message: message.to_string(),
+            span,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn error(message: &str, span: Span) -> Self {
+        Diagnostic::new(Severity::Error, message, span)
+    }
+
+    pub fn warning(message: &str, span: Span) -> Self {
+        Diagnostic::new(Severity::Warning, message, span)
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Converts a parse error. The diagnostic points at the furthest position
+    /// the parser reached, which is usually where the input is wrong, and
+    /// lists what was expected there.
+    pub fn from_parse_error(error: &ErrorType<Input>) -> Self {
+        let mut furthest = Furthest::default();
+        furthest.visit(error, &[]);
+        let location = furthest
+            .location
+            .expect("parse errors have at least one location");
+        let at = Span::new(location, location);
+
+        let unterminated_quote = !furthest.at_end && is_unterminated_quote(furthest.rest);
+        let message = if let Some(message) = furthest.message {
+            Some(message)
+        } else if let Some(block) = furthest.block {
+            Some(format!("expected `end{}()`", block))
+        } else if unterminated_quote {
+            Some("unterminated quoted argument".to_string())
+        } else if !furthest.expected.is_empty() {
+            Some(format!("expected {}", furthest.expected.join(" or ")))
+        } else {
+            match furthest.rest.chars().next() {
+                None => None,
+                Some('\r' | '\n') => Some("unexpected end of line".to_string()),
+                Some(c) => Some(format!("unexpected `{}`", c)),
+            }
+        };
+        let message = match message {
+            Some(message) if furthest.at_end => format!("unexpected end of file, {}", message),
+            Some(message) => message,
+            None => "unexpected end of file".to_string(),
+        };
+        let mut diagnostic = Diagnostic::error(&message, at);
+        let context = furthest
+            .context
+            .filter(|(span, _)| span.start.offset != location.offset);
+        if let Some((span, context)) = context {
+            diagnostic = diagnostic.with_label(span, &format!("while parsing this {}", context));
+        }
+        if let Some(block) = furthest.block {
+            diagnostic = diagnostic.with_help(&format!(
+                "every `{}()` needs a matching `end{}()`",
+                block, block
+            ));
+        } else if unterminated_quote {
+            diagnostic = diagnostic.with_help("check for a missing `\"`");
+        } else if furthest.at_end && furthest.expected.iter().any(|e| e == "`)`") {
+            diagnostic = diagnostic.with_help("check for a missing `)`");
+        }
+        diagnostic
+    }
+}
+
+/// The statements that are blocks, named as their parser contexts.
+const BLOCKS: [&str; 6] = ["if", "foreach", "while", "function", "macro", "block"];
+
+/// The block whose closing command was expected at `here`, when the parser
+/// failed to find it while in `contexts`.
+fn unclosed_block(
+    contexts: &[(Span, &'static str)],
+    here: crate::parser::types::Position,
+) -> Option<&'static str> {
+    let (span, context) = contexts.last()?;
+    match *context {
+        "parse_endif" => Some("if"),
+        "parse_endwhile" => Some("while"),
+        // Where a block starts, the context is its opening command failing
+        context if span.start.offset < here.offset => {
+            BLOCKS.iter().copied().find(|block| *block == context)
+        }
+        _ => None,
+    }
+}
+
+/// Whether `rest` starts with a quoted argument that is never closed.
+fn is_unterminated_quote(rest: &str) -> bool {
+    let Some(contents) = rest.strip_prefix('"') else {
+        return false;
+    };
+    let mut escaped = false;
+    for c in contents.chars() {
+        match c {
+            '"' if !escaped => return false,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    true
+}
+
+/// The furthest position in a parse error, and what was expected there.
+#[derive(Default)]
+struct Furthest<'a> {
+    location: Option<crate::parser::types::Position>,
+    /// The input from the furthest position on.
+    rest: &'a str,
+    at_end: bool,
+    /// The characters the user could have written there, such as `)`.
+    expected: Vec<String>,
+    /// A block whose closing command was expected, such as `if`.
+    block: Option<&'static str>,
+    /// A message from a parser, which takes the place of `expected`.
+    message: Option<String>,
+    /// The outermost context the furthest error was found in.
+    context: Option<(Span, &'static str)>,
+}
+
+impl<'a> Furthest<'a> {
+    fn visit(&mut self, error: &ErrorType<Input<'a>>, contexts: &[(Span, &'static str)]) {
+        match error {
+            ErrorTree::Base { location, kind } => {
+                let here = position(location);
+                match self.location {
+                    Some(furthest) if furthest.offset > here.offset => return,
+                    Some(furthest) if furthest.offset == here.offset => {}
+                    _ => {
+                        self.location = Some(here);
+                        self.rest = location.fragment();
+                        self.expected.clear();
+                        self.block = None;
+                        self.message = None;
+                        self.context = contexts.first().copied();
+                    }
+                }
+                self.at_end = location.fragment().is_empty();
+                match kind {
+                    // The other expectations come from the alternatives for
+                    // what could start a statement or an argument, and only
+                    // say which parsers were tried
+                    BaseErrorKind::Expected(Expectation::Char(c)) if "()".contains(*c) => {
+                        let expected = format!("`{}`", c);
+                        if !self.expected.contains(&expected) {
+                            self.expected.push(expected);
+                        }
+                    }
+                    BaseErrorKind::Expected(_) => {}
+                    BaseErrorKind::External(error) => self.message = Some(error.to_string()),
+                    BaseErrorKind::Kind(_) => {
+                        if self.block.is_none() {
+                            self.block = unclosed_block(contexts, here);
+                        }
+                    }
+                }
+            }
+            ErrorTree::Stack {
+                base,
+                contexts: stack,
+            } => {
+                let mut contexts = contexts.to_vec();
+                // The stack lists the innermost context first
+                for (location, context) in stack.iter().rev() {
+                    if let StackContext::Context(context) = context {
+                        let at = position(location);
+                        contexts.push((Span::new(at, at), *context));
+                    }
+                }
+                self.visit(base, &contexts);
+            }
+            ErrorTree::Alt(errors) => {
+                for error in errors {
+                    self.visit(error, contexts);
+                }
+            }
+        }
+    }
+}
+
+fn check_parts(parts: &[ArgumentPart], diagnostics: &mut Vec<Diagnostic>) {
+    for part in parts {
+        match part {
+            ArgumentPart::GeneratorExpression(expression) => {
+                if !expression.terminated {
+                    diagnostics.push(
+                        Diagnostic::warning(
+                            "unterminated generator expression, expected `>`",
+                            expression.span,
+                        )
+                        .with_help(
+                            "generator expressions are closed with `>`, as in `$<CONFIG:Debug>`",
+                        ),
+                    );
+                }
+                if expression.name.is_empty() {
+                    diagnostics.push(Diagnostic::warning(
+                        "generator expression has no name",
+                        expression.span,
+                    ));
+                }
+                check_parts(&expression.name, diagnostics);
+                for parameter in &expression.parameters {
+                    check_parts(parameter, diagnostics);
+                }
+            }
+            ArgumentPart::Variable(variable) => check_parts(&variable.name, diagnostics),
+            ArgumentPart::Text(_) | ArgumentPart::Escape(_) | ArgumentPart::LineContinuation(_) => {
+            }
+        }
+    }
+}
+
+/// Reports the problems in a single argument.
+pub fn check_argument(argument: &CMakeArgument) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_parts(&argument.parts, &mut diagnostics);
+    diagnostics
+}
+
+fn check_values(values: &[CMakeValue], diagnostics: &mut Vec<Diagnostic>) {
+    for value in values {
+        if let CMakeValue::QuotedString(argument, _) | CMakeValue::StringLiteral(argument, _) =
+            value
+        {
+            check_parts(&argument.parts, diagnostics);
+        }
+    }
+}
+
+fn check_condition(condition: &CMakeCondition, diagnostics: &mut Vec<Diagnostic>) {
+    match condition {
+        CMakeCondition::Parentheses { value, .. }
+        | CMakeCondition::UnaryTest { value, .. }
+        | CMakeCondition::UnaryLogicalOperator { value, .. } => check_condition(value, diagnostics),
+        CMakeCondition::BinaryTest { left, right, .. }
+        | CMakeCondition::BinaryLogicalOperator { left, right, .. } => {
+            check_condition(left, diagnostics);
+            check_condition(right, diagnostics);
+        }
+        CMakeCondition::Comment { tail, .. } | CMakeCondition::BracketComment { tail, .. } => {
+            if let Some(tail) = tail {
+                check_condition(tail, diagnostics);
+            }
+        }
+        CMakeCondition::TrailingComment { value, .. } => check_condition(value, diagnostics),
+        CMakeCondition::Variable(variable) => check_parts(&variable.name, diagnostics),
+        CMakeCondition::Value(value) => check_values(std::slice::from_ref(value), diagnostics),
+    }
+}
+
+fn check_group(group: &CMakeCommandGroup, diagnostics: &mut Vec<Diagnostic>) {
+    check_values(&group.clause, diagnostics);
+    check_statements(&group.body, diagnostics);
+    check_values(&group.end_clause, diagnostics);
+}
+
+pub(crate) fn check_statements(statements: &[CMakeStatement], diagnostics: &mut Vec<Diagnostic>) {
+    for statement in statements {
+        match statement {
+            CMakeStatement::If(statement) => {
+                for base in std::iter::once(&statement.base).chain(&statement.else_ifs) {
+                    check_condition(&base.condition, diagnostics);
+                    check_statements(&base.body, diagnostics);
+                }
+                if let Some(body) = &statement.else_body {
+                    check_statements(body, diagnostics);
+                }
+            }
+            CMakeStatement::While(statement) => {
+                check_condition(&statement.condition, diagnostics);
+                check_statements(&statement.body, diagnostics);
+            }
+            CMakeStatement::For(statement) => check_group(&statement.group, diagnostics),
+            CMakeStatement::Function(statement) => check_group(&statement.group, diagnostics),
+            CMakeStatement::Block(statement) => check_group(&statement.group, diagnostics),
+            CMakeStatement::Macro(statement) => check_group(&statement.group, diagnostics),
+            CMakeStatement::Command(command) => check_values(&command.args, diagnostics),
+            CMakeStatement::Error(error) => diagnostics.push(error.diagnostic.clone()),
+            CMakeStatement::BracketComment(..)
+            | CMakeStatement::Comment(..)
+            | CMakeStatement::Newline(_) => {}
+        }
+    }
+}
+
+/// Reports the problems in a document, in source order.
+pub fn check(document: &CMakeDocument) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_statements(&document.statements, &mut diagnostics);
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use nom_supreme::final_parser::final_parser;
+
+    use super::*;
+    use crate::parser::types::Position;
+    use crate::parser::{cmake_parser, Input};
+
+    fn check_source(source: &str) -> Vec<(String, usize, usize)> {
+        let document = final_parser(cmake_parser)(Input::new(source))
+            .map_err(|err: nom_supreme::error::ErrorTree<Input>| err.to_string())
+            .unwrap();
+        check(&document)
+            .into_iter()
+            .map(|diagnostic| {
+                let start = diagnostic.span.start;
+                (diagnostic.message, start.line, start.column)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_well_formed_generator_expressions() {
+        let source = "target_compile_options(foo PRIVATE $<$<CONFIG:Debug>:-O0> \"$<IF:$<BOOL:${X}>,a,b>\")\n";
+        assert_eq!(check_source(source), vec![]);
+    }
+
+    #[test]
+    fn test_unterminated_generator_expressions() {
+        let source = "if(X)\n  target_link_libraries(foo $<$<CONFIG:Debug>:bar \"$<TARGET_FILE:baz\")\nendif()\n";
+        assert_eq!(
+            check_source(source),
+            vec![
+                (
+                    String::from("unterminated generator expression, expected `>`"),
+                    2,
+                    29
+                ),
+                (
+                    String::from("unterminated generator expression, expected `>`"),
+                    2,
+                    52
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generator_expression_without_name() {
+        let diagnostics = check_argument(&CMakeArgument::from("a$<>b"));
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::warning(
+                "generator expression has no name",
+                Span::new(
+                    Position {
+                        offset: 1,
+                        line: 1,
+                        column: 2
+                    },
+                    Position {
+                        offset: 4,
+                        line: 1,
+                        column: 5
+                    }
+                )
+            )]
+        );
+        assert_eq!(diagnostics[0].span.range(), 1..4);
+    }
+
+    fn parse_error(source: &str) -> Diagnostic {
+        match crate::parser::cmake_parser(Input::new(source)) {
+            Err(nom::Err::Error(error)) => Diagnostic::from_parse_error(&error),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_points_at_the_furthest_position() {
+        let diagnostic = parse_error(
+            "project(foo)
+if(A B)
+endif()
+",
+        );
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.message, "expected `)`");
+        let start = diagnostic.span.start;
+        assert_eq!((start.line, start.column), (2, 6));
+        let labels: Vec<_> = diagnostic
+            .labels
+            .iter()
+            .map(|label| (label.message.as_str(), label.span.start.column))
+            .collect();
+        assert_eq!(labels, vec![("while parsing this if", 1)]);
+    }
+
+    #[test]
+    fn test_parse_error_at_end_of_file() {
+        let diagnostic = parse_error("foo(a b");
+        assert_eq!(diagnostic.message, "unexpected end of file, expected `)`");
+        assert_eq!(diagnostic.span.start.column, 8);
+        assert_eq!(diagnostic.help.as_deref(), Some("check for a missing `)`"));
+    }
+
+    #[test]
+    fn test_parse_error_for_unclosed_block() {
+        for (source, end) in [
+            ("if(a)\nset(x)\n", "endif"),
+            ("if(a)\nelse()\n", "endif"),
+            ("foreach(x y)\n", "endforeach"),
+            ("while(a)\n", "endwhile"),
+            ("function(f)\nset(x)\n", "endfunction"),
+            ("macro(m)\n", "endmacro"),
+            ("block()\n", "endblock"),
+        ] {
+            let diagnostic = parse_error(source);
+            assert_eq!(
+                diagnostic.message,
+                format!("unexpected end of file, expected `{}()`", end),
+                "{}",
+                source
+            );
+            assert!(
+                diagnostic
+                    .help
+                    .as_deref()
+                    .unwrap()
+                    .ends_with(&format!("`{}()`", end)),
+                "{}",
+                source
+            );
+        }
+        // A block whose opening command isn't closed is missing a `)`
+        let diagnostic = parse_error("foreach(x y\n");
+        assert_eq!(diagnostic.message, "unexpected end of file, expected `)`");
+    }
+
+    #[test]
+    fn test_parse_error_for_unexpected_character() {
+        let diagnostic = parse_error("foo(a))\n");
+        assert_eq!(diagnostic.message, "unexpected `)`");
+        assert_eq!(diagnostic.span.start.column, 7);
+        assert_eq!(diagnostic.help, None);
+    }
+
+    #[test]
+    fn test_parse_error_for_unterminated_quote() {
+        let diagnostic = parse_error("set(x \"a\\\"b\n");
+        assert_eq!(diagnostic.message, "unterminated quoted argument");
+        assert_eq!(diagnostic.span.start.column, 7);
+        assert_eq!(diagnostic.help.as_deref(), Some("check for a missing `\"`"));
+    }
+
+    #[test]
+    fn test_parse_error_for_block_keyword() {
+        let diagnostic = parse_error(
+            "endif()
+",
+        );
+        assert_eq!(
+            diagnostic.message,
+            "unexpected `endif()`, which isn't part of a block"
+        );
+        assert!(diagnostic.labels.is_empty());
+    }
+}
@@ -0,0 +1,922 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! The syntax tree as JSON, for tools written in other languages.
+//!
+//! [`to_json`] writes a [`CMakeDocument`] and [`from_json`] reads one back,
+//! so a document can be printed again from its JSON. The schema is versioned
+//! by the top-level `version` field, currently [`VERSION`]. It only changes
+//! in backward compatible ways, such as new node kinds, while the version
+//! stays the same.
+//!
+//! Every node is an object whose `kind` field names its type, in
+//! `snake_case`. Nodes that come from the source have a `span`:
+//!
+//! ```json
+//! {"start": {"offset": 0, "line": 1, "column": 1},
+//!  "end": {"offset": 12, "line": 1, "column": 13}}
+//! ```
+//!
+//! where `offset` counts bytes and `line` and `column` start at 1. Spans are
+//! optional when reading. The nodes are:
+//!
+//! * The document: `{"version": 1, "statements": [statement]}`
+//! * Statements:
+//!   * `command`: `name`, `arguments: [value]`
+//!   * `if`: `condition`, `body: [statement]`, `else_ifs: [{"condition",
+//!     "body"}]` and `else_body: [statement] | null`
+//!   * `while`: `condition`, `body`
+//!   * `foreach`, `function`, `macro`, `block`: `arguments` of the opening
+//!     command, `body` and `end_arguments` of the closing command
+//!   * `comment`: `text` after the `#`
+//!   * `bracket_comment`: `delimiter`, the `=` signs, and `contents`
+//!   * `newline`: a line break. Blank lines are consecutive newlines
+//!   * `error`: `text` that failed to parse, kept as is, and the
+//!     `diagnostic` explaining why: `severity` (`error` or `warning`),
+//!     `message`, `span`, `labels: [{"span", "message"}]`, `notes: [string]`
+//!     and `help: string | null`
+//! * Values, the arguments of commands:
+//!   * `keyword`: `text`, an unquoted upper-case word such as `PRIVATE`
+//!   * `unquoted`, `quoted`: `parts`, without the quotes
+//!   * `bracket`: `delimiter` and `contents` of `[=[...]=]`
+//!   * `comment`, `bracket_comment`: as the statements
+//!   * `parenthesis`: `text`, `(` or `)`, nested in the arguments
+//! * Parts of unquoted and quoted values:
+//!   * `text`: `text`
+//!   * `escape`: the escaped `character`, as in `\n`
+//!   * `line_continuation`: the `line_ending` after a trailing `\`
+//!   * `variable`: `type` (`normal` for `${}`, `env`, `cache` or `at` for
+//!     `@VAR@`) and `name: [part]`
+//!   * `generator_expression`: `name: [part]`, `parameters: [[part]]` and
+//!     whether it is `terminated` by `>`
+//! * Conditions:
+//!   * `parentheses`: `value`
+//!   * `unary_test`, `unary_logical`: `operator`, `value`
+//!   * `binary_test`, `binary_logical`: `operator`, `left`, `right`
+//!   * `comment`: `text` and the condition after it, `tail`, or `null`
+//!   * `bracket_comment`: `delimiter`, `contents` and `tail`
+//!   * `trailing_comment`: `value` and the `comment` after it
+//!   * `variable`: as the part, for `DEFINED ENV{NAME}`
+//!   * `value`: a `value`
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::diagnostics::Diagnostic;
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand,
+    CMakeCommandGroup, CMakeCondition, CMakeDocument, CMakeErrorStatement, CMakeForEachStatement,
+    CMakeFunctionStatement, CMakeIfBase, CMakeIfStatement, CMakeMacroStatement, CMakeStatement,
+    CMakeValue, CMakeWhileStatement, GeneratorExpression, Span, VariableKind, VariableReference,
+};
+
+/// The version of the schema written by [`to_json`].
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    version: u32,
+    statements: Vec<Statement>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Statement {
+    Command {
+        name: String,
+        arguments: Vec<Value>,
+        #[serde(default)]
+        span: Span,
+    },
+    If {
+        condition: Condition,
+        body: Vec<Statement>,
+        else_ifs: Vec<Branch>,
+        else_body: Option<Vec<Statement>>,
+        #[serde(default)]
+        span: Span,
+    },
+    While {
+        condition: Condition,
+        body: Vec<Statement>,
+        #[serde(default)]
+        span: Span,
+    },
+    Foreach(Group),
+    Function(Group),
+    Macro(Group),
+    Block(Group),
+    Comment {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+    BracketComment {
+        delimiter: String,
+        contents: String,
+        #[serde(default)]
+        span: Span,
+    },
+    Newline {
+        #[serde(default)]
+        span: Span,
+    },
+    Error {
+        text: String,
+        diagnostic: Diagnostic,
+        #[serde(default)]
+        span: Span,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Branch {
+    condition: Condition,
+    body: Vec<Statement>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Group {
+    arguments: Vec<Value>,
+    body: Vec<Statement>,
+    end_arguments: Vec<Value>,
+    #[serde(default)]
+    span: Span,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Value {
+    Keyword {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+    Unquoted {
+        parts: Vec<Part>,
+        #[serde(default)]
+        span: Span,
+    },
+    Quoted {
+        parts: Vec<Part>,
+        #[serde(default)]
+        span: Span,
+    },
+    Bracket {
+        delimiter: String,
+        contents: String,
+        #[serde(default)]
+        span: Span,
+    },
+    Comment {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+    BracketComment {
+        delimiter: String,
+        contents: String,
+        #[serde(default)]
+        span: Span,
+    },
+    Parenthesis {
+        text: String,
+        #[serde(default)]
+        span: Span,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Part {
+    Text {
+        text: String,
+    },
+    Escape {
+        character: char,
+    },
+    LineContinuation {
+        line_ending: String,
+    },
+    Variable(Variable),
+    GeneratorExpression {
+        name: Vec<Part>,
+        parameters: Vec<Vec<Part>>,
+        terminated: bool,
+        #[serde(default)]
+        span: Span,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Variable {
+    #[serde(rename = "type")]
+    kind: VariableType,
+    name: Vec<Part>,
+    #[serde(default)]
+    span: Span,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum VariableType {
+    Normal,
+    Env,
+    Cache,
+    At,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Condition {
+    Parentheses {
+        value: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    UnaryTest {
+        operator: String,
+        value: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    BinaryTest {
+        operator: String,
+        left: Box<Condition>,
+        right: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    UnaryLogical {
+        operator: String,
+        value: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    BinaryLogical {
+        operator: String,
+        left: Box<Condition>,
+        right: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    Comment {
+        text: String,
+        tail: Option<Box<Condition>>,
+        #[serde(default)]
+        span: Span,
+    },
+    BracketComment {
+        delimiter: String,
+        contents: String,
+        tail: Option<Box<Condition>>,
+        #[serde(default)]
+        span: Span,
+    },
+    TrailingComment {
+        value: Box<Condition>,
+        comment: Box<Condition>,
+        #[serde(default)]
+        span: Span,
+    },
+    Variable(Variable),
+    Value {
+        value: Value,
+    },
+}
+
+/// Writes `document` as pretty-printed JSON.
+pub fn to_json(document: &CMakeDocument<'_>) -> String {
+    let document = Document {
+        version: VERSION,
+        statements: statements_to_json(&document.statements),
+    };
+    serde_json::to_string_pretty(&document).expect("the syntax tree is valid JSON")
+}
+
+/// Reads a document written by [`to_json`], or built by hand following the
+/// same schema.
+pub fn from_json(json: &str) -> Result<CMakeDocument<'static>, serde_json::Error> {
+    let document: Document = serde_json::from_str(json)?;
+    if document.version != VERSION {
+        return Err(serde::de::Error::custom(format!(
+            "unsupported version {}, expected {}",
+            document.version, VERSION
+        )));
+    }
+    Ok(CMakeDocument {
+        statements: statements_from_json(document.statements),
+    })
+}
+
+fn statements_to_json(statements: &[CMakeStatement<'_>]) -> Vec<Statement> {
+    statements.iter().map(statement_to_json).collect()
+}
+
+fn statements_from_json(statements: Vec<Statement>) -> Vec<CMakeStatement<'static>> {
+    statements.into_iter().map(statement_from_json).collect()
+}
+
+fn values_to_json(values: &[CMakeValue<'_>]) -> Vec<Value> {
+    values.iter().map(value_to_json).collect()
+}
+
+fn values_from_json(values: Vec<Value>) -> Vec<CMakeValue<'static>> {
+    values.into_iter().map(value_from_json).collect()
+}
+
+fn parts_to_json(parts: &[ArgumentPart<'_>]) -> Vec<Part> {
+    parts.iter().map(part_to_json).collect()
+}
+
+fn parts_from_json(parts: Vec<Part>) -> Vec<ArgumentPart<'static>> {
+    parts.into_iter().map(part_from_json).collect()
+}
+
+fn statement_to_json(statement: &CMakeStatement<'_>) -> Statement {
+    let group = |group: &CMakeCommandGroup| Group {
+        arguments: values_to_json(&group.clause),
+        body: statements_to_json(&group.body),
+        end_arguments: values_to_json(&group.end_clause),
+        span: group.span,
+    };
+    let branch = |base: &CMakeIfBase| Branch {
+        condition: condition_to_json(&base.condition),
+        body: statements_to_json(&base.body),
+    };
+    match statement {
+        CMakeStatement::Command(command) => Statement::Command {
+            name: command.name.to_string(),
+            arguments: values_to_json(&command.args),
+            span: command.span,
+        },
+        CMakeStatement::If(statement) => Statement::If {
+            condition: condition_to_json(&statement.base.condition),
+            body: statements_to_json(&statement.base.body),
+            else_ifs: statement.else_ifs.iter().map(branch).collect(),
+            else_body: statement.else_body.as_deref().map(statements_to_json),
+            span: statement.span,
+        },
+        CMakeStatement::While(statement) => Statement::While {
+            condition: condition_to_json(&statement.condition),
+            body: statements_to_json(&statement.body),
+            span: statement.span,
+        },
+        CMakeStatement::For(statement) => Statement::Foreach(group(&statement.group)),
+        CMakeStatement::Function(statement) => Statement::Function(group(&statement.group)),
+        CMakeStatement::Macro(statement) => Statement::Macro(group(&statement.group)),
+        CMakeStatement::Block(statement) => Statement::Block(group(&statement.group)),
+        CMakeStatement::Comment(text, span) => Statement::Comment {
+            text: text.to_string(),
+            span: *span,
+        },
+        CMakeStatement::BracketComment(comment, span) => Statement::BracketComment {
+            delimiter: comment.delimiter.to_string(),
+            contents: comment.contents.to_string(),
+            span: *span,
+        },
+        CMakeStatement::Newline(span) => Statement::Newline { span: *span },
+        CMakeStatement::Error(error) => Statement::Error {
+            text: error.text.to_string(),
+            diagnostic: error.diagnostic.clone(),
+            span: error.span,
+        },
+    }
+}
+
+fn statement_from_json(statement: Statement) -> CMakeStatement<'static> {
+    let group = |group: Group| CMakeCommandGroup {
+        clause: values_from_json(group.arguments),
+        body: statements_from_json(group.body),
+        end_clause: values_from_json(group.end_arguments),
+        span: group.span,
+    };
+    match statement {
+        Statement::Command {
+            name,
+            arguments,
+            span,
+        } => CMakeStatement::Command(CMakeCommand {
+            name: name.into(),
+            args: values_from_json(arguments),
+            span,
+        }),
+        Statement::If {
+            condition,
+            body,
+            else_ifs,
+            else_body,
+            span,
+        } => CMakeStatement::If(CMakeIfStatement {
+            base: CMakeIfBase {
+                condition: condition_from_json(condition),
+                body: statements_from_json(body),
+            },
+            else_ifs: else_ifs
+                .into_iter()
+                .map(|branch| CMakeIfBase {
+                    condition: condition_from_json(branch.condition),
+                    body: statements_from_json(branch.body),
+                })
+                .collect(),
+            else_body: else_body.map(statements_from_json),
+            span,
+        }),
+        Statement::While {
+            condition,
+            body,
+            span,
+        } => CMakeStatement::While(CMakeWhileStatement {
+            condition: condition_from_json(condition),
+            body: statements_from_json(body),
+            span,
+        }),
+        Statement::Foreach(g) => CMakeStatement::For(CMakeForEachStatement { group: group(g) }),
+        Statement::Function(g) => {
+            CMakeStatement::Function(CMakeFunctionStatement { group: group(g) })
+        }
+        Statement::Macro(g) => CMakeStatement::Macro(CMakeMacroStatement { group: group(g) }),
+        Statement::Block(g) => CMakeStatement::Block(CMakeBlockStatement { group: group(g) }),
+        Statement::Comment { text, span } => CMakeStatement::Comment(text.into(), span),
+        Statement::BracketComment {
+            delimiter,
+            contents,
+            span,
+        } => CMakeStatement::BracketComment(bracket(true, delimiter, contents), span),
+        Statement::Newline { span } => CMakeStatement::Newline(span),
+        Statement::Error {
+            text,
+            diagnostic,
+            span,
+        } => CMakeStatement::Error(CMakeErrorStatement {
+            text: text.into(),
+            diagnostic,
+            span,
+        }),
+    }
+}
+
+fn bracket(is_comment: bool, delimiter: String, contents: String) -> CMakeBracketLiteral<'static> {
+    CMakeBracketLiteral {
+        is_comment,
+        delimiter: delimiter.into(),
+        contents: contents.into(),
+    }
+}
+
+fn value_to_json(value: &CMakeValue<'_>) -> Value {
+    match value {
+        CMakeValue::ArgumentSpecifier(text, span) => Value::Keyword {
+            text: text.to_string(),
+            span: *span,
+        },
+        CMakeValue::StringLiteral(argument, span) => Value::Unquoted {
+            parts: parts_to_json(&argument.parts),
+            span: *span,
+        },
+        CMakeValue::QuotedString(argument, span) => Value::Quoted {
+            parts: parts_to_json(&argument.parts),
+            span: *span,
+        },
+        CMakeValue::BracketQuotedString(literal, span) => Value::Bracket {
+            delimiter: literal.delimiter.to_string(),
+            contents: literal.contents.to_string(),
+            span: *span,
+        },
+        CMakeValue::Comment(text, span) => Value::Comment {
+            text: text.to_string(),
+            span: *span,
+        },
+        CMakeValue::BracketComment(literal, span) => Value::BracketComment {
+            delimiter: literal.delimiter.to_string(),
+            contents: literal.contents.to_string(),
+            span: *span,
+        },
+        CMakeValue::Parenthesis(text, span) => Value::Parenthesis {
+            text: text.to_string(),
+            span: *span,
+        },
+    }
+}
+
+fn value_from_json(value: Value) -> CMakeValue<'static> {
+    match value {
+        Value::Keyword { text, span } => CMakeValue::ArgumentSpecifier(text.into(), span),
+        Value::Unquoted { parts, span } => CMakeValue::StringLiteral(
+            CMakeArgument {
+                parts: parts_from_json(parts),
+            },
+            span,
+        ),
+        Value::Quoted { parts, span } => CMakeValue::QuotedString(
+            CMakeArgument {
+                parts: parts_from_json(parts),
+            },
+            span,
+        ),
+        Value::Bracket {
+            delimiter,
+            contents,
+            span,
+        } => CMakeValue::BracketQuotedString(bracket(false, delimiter, contents), span),
+        Value::Comment { text, span } => CMakeValue::Comment(text.into(), span),
+        Value::BracketComment {
+            delimiter,
+            contents,
+            span,
+        } => CMakeValue::BracketComment(bracket(true, delimiter, contents), span),
+        Value::Parenthesis { text, span } => CMakeValue::Parenthesis(text.into(), span),
+    }
+}
+
+fn variable_to_json(variable: &VariableReference<'_>) -> Variable {
+    Variable {
+        kind: match variable.kind {
+            VariableKind::Normal => VariableType::Normal,
+            VariableKind::Env => VariableType::Env,
+            VariableKind::Cache => VariableType::Cache,
+            VariableKind::At => VariableType::At,
+        },
+        name: parts_to_json(&variable.name),
+        span: variable.span,
+    }
+}
+
+fn variable_from_json(variable: Variable) -> VariableReference<'static> {
+    VariableReference {
+        kind: match variable.kind {
+            VariableType::Normal => VariableKind::Normal,
+            VariableType::Env => VariableKind::Env,
+            VariableType::Cache => VariableKind::Cache,
+            VariableType::At => VariableKind::At,
+        },
+        name: parts_from_json(variable.name),
+        span: variable.span,
+    }
+}
+
+fn part_to_json(part: &ArgumentPart<'_>) -> Part {
+    match part {
+        ArgumentPart::Text(text) => Part::Text {
+            text: text.to_string(),
+        },
+        ArgumentPart::Escape(character) => Part::Escape {
+            character: *character,
+        },
+        ArgumentPart::LineContinuation(line_ending) => Part::LineContinuation {
+            line_ending: line_ending.to_string(),
+        },
+        ArgumentPart::Variable(variable) => Part::Variable(variable_to_json(variable)),
+        ArgumentPart::GeneratorExpression(expression) => Part::GeneratorExpression {
+            name: parts_to_json(&expression.name),
+            parameters: expression
+                .parameters
+                .iter()
+                .map(|parameter| parts_to_json(parameter))
+                .collect(),
+            terminated: expression.terminated,
+            span: expression.span,
+        },
+    }
+}
+
+fn part_from_json(part: Part) -> ArgumentPart<'static> {
+    match part {
+        Part::Text { text } => ArgumentPart::Text(text.into()),
+        Part::Escape { character } => ArgumentPart::Escape(character),
+        Part::LineContinuation { line_ending } => {
+            ArgumentPart::LineContinuation(line_ending.into())
+        }
+        Part::Variable(variable) => ArgumentPart::Variable(variable_from_json(variable)),
+        Part::GeneratorExpression {
+            name,
+            parameters,
+            terminated,
+            span,
+        } => ArgumentPart::GeneratorExpression(GeneratorExpression {
+            name: parts_from_json(name),
+            parameters: parameters.into_iter().map(parts_from_json).collect(),
+            terminated,
+            span,
+        }),
+    }
+}
+
+fn condition_to_json(condition: &CMakeCondition<'_>) -> Condition {
+    let boxed = |condition: &CMakeCondition| Box::new(condition_to_json(condition));
+    match condition {
+        CMakeCondition::Parentheses { value, span } => Condition::Parentheses {
+            value: boxed(value),
+            span: *span,
+        },
+        CMakeCondition::UnaryTest {
+            operator,
+            value,
+            span,
+        } => Condition::UnaryTest {
+            operator: operator.to_string(),
+            value: boxed(value),
+            span: *span,
+        },
+        CMakeCondition::BinaryTest {
+            operator,
+            left,
+            right,
+            span,
+        } => Condition::BinaryTest {
+            operator: operator.to_string(),
+            left: boxed(left),
+            right: boxed(right),
+            span: *span,
+        },
+        CMakeCondition::UnaryLogicalOperator {
+            operator,
+            value,
+            span,
+        } => Condition::UnaryLogical {
+            operator: operator.to_string(),
+            value: boxed(value),
+            span: *span,
+        },
+        CMakeCondition::BinaryLogicalOperator {
+            operator,
+            left,
+            right,
+            span,
+        } => Condition::BinaryLogical {
+            operator: operator.to_string(),
+            left: boxed(left),
+            right: boxed(right),
+            span: *span,
+        },
+        CMakeCondition::Comment {
+            content,
+            tail,
+            span,
+        } => Condition::Comment {
+            text: content.to_string(),
+            tail: tail.as_deref().map(boxed),
+            span: *span,
+        },
+        CMakeCondition::BracketComment {
+            comment,
+            tail,
+            span,
+        } => Condition::BracketComment {
+            delimiter: comment.delimiter.to_string(),
+            contents: comment.contents.to_string(),
+            tail: tail.as_deref().map(boxed),
+            span: *span,
+        },
+        CMakeCondition::TrailingComment {
+            value,
+            comment,
+            span,
+        } => Condition::TrailingComment {
+            value: boxed(value),
+            comment: boxed(comment),
+            span: *span,
+        },
+        CMakeCondition::Variable(variable) => Condition::Variable(variable_to_json(variable)),
+        CMakeCondition::Value(value) => Condition::Value {
+            value: value_to_json(value),
+        },
+    }
+}
+
+fn condition_from_json(condition: Condition) -> CMakeCondition<'static> {
+    let boxed = |condition: Box<Condition>| Box::new(condition_from_json(*condition));
+    match condition {
+        Condition::Parentheses { value, span } => CMakeCondition::Parentheses {
+            value: boxed(value),
+            span,
+        },
+        Condition::UnaryTest {
+            operator,
+            value,
+            span,
+        } => CMakeCondition::UnaryTest {
+            operator: operator.into(),
+            value: boxed(value),
+            span,
+        },
+        Condition::BinaryTest {
+            operator,
+            left,
+            right,
+            span,
+        } => CMakeCondition::BinaryTest {
+            operator: operator.into(),
+            left: boxed(left),
+            right: boxed(right),
+            span,
+        },
+        Condition::UnaryLogical {
+            operator,
+            value,
+            span,
+        } => CMakeCondition::UnaryLogicalOperator {
+            operator: operator.into(),
+            value: boxed(value),
+            span,
+        },
+        Condition::BinaryLogical {
+            operator,
+            left,
+            right,
+            span,
+        } => CMakeCondition::BinaryLogicalOperator {
+            operator: operator.into(),
+            left: boxed(left),
+            right: boxed(right),
+            span,
+        },
+        Condition::Comment { text, tail, span } => CMakeCondition::Comment {
+            content: text.into(),
+            tail: tail.map(boxed),
+            span,
+        },
+        Condition::BracketComment {
+            delimiter,
+            contents,
+            tail,
+            span,
+        } => CMakeCondition::BracketComment {
+            comment: bracket(true, delimiter, contents),
+            tail: tail.map(boxed),
+            span,
+        },
+        Condition::TrailingComment {
+            value,
+            comment,
+            span,
+        } => CMakeCondition::TrailingComment {
+            value: boxed(value),
+            comment: boxed(comment),
+            span,
+        },
+        Condition::Variable(variable) => CMakeCondition::Variable(variable_from_json(variable)),
+        Condition::Value { value } => CMakeCondition::Value(value_from_json(value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::format::parse_document;
+
+    /// The JSON of the first statement of `source`.
+    fn first_statement(source: &str) -> serde_json::Value {
+        let json: serde_json::Value =
+            serde_json::from_str(&to_json(&parse_document(source).unwrap())).unwrap();
+        assert_eq!(json["version"], VERSION);
+        json["statements"][0].clone()
+    }
+
+    /// A span on the first line.
+    fn span(start: usize, end: usize) -> serde_json::Value {
+        json!({
+            "start": {"offset": start, "line": 1, "column": start + 1},
+            "end": {"offset": end, "line": 1, "column": end + 1},
+        })
+    }
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(
+            first_statement("set(B \"$<C:d>\" # e\n)"),
+            json!({
+                "kind": "command",
+                "name": "set",
+                "arguments": [
+                    {"kind": "keyword", "text": "B", "span": span(4, 5)},
+                    {
+                        "kind": "quoted",
+                        "parts": [{
+                            "kind": "generator_expression",
+                            "name": [{"kind": "text", "text": "C"}],
+                            "parameters": [[{"kind": "text", "text": "d"}]],
+                            "terminated": true,
+                            "span": span(7, 13),
+                        }],
+                        "span": span(6, 14),
+                    },
+                    {"kind": "comment", "text": " e", "span": span(15, 18)},
+                ],
+                "span": {
+                    "start": {"offset": 0, "line": 1, "column": 1},
+                    "end": {"offset": 20, "line": 2, "column": 2},
+                },
+            })
+        );
+        assert_eq!(
+            first_statement("if(NOT ${A})\nendif()")["condition"],
+            json!({
+                "kind": "unary_logical",
+                "operator": "NOT",
+                "value": {
+                    "kind": "value",
+                    "value": {
+                        "kind": "unquoted",
+                        "parts": [{
+                            "kind": "variable",
+                            "type": "normal",
+                            "name": [{"kind": "text", "text": "A"}],
+                            "span": span(7, 11),
+                        }],
+                        "span": span(7, 11),
+                    },
+                },
+                "span": span(3, 11),
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = r#"# comment
+#[=[ bracket ]=]
+if(DEFINED ENV{HOME} AND (a STREQUAL "b" # why
+    OR #[[c]] NOT c))
+  message(STATUS [==[text]==] "line \
+continued" $CACHE{X} @Y@ ${Z_${W}} $<$<CONFIG:Debug>:x,y> $<BROKEN)
+elseif(x)
+  while(y)
+  endwhile()
+else()
+  foreach(i a (b))
+  endforeach()
+endif()
+function(f a)
+  macro(m)
+    block(SCOPE_FOR VARIABLES)
+    endblock()
+  endmacro()
+endfunction(f)
+set(x # trailing
+  #[[ bracket ]] y)
+bad(
+ok()
+"#;
+        let document = parse_document(source).unwrap();
+        assert!(matches!(
+            document.statements.last(),
+            Some(CMakeStatement::Command(_))
+        ));
+        let json = to_json(&document);
+        let read = from_json(&json).unwrap();
+        assert_eq!(read, document);
+        // Spans don't take part in comparisons
+        assert_eq!(to_json(&read), json);
+    }
+
+    #[test]
+    fn test_without_spans() {
+        let json = r#"{
+            "version": 1,
+            "statements": [{
+                "kind": "command",
+                "name": "add_library",
+                "arguments": [
+                    {"kind": "unquoted", "parts": [{"kind": "text", "text": "foo"}]},
+                    {"kind": "keyword", "text": "STATIC"},
+                    {"kind": "quoted", "parts": [{"kind": "text", "text": "a b.cpp"}]}
+                ]
+            }]
+        }"#;
+        let mut output = String::new();
+        from_json(json)
+            .unwrap()
+            .print()
+            .render_fmt(80, &mut output)
+            .unwrap();
+        assert_eq!(output, "add_library(foo STATIC \"a b.cpp\")");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = from_json(r#"{"version": 2, "statements": []}"#).unwrap_err();
+        assert_eq!(error.to_string(), "unsupported version 2, expected 1");
+        assert!(from_json(r#"{"version": 1, "statements": [{"kind": "unknown"}]}"#).is_err());
+    }
+}
@@ -0,0 +1,407 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! A lossless tokenizer following the lexical rules of cmake-language(7).
+//!
+//! Every byte of the input belongs to exactly one token, so concatenating the
+//! text of all tokens gives back the input.
+
+use crate::parser::types::{Position, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A run of spaces and tabs.
+    Whitespace,
+    /// `\n` or `\r\n`.
+    Newline,
+    /// `# ...` up to the end of the line, without the line ending.
+    Comment,
+    /// `#[[ ... ]]`, with any number of `=` between the brackets.
+    BracketComment,
+    LeftParen,
+    RightParen,
+    /// An unquoted argument that is also a valid command name.
+    Identifier,
+    /// `"..."`, including escape sequences and line continuations.
+    QuotedArgument,
+    /// `[[ ... ]]`, with any number of `=` between the brackets.
+    BracketArgument,
+    UnquotedArgument,
+    /// An unterminated quoted argument or bracket.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Token {
+    /// The text of this token in `input`, which must be the tokenized input.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        &input[self.span.range()]
+    }
+}
+
+/// Reads tokens one at a time, without looking ahead.
+pub struct Lexer<'a> {
+    input: &'a str,
+    /// The offset of `input` in its file.
+    base: usize,
+    position: Position,
+}
+
+impl<'a> Lexer<'a> {
+    /// A lexer over `input`, which starts at `start` in its file.
+    pub fn new(input: &'a str, start: Position) -> Self {
+        Lexer {
+            input,
+            base: start.offset,
+            position: start,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.position.offset - self.base..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position.offset += c.len_utf8();
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    fn bump_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&predicate) {
+            self.bump();
+        }
+    }
+
+    fn bump_to(&mut self, offset: usize) {
+        while self.position.offset < offset {
+            self.bump();
+        }
+    }
+
+    /// The number of `=` in a bracket opening such as `[==[` at the current
+    /// position.
+    fn bracket_open_level(&self) -> Option<usize> {
+        let rest = self.rest().strip_prefix('[')?;
+        let level = rest.len() - rest.trim_start_matches('=').len();
+        rest[level..].starts_with('[').then_some(level)
+    }
+
+    /// Consumes a bracket argument, returning whether it was terminated.
+    fn bracket(&mut self, level: usize) -> bool {
+        for _ in 0..level + 2 {
+            self.bump();
+        }
+        let close = format!("]{}]", "=".repeat(level));
+        match self.rest().find(&close) {
+            Some(end) => {
+                self.bump_to(self.position.offset + end + close.len());
+                true
+            }
+            None => {
+                while self.bump().is_some() {}
+                false
+            }
+        }
+    }
+
+    /// Consumes the rest of a quoted argument after its opening quote,
+    /// returning whether it was terminated.
+    fn quoted(&mut self) -> bool {
+        while let Some(c) = self.bump() {
+            match c {
+                '"' => return true,
+                '\\' => {
+                    self.bump();
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn unquoted(&mut self) -> TokenKind {
+        let start = self.position.offset;
+        while let Some(c) = self.peek() {
+            match c {
+                '\\' => {
+                    self.bump();
+                    self.bump();
+                }
+                // Legacy unquoted arguments, such as `-DFOO="a b"`
+                '"' if self.position.offset > start => {
+                    self.bump();
+                    if !self.quoted() {
+                        return TokenKind::Error;
+                    }
+                }
+                c if c.is_whitespace() || matches!(c, '(' | ')' | '#' | '"') => break,
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        let text = &self.input[start - self.base..self.position.offset - self.base];
+        if is_identifier(text) {
+            TokenKind::Identifier
+        } else {
+            TokenKind::UnquotedArgument
+        }
+    }
+
+    fn next_kind(&mut self) -> Option<TokenKind> {
+        let kind = match self.peek()? {
+            ' ' | '\t' => {
+                self.bump_while(|c| c == ' ' || c == '\t');
+                TokenKind::Whitespace
+            }
+            '\n' => {
+                self.bump();
+                TokenKind::Newline
+            }
+            '\r' if self.rest().starts_with("\r\n") => {
+                self.bump();
+                self.bump();
+                TokenKind::Newline
+            }
+            c if c.is_whitespace() => {
+                self.bump();
+                TokenKind::Whitespace
+            }
+            '#' => {
+                self.bump();
+                match self.bracket_open_level() {
+                    Some(level) if self.bracket(level) => TokenKind::BracketComment,
+                    Some(_) => TokenKind::Error,
+                    None => {
+                        let line = self.rest().split('\n').next().unwrap_or("");
+                        let end = self.position.offset + line.trim_end_matches('\r').len();
+                        self.bump_to(end);
+                        TokenKind::Comment
+                    }
+                }
+            }
+            '(' => {
+                self.bump();
+                TokenKind::LeftParen
+            }
+            ')' => {
+                self.bump();
+                TokenKind::RightParen
+            }
+            '"' => {
+                self.bump();
+                if self.quoted() {
+                    TokenKind::QuotedArgument
+                } else {
+                    TokenKind::Error
+                }
+            }
+            '[' => match self.bracket_open_level() {
+                Some(level) if self.bracket(level) => TokenKind::BracketArgument,
+                Some(_) => TokenKind::Error,
+                None => self.unquoted(),
+            },
+            _ => self.unquoted(),
+        };
+        Some(kind)
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        let start = self.position;
+        let kind = self.next_kind()?;
+        Some(Token {
+            kind,
+            span: Span::new(start, self.position),
+        })
+    }
+}
+
+/// Splits `input` into tokens.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let start = Position {
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+    Lexer::new(input, start).collect()
+}
+
+/// The tokens of `input`, one per line with its kind, text and span, as in
+/// `Identifier "set" 1:1..1:4`.
+pub fn to_text(input: &str) -> String {
+    tokenize(input)
+        .iter()
+        .map(|token| {
+            let Span { start, end } = token.span;
+            format!(
+                "{:?} {:?} {}:{}..{}:{}\n",
+                token.kind,
+                token.text(input),
+                start.line,
+                start.column,
+                end.line,
+                end.column
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(input)
+            .iter()
+            .map(|token| (token.kind, token.text(input)))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_command() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("set(FOO \"a b\" ${BAR}) # done\r\n"),
+            vec![
+                (Identifier, "set"),
+                (LeftParen, "("),
+                (Identifier, "FOO"),
+                (Whitespace, " "),
+                (QuotedArgument, "\"a b\""),
+                (Whitespace, " "),
+                (UnquotedArgument, "${BAR}"),
+                (RightParen, ")"),
+                (Whitespace, " "),
+                (Comment, "# done"),
+                (Newline, "\r\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(
+            to_text("set(a)\n"),
+            [
+                "Identifier \"set\" 1:1..1:4",
+                "LeftParen \"(\" 1:4..1:5",
+                "Identifier \"a\" 1:5..1:6",
+                "RightParen \")\" 1:6..1:7",
+                "Newline \"\\n\" 1:7..2:1",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_tokenize_brackets() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("#[==[ a ]] ]==] [[b]] [c"),
+            vec![
+                (BracketComment, "#[==[ a ]] ]==]"),
+                (Whitespace, " "),
+                (BracketArgument, "[[b]]"),
+                (Whitespace, " "),
+                (UnquotedArgument, "[c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_escapes_and_legacy_quotes() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(
+                r#"a\ b\(c -DFOO="x y" "q\"\
+r""#
+            ),
+            vec![
+                (UnquotedArgument, r"a\ b\(c"),
+                (Whitespace, " "),
+                (UnquotedArgument, r#"-DFOO="x y""#),
+                (Whitespace, " "),
+                (QuotedArgument, "\"q\\\"\\\nr\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_unterminated() {
+        use TokenKind::*;
+        assert_eq!(kinds("\"abc"), vec![(Error, "\"abc")]);
+        assert_eq!(kinds("#[[abc"), vec![(Error, "#[[abc")]);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let input = "a\n  bé c";
+        let tokens = tokenize(input);
+        let c = tokens.last().unwrap();
+        assert_eq!(c.text(input), "c");
+        assert_eq!((c.span.start.line, c.span.start.column), (2, 6));
+        assert_eq!(c.span.start.offset, 8);
+    }
+
+    #[test]
+    fn test_lexer_from_a_position() {
+        let input = "if(A\n  B)";
+        let start = Position {
+            offset: 3,
+            line: 1,
+            column: 4,
+        };
+        let tokens: Vec<Token> = Lexer::new(&input[3..], start).collect();
+        let b = tokens[3];
+        assert_eq!(b.text(input), "B");
+        assert_eq!((b.span.start.line, b.span.start.column), (2, 3));
+    }
+}
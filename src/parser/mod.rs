@@ -21,40 +21,52 @@
 // THE SOFTWARE.
 
 use nom::bytes::complete::{is_not, tag_no_case, take_till, take_until};
-use nom::character::complete::line_ending;
-use nom::combinator::{map, opt};
-use nom::error::{context, ParseError};
-use nom::multi::many0;
-use nom::sequence::preceded;
+use nom::character::complete::{line_ending, satisfy};
+use nom::combinator::{map, opt, peek, recognize};
+use nom::error::{context, FromExternalError, ParseError};
+use nom::multi::{fold_many0, many0};
+use nom::sequence::{preceded, terminated};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_till1},
     character::complete::{char, multispace0, multispace1, space0},
     error::ErrorKind,
-    multi::{many1, separated_list0},
+    multi::separated_list0,
     sequence::{delimited, tuple},
-    InputTakeAtPosition, Parser,
+    InputTake, InputTakeAtPosition, Parser,
 };
-use nom_supreme::context::ContextError;
+use nom_locate::LocatedSpan;
 use nom_supreme::multi::parse_separated_terminated;
 use nom_supreme::ParserExt;
 
+use crate::parser::diagnostics::Diagnostic;
 use crate::parser::parse_condition::cmake_condition;
 use crate::parser::types::{
     CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand, CMakeCommandGroup, CMakeDocument,
-    CMakeForEachStatement, CMakeFunctionStatement, CMakeIfBase, CMakeIfStatement,
-    CMakeMacroStatement, CMakeStatement, CMakeValue,
+    CMakeErrorStatement, CMakeForEachStatement, CMakeFunctionStatement, CMakeIfBase,
+    CMakeIfStatement, CMakeMacroStatement, CMakeStatement, CMakeValue, CMakeWhileStatement,
+    Position, Span,
 };
 
+pub mod builder;
+pub mod cst;
+pub mod diagnostics;
+pub mod json;
+pub mod lexer;
+pub mod owned;
+pub mod tree;
 pub mod types;
+pub mod visit;
 
-const RESERVED_WORDS: [&str; 12] = [
+const RESERVED_WORDS: [&str; 14] = [
     "if",
     "elseif",
     "else",
     "endif",
     "foreach",
     "endforeach",
+    "while",
+    "endwhile",
     "function",
     "endfunction",
     "macro",
@@ -63,25 +75,52 @@ const RESERVED_WORDS: [&str; 12] = [
     "endblock",
 ];
 
+/// Parser input, which keeps track of its position in the source text.
+pub type Input<'a> = LocatedSpan<&'a str>;
 pub type ErrorType<I> = nom_supreme::error::ErrorTree<I>;
 pub type IResult<I, O> = Result<(I, O), nom::Err<ErrorType<I>>>;
 
-fn cmake_comment(input: &str) -> IResult<&str, &str> {
+fn position(input: &Input) -> Position {
+    Position {
+        offset: input.location_offset(),
+        line: input.location_line() as usize,
+        column: input.get_utf8_column(),
+    }
+}
+
+/// Runs `parser` and returns its output along with the span it consumed.
+fn spanned<'a, O>(
+    mut parser: impl Parser<Input<'a>, O, ErrorType<Input<'a>>>,
+) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, (O, Span)> {
+    move |input| {
+        let start = position(&input);
+        let (input, output) = parser.parse(input)?;
+        Ok((input, (output, Span::new(start, position(&input)))))
+    }
+}
+
+fn cmake_comment(input: Input<'_>) -> IResult<Input<'_>, &str> {
     let comment_start = char('#');
     let comment_contents = take_till(|item| item == '\n');
-    map(tuple((comment_start, comment_contents)), |(_, comment)| {
-        comment
-    })(input)
+    map(
+        tuple((comment_start, comment_contents)),
+        |(_, comment): (_, Input)| *comment.fragment(),
+    )(input)
 }
 
-fn cmake_bracket_comment(is_comment: bool) -> impl Fn(&str) -> IResult<&str, CMakeBracketLiteral> {
+fn cmake_bracket_comment(
+    is_comment: bool,
+) -> impl Fn(Input) -> IResult<Input, CMakeBracketLiteral<'_>> {
     move |input| {
         let (input, delimiter) = delimited(
             if is_comment { tag("#[") } else { tag("[") },
             opt(is_not("[\n")),
             tag("["),
         )(input)?;
-        let end_tag = format!("]{}]", delimiter.unwrap_or(""));
+        let end_tag = format!(
+            "]{}]",
+            delimiter.map_or("", |delimiter| *delimiter.fragment())
+        );
         let end_parser = tag(&*end_tag);
         let (input, comment) = take_until(&*end_tag)(input)?;
         let (input, _) = end_parser(input)?;
@@ -89,74 +128,109 @@ fn cmake_bracket_comment(is_comment: bool) -> impl Fn(&str) -> IResult<&str, CMa
             input,
             CMakeBracketLiteral {
                 is_comment,
-                delimiter: delimiter.unwrap_or("").to_string(),
-                contents: comment.to_string(),
+                delimiter: delimiter
+                    .map_or("", |delimiter| *delimiter.fragment())
+                    .into(),
+                contents: (*comment.fragment()).into(),
             },
         ))
     }
 }
 
-fn cmake_command_name(input: &str) -> IResult<&str, &str> {
+fn cmake_command_name(input: Input) -> IResult<Input, Input> {
     input.split_at_position1_complete(
         |item| !item.is_alphanumeric() && item != '_',
         ErrorKind::AlphaNumeric,
     )
 }
 
-fn cmake_quoted_string_literal(input: &str) -> IResult<&str, CMakeValue> {
-    map(strings::parse_string, CMakeValue::QuotedString)(input)
+fn cmake_quoted_string_literal(input: Input<'_>) -> IResult<Input<'_>, CMakeValue<'_>> {
+    map(
+        terminated(spanned(strings::parse_string), many0(char(','))),
+        |(string, span)| CMakeValue::QuotedString(string, span),
+    )(input)
 }
 
-fn cmake_string_part(input: &str) -> IResult<&str, String> {
-    let is_invalid_char = |item: char| {
-        item.is_whitespace() || item == ')' || item == '(' || item == '{' || item == '}'
+/// `\` and the escaped character. A backslash at the end of a line is kept
+/// on its own.
+fn cmake_unquoted_escape(input: Input) -> IResult<Input, Input> {
+    recognize(preceded(
+        char('\\'),
+        opt(satisfy(|c| c != '\n' && c != '\r')),
+    ))(input)
+}
+
+/// The quoted section of a legacy unquoted argument, such as `"a b"` in
+/// `-DFOO="a b"`. It can't span lines.
+fn cmake_legacy_quoted_part(input: Input) -> IResult<Input, Input> {
+    recognize(delimited(
+        char('"'),
+        many0(alt((
+            cmake_unquoted_escape,
+            take_till1(|c| c == '"' || c == '\\' || c == '\n'),
+        ))),
+        char('"'),
+    ))(input)
+}
+
+/// An unquoted argument, as in cmake-language(7): any characters except
+/// whitespace, `(`, `)`, `#`, `"` and `\`, plus escape sequences such as
+/// `\ ` or `\(`. Balanced parentheses, as in `$(VAR)`, and quoted sections
+/// after the first character are kept in the argument too.
+fn cmake_string_part(input: Input) -> IResult<Input, Input> {
+    let is_invalid_char =
+        |item: char| item.is_whitespace() || matches!(item, '(' | ')' | '#' | '"' | '\\');
+    let element = || {
+        alt((
+            take_till1(is_invalid_char),
+            cmake_unquoted_escape,
+            // $(variable)
+            recognize(delimited(char('('), cmake_string_part, char(')'))),
+        ))
     };
     context(
         "string_part",
-        map(
-            many1(alt((
-                map(take_till1(is_invalid_char), |item: &str| item.to_string()),
-                // $(variable)
-                map(delimited(char('('), cmake_string_part, char(')')), |s| {
-                    format!("({})", s)
-                }),
-                // ${variable}
-                map(delimited(char('{'), cmake_string_part, char('}')), |s| {
-                    format!("{{{}}}", s)
-                }),
-            ))),
-            |result| result.join(""),
-        ),
+        recognize(tuple((
+            element(),
+            many0(alt((element(), cmake_legacy_quoted_part, tag("\"")))),
+        ))),
     )(input)
 }
 
 #[inline]
-fn cmake_string_literal(input: &str) -> IResult<&str, CMakeValue> {
-    let (input, result) = cmake_string_part(input)?;
+fn cmake_string_literal(input: Input<'_>) -> IResult<Input<'_>, CMakeValue<'_>> {
+    let (input, (result, span)) = spanned(cmake_string_part)(input)?;
     if result
         .chars()
         .all(|c| c.is_uppercase() || c == '_' || c.is_numeric())
     {
-        return Ok((input, CMakeValue::ArgumentSpecifier(result.to_string())));
+        let result = (*result.fragment()).into();
+        return Ok((input, CMakeValue::ArgumentSpecifier(result, span)));
     }
-    Ok((input, CMakeValue::StringLiteral(result)))
+    Ok((
+        input,
+        CMakeValue::StringLiteral(strings::parse_argument(result), span),
+    ))
 }
 
-fn cmake_value(input: &str) -> IResult<&str, CMakeValue> {
+fn cmake_value(input: Input<'_>) -> IResult<Input<'_>, CMakeValue<'_>> {
     context(
         "Value",
         alt((
             context(
                 "bracket_comment",
-                cmake_bracket_comment(true).map(|item| CMakeValue::BracketComment(item)),
+                spanned(cmake_bracket_comment(true))
+                    .map(|(comment, span)| CMakeValue::BracketComment(comment, span)),
             ),
             context(
                 "comment",
-                cmake_comment.map(|item| CMakeValue::Comment(item.to_string())),
+                spanned(cmake_comment)
+                    .map(|(comment, span)| CMakeValue::Comment(comment.into(), span)),
             ),
             context(
                 "bracket_string",
-                cmake_bracket_comment(false).map(|item| CMakeValue::BracketQuotedString(item)),
+                spanned(cmake_bracket_comment(false))
+                    .map(|(string, span)| CMakeValue::BracketQuotedString(string, span)),
             ),
             context("quoted_string_literal", cmake_quoted_string_literal),
             context("string_literal", cmake_string_literal),
@@ -164,13 +238,31 @@ fn cmake_value(input: &str) -> IResult<&str, CMakeValue> {
     )(input)
 }
 
-fn cmake_command(input: &str) -> IResult<&str, CMakeCommand> {
+/// A block keyword used where a command was expected, such as an `endif()`
+/// without an `if()`.
+#[derive(Debug)]
+struct ReservedWord(String);
+
+impl std::fmt::Display for ReservedWord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unexpected `{}()`, which isn't part of a block", self.0)
+    }
+}
+
+impl std::error::Error for ReservedWord {}
+
+fn cmake_command(input: Input<'_>) -> IResult<Input<'_>, CMakeCommand<'_>> {
+    let start = position(&input);
     let (input, name) = cmake_command_name(input)?;
-    if RESERVED_WORDS.contains(&&*name.to_lowercase()) {
-        return Err(nom::Err::Error(ErrorType::add_context(
-            input,
-            "reserved word can't be used as command",
-            ErrorType::from_error_kind(input, ErrorKind::AlphaNumeric),
+    if RESERVED_WORDS
+        .iter()
+        .any(|word| name.fragment().eq_ignore_ascii_case(word))
+    {
+        let error = ReservedWord(name.fragment().to_string());
+        return Err(nom::Err::Error(ErrorType::from_external_error(
+            name,
+            ErrorKind::Verify,
+            error,
         )));
     }
     let (input, _) = space0(input)?;
@@ -178,13 +270,14 @@ fn cmake_command(input: &str) -> IResult<&str, CMakeCommand> {
     Ok((
         input,
         CMakeCommand {
-            name: name.to_string(),
+            name: (*name.fragment()).into(),
             args,
+            span: Span::new(start, position(&input)),
         },
     ))
 }
 
-fn cmake_args(input: &str) -> IResult<&str, Vec<CMakeValue>> {
+fn cmake_args(input: Input<'_>) -> IResult<Input<'_>, Vec<CMakeValue<'_>>> {
     delimited(
         char('('),
         delimited(multispace0, cmake_arg_list_inner, multispace0),
@@ -192,11 +285,11 @@ fn cmake_args(input: &str) -> IResult<&str, Vec<CMakeValue>> {
     )(input)
 }
 
-fn cmake_arg_parenthesis(input: &str) -> IResult<&str, Vec<CMakeValue>> {
+fn cmake_arg_parenthesis(input: Input<'_>) -> IResult<Input<'_>, Vec<CMakeValue<'_>>> {
     let (input, (start, inner, end)) = tuple((
-        char('(').map(|_| CMakeValue::Parenthesis("(".to_string())),
+        spanned(char('(')).map(|(_, span)| CMakeValue::Parenthesis("(".into(), span)),
         separated_list0(multispace1, cmake_arg_list_inner),
-        char(')').map(|_| CMakeValue::Parenthesis(")".to_string())),
+        spanned(char(')')).map(|(_, span)| CMakeValue::Parenthesis(")".into(), span)),
     ))(input)?;
 
     let mut result = vec![start];
@@ -205,27 +298,53 @@ fn cmake_arg_parenthesis(input: &str) -> IResult<&str, Vec<CMakeValue>> {
     Ok((input, result))
 }
 
-fn cmake_arg_list_inner(input: &str) -> IResult<&str, Vec<CMakeValue>> {
-    let value_parser = map(cmake_value, |item| vec![item]);
-    map(
-        alt((separated_list0(
-            multispace1,
-            alt((cmake_arg_parenthesis, value_parser)),
-        ),)),
-        |output| output.into_iter().flatten().collect(),
+/// An argument, or the arguments in a pair of parentheses.
+enum ArgItem<'a> {
+    Value(CMakeValue<'a>),
+    Parenthesis(Vec<CMakeValue<'a>>),
+}
+
+fn push_arg_item<'a>(mut args: Vec<CMakeValue<'a>>, item: ArgItem<'a>) -> Vec<CMakeValue<'a>> {
+    match item {
+        ArgItem::Value(value) => args.push(value),
+        ArgItem::Parenthesis(values) => args.extend(values),
+    }
+    args
+}
+
+fn cmake_arg_list_inner(input: Input<'_>) -> IResult<Input<'_>, Vec<CMakeValue<'_>>> {
+    let item = || {
+        alt((
+            map(cmake_arg_parenthesis, ArgItem::Parenthesis),
+            map(cmake_value, ArgItem::Value),
+        ))
+    };
+    // A comment ends the argument before it, even without whitespace.
+    let separator = alt((multispace1, recognize(peek(char('#')))));
+    let (input, first) = opt(item())(input)?;
+    let Some(first) = first else {
+        return Ok((input, Vec::new()));
+    };
+    let mut args = Some(push_arg_item(Vec::new(), first));
+    fold_many0(
+        preceded(separator, item()),
+        move || args.take().unwrap_or_default(),
+        push_arg_item,
     )(input)
 }
 
-fn cmake_else_if_block(input: &str) -> IResult<&str, CMakeIfBase> {
+fn cmake_else_if_block<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeIfBase<'_>> {
     let base = tuple((
         tag_no_case("elseif"),
         multispace0,
-        tag("("),
+        char('('),
         multispace0,
         cmake_condition,
         multispace0,
-        tag(")"),
-        parse_statement_list(),
+        char(')'),
+        parse_statement_list::<RECOVER>(),
     ));
     let mut inner = map(base, |(_, _, _, _, condition, _, _, body)| CMakeIfBase {
         condition,
@@ -235,26 +354,29 @@ fn cmake_else_if_block(input: &str) -> IResult<&str, CMakeIfBase> {
     inner(input)
 }
 
-fn cmake_if_group(input: &str) -> IResult<&str, CMakeStatement> {
-    let if_start = tuple((tag_no_case("if"), multispace0, tag("("), multispace0));
+fn cmake_if_group<const RECOVER: bool>(input: Input<'_>) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let if_start = tuple((tag_no_case("if"), multispace0, char('('), multispace0));
     let condition = cmake_condition;
-    let if_end = tuple((multispace0, tag(")")));
+    let if_end = tuple((multispace0, char(')')));
     let parse_condition = context(
         "parse_if_condition",
         map(tuple((if_start, condition, if_end)), |(_, condition, _)| {
             condition
         }),
     );
-    let parse_if_statements = context("parse_if_statements", parse_statement_list());
+    let parse_if_statements = context("parse_if_statements", parse_statement_list::<RECOVER>());
     let parse_else_if_blocks = context(
         "parse_else_if_blocks",
-        many0(delimited(space0, cmake_else_if_block, space0)),
+        many0(delimited(space0, cmake_else_if_block::<RECOVER>, space0)),
     );
     let parse_else_block = context(
         "parse_else_block",
         opt(delimited(
             space0,
-            tuple((skip_empty_command("else"), parse_statement_list())),
+            tuple((
+                skip_empty_command("else"),
+                parse_statement_list::<RECOVER>(),
+            )),
             space0,
         )),
     );
@@ -269,12 +391,13 @@ fn cmake_if_group(input: &str) -> IResult<&str, CMakeStatement> {
     ));
 
     let mut parse_if_statement = map(
-        parse_if_statement_tuple,
-        |(condition, body, else_ifs, else_body, _)| {
+        spanned(parse_if_statement_tuple),
+        |((condition, body, else_ifs, else_body, _), span)| {
             CMakeStatement::If(CMakeIfStatement {
                 base: CMakeIfBase { condition, body },
                 else_ifs,
                 else_body: else_body.map(|(_, body)| body),
+                span,
             })
         },
     );
@@ -282,55 +405,107 @@ fn cmake_if_group(input: &str) -> IResult<&str, CMakeStatement> {
     parse_if_statement(input)
 }
 
-fn parse_statement_list() -> impl FnMut(&str) -> IResult<&str, Vec<CMakeStatement>> {
-    |input| many0(delimited(space0, cmake_statement, space0))(input)
+fn parse_statement_list<const RECOVER: bool>(
+) -> impl FnMut(Input) -> IResult<Input, Vec<CMakeStatement<'_>>> {
+    |input| {
+        if RECOVER {
+            many0(delimited(space0, cmake_body_statement_recovering, space0))(input)
+        } else {
+            many0(delimited(space0, cmake_statement::<false>, space0))(input)
+        }
+    }
 }
 
-fn cmake_clause_body_block<'a>(
+fn cmake_clause_body_block<'a, const RECOVER: bool>(
     keyword: &'a str,
-) -> impl FnMut(&str) -> IResult<&str, CMakeCommandGroup> + 'a {
-    let keyword_end = format!("end{}", keyword);
+    end_keyword: &'a str,
+) -> impl FnMut(Input) -> IResult<Input, CMakeCommandGroup<'_>> + 'a {
     move |input| {
         let prefix = tuple((tag_no_case(keyword), space0));
-        let body = parse_statement_list();
-        let base = tuple((prefix, cmake_args, body, skip_empty_command(&keyword_end)));
-
-        let mut parser = map(base, |(_, clause, body, end_clause)| CMakeCommandGroup {
-            clause,
-            body,
-            end_clause,
+        let body = parse_statement_list::<RECOVER>();
+        let base = tuple((prefix, cmake_args, body, skip_empty_command(end_keyword)));
+
+        let mut parser = map(spanned(base), |((_, clause, body, end_clause), span)| {
+            CMakeCommandGroup {
+                clause,
+                body,
+                end_clause,
+                span,
+            }
         });
         parser(input)
     }
 }
 
-fn cmake_foreach_group(input: &str) -> IResult<&str, CMakeStatement> {
-    let block = cmake_clause_body_block("foreach");
+fn cmake_foreach_group<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let block = cmake_clause_body_block::<RECOVER>("foreach", "endforeach");
     map(block, |group| {
         CMakeStatement::For(CMakeForEachStatement { group })
     })(input)
 }
 
-fn cmake_function_group(input: &str) -> IResult<&str, CMakeStatement> {
-    let function_block = cmake_clause_body_block("function");
+fn cmake_while_group<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let while_start = tuple((tag_no_case("while"), space0, char('('), multispace0));
+    let while_end = tuple((multispace0, char(')')));
+    let parse_condition = context(
+        "parse_while_condition",
+        map(
+            tuple((while_start, cmake_condition, while_end)),
+            |(_, condition, _)| condition,
+        ),
+    );
+    let parse_endwhile = context("parse_endwhile", skip_empty_command("endwhile"));
+
+    map(
+        spanned(tuple((
+            parse_condition,
+            parse_statement_list::<RECOVER>(),
+            parse_endwhile,
+        ))),
+        |((condition, body, _), span)| {
+            CMakeStatement::While(CMakeWhileStatement {
+                condition,
+                body,
+                span,
+            })
+        },
+    )(input)
+}
+
+fn cmake_function_group<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let function_block = cmake_clause_body_block::<RECOVER>("function", "endfunction");
     map(function_block, |group| {
         CMakeStatement::Function(CMakeFunctionStatement { group })
     })(input)
 }
 
-fn cmake_macro_group(input: &str) -> IResult<&str, CMakeStatement> {
-    map(cmake_clause_body_block("macro"), |group| {
-        CMakeStatement::Macro(CMakeMacroStatement { group })
-    })(input)
+fn cmake_macro_group<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    map(
+        cmake_clause_body_block::<RECOVER>("macro", "endmacro"),
+        |group| CMakeStatement::Macro(CMakeMacroStatement { group }),
+    )(input)
 }
 
-fn cmake_block_group(input: &str) -> IResult<&str, CMakeStatement> {
-    map(cmake_clause_body_block("block"), |group| {
-        CMakeStatement::Block(CMakeBlockStatement { group })
-    })(input)
+fn cmake_block_group<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    map(
+        cmake_clause_body_block::<RECOVER>("block", "endblock"),
+        |group| CMakeStatement::Block(CMakeBlockStatement { group }),
+    )(input)
 }
 
-fn skip_empty_command<'a>(name: &'a str) -> impl Fn(&str) -> IResult<&str, Vec<CMakeValue>> + 'a {
+fn skip_empty_command<'a>(
+    name: &'a str,
+) -> impl Fn(Input) -> IResult<Input, Vec<CMakeValue<'_>>> + 'a {
     move |input| {
         let command = tag_no_case(name);
         let parser = tuple((command, space0, cmake_args));
@@ -338,35 +513,142 @@ fn skip_empty_command<'a>(name: &'a str) -> impl Fn(&str) -> IResult<&str, Vec<C
     }
 }
 
-fn cmake_statement(input: &str) -> IResult<&str, CMakeStatement> {
+fn cmake_statement<const RECOVER: bool>(
+    input: Input<'_>,
+) -> IResult<Input<'_>, CMakeStatement<'_>> {
     alt((
         context("command", cmake_command.map(CMakeStatement::Command)),
         context(
             "bracket_comment",
-            cmake_bracket_comment(true).map(|item| CMakeStatement::BracketComment(item)),
+            spanned(cmake_bracket_comment(true))
+                .map(|(comment, span)| CMakeStatement::BracketComment(comment, span)),
         ),
         context(
             "comment",
-            cmake_comment.map(|item| CMakeStatement::Comment(item.to_string())),
+            spanned(cmake_comment)
+                .map(|(comment, span)| CMakeStatement::Comment(comment.into(), span)),
         ),
         context(
             "newline",
-            tuple((line_ending, space0)).map(|_| CMakeStatement::Newline),
+            tuple((spanned(line_ending), space0))
+                .map(|((_, span), _)| CMakeStatement::Newline(span)),
         ),
-        context("if", cmake_if_group),
-        context("foreach", cmake_foreach_group),
-        context("function", cmake_function_group),
-        context("macro", cmake_macro_group),
-        context("block", cmake_block_group),
+        context("if", cmake_if_group::<RECOVER>),
+        context("foreach", cmake_foreach_group::<RECOVER>),
+        context("while", cmake_while_group::<RECOVER>),
+        context("function", cmake_function_group::<RECOVER>),
+        context("macro", cmake_macro_group::<RECOVER>),
+        context("block", cmake_block_group::<RECOVER>),
     ))(input)
 }
 
-pub fn cmake_parser(input: &str) -> IResult<&str, CMakeDocument> {
+pub fn cmake_parser(input: Input<'_>) -> IResult<Input<'_>, CMakeDocument<'_>> {
+    let mut parser = parse_separated_terminated(
+        preceded(space0, cmake_statement::<false>),
+        space0,
+        multispace0.all_consuming(),
+        Vec::new,
+        |mut memo, current| {
+            memo.push(current);
+            memo
+        },
+    );
+    let (input, statements) = parser.parse(input)?;
+    Ok((input, CMakeDocument { statements }))
+}
+
+const CLOSING_WORDS: [&str; 8] = [
+    "elseif",
+    "else",
+    "endif",
+    "endforeach",
+    "endwhile",
+    "endfunction",
+    "endmacro",
+    "endblock",
+];
+
+/// Returns the name of the command `line` starts with, if any.
+fn line_command_name(line: &str) -> Option<&str> {
+    let line = line.trim_start_matches([' ', '\t']);
+    let length = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(line.len());
+    let name = &line[..length];
+    let starts_with_letter = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+    let is_call = line[length..]
+        .trim_start_matches([' ', '\t'])
+        .starts_with('(');
+    (starts_with_letter && is_call).then_some(name)
+}
+
+/// Skips the rest of the line and every following line up to the next one
+/// that starts a command. Trailing whitespace is left for the next statement.
+fn cmake_error_region(input: Input) -> IResult<Input, Input> {
+    let text = *input.fragment();
+    let mut end = text.find('\n').unwrap_or(text.len());
+    while end < text.len() {
+        let line_start = end + 1;
+        let line_end = text[line_start..]
+            .find('\n')
+            .map_or(text.len(), |length| line_start + length);
+        if line_command_name(&text[line_start..line_end]).is_some() {
+            break;
+        }
+        end = line_end;
+    }
+    let end = text[..end].trim_end().len();
+    if end == 0 {
+        return Err(nom::Err::Error(ErrorType::from_error_kind(
+            input,
+            ErrorKind::Eof,
+        )));
+    }
+    Ok(input.take_split(end))
+}
+
+/// Parses a statement, turning an unparsable region into an error statement.
+fn cmake_statement_recovering(input: Input<'_>) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let error = match cmake_statement::<true>(input) {
+        Err(nom::Err::Error(error)) => error,
+        result => return result,
+    };
+    let (input, (text, span)) = spanned(cmake_error_region)(input)?;
+    let diagnostic =
+        Diagnostic::from_parse_error(&error).with_note("the statement is kept unformatted");
+    let error = CMakeErrorStatement {
+        text: (*text.fragment()).into(),
+        diagnostic,
+        span,
+    };
+    Ok((input, CMakeStatement::Error(error)))
+}
+
+/// Like [`cmake_statement_recovering`], but leaves the commands that close
+/// a block to the block parser.
+fn cmake_body_statement_recovering(input: Input<'_>) -> IResult<Input<'_>, CMakeStatement<'_>> {
+    let closes_block = line_command_name(input.fragment()).is_some_and(|name| {
+        CLOSING_WORDS
+            .iter()
+            .any(|word| word.eq_ignore_ascii_case(name))
+    });
+    if closes_block {
+        return cmake_statement::<true>(input);
+    }
+    cmake_statement_recovering(input)
+}
+
+/// Parses a whole document, turning regions that fail to parse into
+/// [`CMakeStatement::Error`] statements instead of failing.
+///
+/// Parsing resumes at the next line that starts a command, including inside
+/// block bodies, so everything else can still be formatted.
+pub fn cmake_parser_recovering(input: Input<'_>) -> IResult<Input<'_>, CMakeDocument<'_>> {
     let mut parser = parse_separated_terminated(
-        preceded(space0, cmake_statement),
+        preceded(space0, cmake_statement_recovering),
         space0,
         multispace0.all_consuming(),
-        || vec![],
+        Vec::new,
         |mut memo, current| {
             memo.push(current);
             memo
@@ -0,0 +1,335 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+//! Converting syntax trees that borrow from the source into ones that own
+//! their text, to keep them after the source is gone.
+
+use std::borrow::Cow;
+
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand,
+    CMakeCommandGroup, CMakeCondition, CMakeDocument, CMakeErrorStatement, CMakeForEachStatement,
+    CMakeFunctionStatement, CMakeIfBase, CMakeIfStatement, CMakeMacroStatement, CMakeStatement,
+    CMakeValue, CMakeWhileStatement, GeneratorExpression, VariableReference,
+};
+
+fn owned(text: Cow<str>) -> Cow<'static, str> {
+    Cow::Owned(text.into_owned())
+}
+
+fn owned_parts(parts: Vec<ArgumentPart>) -> Vec<ArgumentPart<'static>> {
+    parts.into_iter().map(ArgumentPart::into_owned).collect()
+}
+
+fn owned_values(values: Vec<CMakeValue>) -> Vec<CMakeValue<'static>> {
+    values.into_iter().map(CMakeValue::into_owned).collect()
+}
+
+fn owned_statements(statements: Vec<CMakeStatement>) -> Vec<CMakeStatement<'static>> {
+    statements
+        .into_iter()
+        .map(CMakeStatement::into_owned)
+        .collect()
+}
+
+fn owned_condition(condition: CMakeCondition) -> Box<CMakeCondition<'static>> {
+    Box::new(condition.into_owned())
+}
+
+impl VariableReference<'_> {
+    pub fn into_owned(self) -> VariableReference<'static> {
+        VariableReference {
+            kind: self.kind,
+            name: owned_parts(self.name),
+            span: self.span,
+        }
+    }
+}
+
+impl GeneratorExpression<'_> {
+    pub fn into_owned(self) -> GeneratorExpression<'static> {
+        GeneratorExpression {
+            name: owned_parts(self.name),
+            parameters: self.parameters.into_iter().map(owned_parts).collect(),
+            terminated: self.terminated,
+            span: self.span,
+        }
+    }
+}
+
+impl ArgumentPart<'_> {
+    pub fn into_owned(self) -> ArgumentPart<'static> {
+        match self {
+            ArgumentPart::Text(text) => ArgumentPart::Text(owned(text)),
+            ArgumentPart::Escape(c) => ArgumentPart::Escape(c),
+            ArgumentPart::LineContinuation(line_ending) => {
+                ArgumentPart::LineContinuation(owned(line_ending))
+            }
+            ArgumentPart::Variable(variable) => ArgumentPart::Variable(variable.into_owned()),
+            ArgumentPart::GeneratorExpression(expression) => {
+                ArgumentPart::GeneratorExpression(expression.into_owned())
+            }
+        }
+    }
+}
+
+impl CMakeArgument<'_> {
+    pub fn into_owned(self) -> CMakeArgument<'static> {
+        CMakeArgument {
+            parts: owned_parts(self.parts),
+        }
+    }
+}
+
+impl CMakeBracketLiteral<'_> {
+    pub fn into_owned(self) -> CMakeBracketLiteral<'static> {
+        CMakeBracketLiteral {
+            is_comment: self.is_comment,
+            delimiter: owned(self.delimiter),
+            contents: owned(self.contents),
+        }
+    }
+}
+
+impl CMakeValue<'_> {
+    pub fn into_owned(self) -> CMakeValue<'static> {
+        match self {
+            CMakeValue::ArgumentSpecifier(keyword, span) => {
+                CMakeValue::ArgumentSpecifier(owned(keyword), span)
+            }
+            CMakeValue::QuotedString(argument, span) => {
+                CMakeValue::QuotedString(argument.into_owned(), span)
+            }
+            CMakeValue::BracketQuotedString(literal, span) => {
+                CMakeValue::BracketQuotedString(literal.into_owned(), span)
+            }
+            CMakeValue::StringLiteral(argument, span) => {
+                CMakeValue::StringLiteral(argument.into_owned(), span)
+            }
+            CMakeValue::Comment(comment, span) => CMakeValue::Comment(owned(comment), span),
+            CMakeValue::BracketComment(comment, span) => {
+                CMakeValue::BracketComment(comment.into_owned(), span)
+            }
+            CMakeValue::Parenthesis(parenthesis, span) => {
+                CMakeValue::Parenthesis(owned(parenthesis), span)
+            }
+        }
+    }
+}
+
+impl CMakeCondition<'_> {
+    pub fn into_owned(self) -> CMakeCondition<'static> {
+        match self {
+            CMakeCondition::Parentheses { value, span } => CMakeCondition::Parentheses {
+                value: owned_condition(*value),
+                span,
+            },
+            CMakeCondition::UnaryTest {
+                operator,
+                value,
+                span,
+            } => CMakeCondition::UnaryTest {
+                operator: owned(operator),
+                value: owned_condition(*value),
+                span,
+            },
+            CMakeCondition::BinaryTest {
+                operator,
+                left,
+                right,
+                span,
+            } => CMakeCondition::BinaryTest {
+                operator: owned(operator),
+                left: owned_condition(*left),
+                right: owned_condition(*right),
+                span,
+            },
+            CMakeCondition::UnaryLogicalOperator {
+                operator,
+                value,
+                span,
+            } => CMakeCondition::UnaryLogicalOperator {
+                operator: owned(operator),
+                value: owned_condition(*value),
+                span,
+            },
+            CMakeCondition::BinaryLogicalOperator {
+                operator,
+                left,
+                right,
+                span,
+            } => CMakeCondition::BinaryLogicalOperator {
+                operator: owned(operator),
+                left: owned_condition(*left),
+                right: owned_condition(*right),
+                span,
+            },
+            CMakeCondition::Comment {
+                content,
+                tail,
+                span,
+            } => CMakeCondition::Comment {
+                content: owned(content),
+                tail: tail.map(|tail| owned_condition(*tail)),
+                span,
+            },
+            CMakeCondition::BracketComment {
+                comment,
+                tail,
+                span,
+            } => CMakeCondition::BracketComment {
+                comment: comment.into_owned(),
+                tail: tail.map(|tail| owned_condition(*tail)),
+                span,
+            },
+            CMakeCondition::TrailingComment {
+                value,
+                comment,
+                span,
+            } => CMakeCondition::TrailingComment {
+                value: owned_condition(*value),
+                comment: owned_condition(*comment),
+                span,
+            },
+            CMakeCondition::Variable(variable) => CMakeCondition::Variable(variable.into_owned()),
+            CMakeCondition::Value(value) => CMakeCondition::Value(value.into_owned()),
+        }
+    }
+}
+
+impl CMakeCommand<'_> {
+    pub fn into_owned(self) -> CMakeCommand<'static> {
+        CMakeCommand {
+            name: owned(self.name),
+            args: owned_values(self.args),
+            span: self.span,
+        }
+    }
+}
+
+impl CMakeIfBase<'_> {
+    pub fn into_owned(self) -> CMakeIfBase<'static> {
+        CMakeIfBase {
+            condition: self.condition.into_owned(),
+            body: owned_statements(self.body),
+        }
+    }
+}
+
+impl CMakeIfStatement<'_> {
+    pub fn into_owned(self) -> CMakeIfStatement<'static> {
+        CMakeIfStatement {
+            base: self.base.into_owned(),
+            else_ifs: self
+                .else_ifs
+                .into_iter()
+                .map(CMakeIfBase::into_owned)
+                .collect(),
+            else_body: self.else_body.map(owned_statements),
+            span: self.span,
+        }
+    }
+}
+
+impl CMakeWhileStatement<'_> {
+    pub fn into_owned(self) -> CMakeWhileStatement<'static> {
+        CMakeWhileStatement {
+            condition: self.condition.into_owned(),
+            body: owned_statements(self.body),
+            span: self.span,
+        }
+    }
+}
+
+impl CMakeCommandGroup<'_> {
+    pub fn into_owned(self) -> CMakeCommandGroup<'static> {
+        CMakeCommandGroup {
+            clause: owned_values(self.clause),
+            body: owned_statements(self.body),
+            end_clause: owned_values(self.end_clause),
+            span: self.span,
+        }
+    }
+}
+
+impl CMakeErrorStatement<'_> {
+    pub fn into_owned(self) -> CMakeErrorStatement<'static> {
+        CMakeErrorStatement {
+            text: owned(self.text),
+            diagnostic: self.diagnostic,
+            span: self.span,
+        }
+    }
+}
+
+impl CMakeStatement<'_> {
+    pub fn into_owned(self) -> CMakeStatement<'static> {
+        match self {
+            CMakeStatement::If(statement) => CMakeStatement::If(statement.into_owned()),
+            CMakeStatement::For(statement) => CMakeStatement::For(CMakeForEachStatement {
+                group: statement.group.into_owned(),
+            }),
+            CMakeStatement::While(statement) => CMakeStatement::While(statement.into_owned()),
+            CMakeStatement::Function(statement) => {
+                CMakeStatement::Function(CMakeFunctionStatement {
+                    group: statement.group.into_owned(),
+                })
+            }
+            CMakeStatement::Block(statement) => CMakeStatement::Block(CMakeBlockStatement {
+                group: statement.group.into_owned(),
+            }),
+            CMakeStatement::Macro(statement) => CMakeStatement::Macro(CMakeMacroStatement {
+                group: statement.group.into_owned(),
+            }),
+            CMakeStatement::Command(command) => CMakeStatement::Command(command.into_owned()),
+            CMakeStatement::BracketComment(comment, span) => {
+                CMakeStatement::BracketComment(comment.into_owned(), span)
+            }
+            CMakeStatement::Comment(comment, span) => CMakeStatement::Comment(owned(comment), span),
+            CMakeStatement::Newline(span) => CMakeStatement::Newline(span),
+            CMakeStatement::Error(error) => CMakeStatement::Error(error.into_owned()),
+        }
+    }
+}
+
+impl CMakeDocument<'_> {
+    /// A copy of the document that doesn't borrow from the source.
+    pub fn into_owned(self) -> CMakeDocument<'static> {
+        CMakeDocument {
+            statements: owned_statements(self.statements),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::parse_document;
+
+    #[test]
+    fn test_into_owned() {
+        let source = "if(NOT A)\n  set(B \"${C}\" [[d]]) # e\nendif()\nforeach(x IN LISTS y)\nendforeach()\n";
+        let owned = {
+            let source = source.to_string();
+            parse_document(&source).unwrap().into_owned()
+        };
+        assert_eq!(owned, parse_document(source).unwrap());
+    }
+}
@@ -20,239 +20,500 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
+//! Conditions of `if()`, `elseif()` and `while()`.
+//!
+//! Conditions are read token by token with [`Lexer`] and parsed by
+//! precedence climbing, so each token is looked at once however long the
+//! condition is. The precedence follows the `if()` documentation, from the
+//! tightest to the loosest: parentheses, unary tests, binary tests, `NOT`,
+//! `AND` and `OR`. Operators of the same level are evaluated from left to
+//! right, so chains such as `A AND B AND C` nest to the left.
+
 use nom::branch::alt;
-use nom::bytes::complete::tag_no_case;
-use nom::character::complete::multispace0;
-use nom::combinator::{map, opt};
-use nom::error::ParseError;
-use nom::error::{context, ErrorKind};
-use nom::sequence::tuple;
-use std::fmt::Debug;
-
-use crate::parser::types::CMakeCondition;
-use crate::parser::{cmake_comment, cmake_value, ErrorType, IResult};
-
-fn cmake_condition_parentheses(input: &str) -> IResult<&str, CMakeCondition> {
-    let base = tuple((
-        nom::character::complete::char('('),
-        multispace0,
-        cmake_condition,
-        multispace0,
-        nom::character::complete::char(')'),
-    ));
-    let inner = map(base, |(_, _, condition, _, _)| {
-        CMakeCondition::Parentheses {
-            value: Box::new(condition),
-        }
-    });
+use nom::bytes::complete::tag;
+use nom::character::complete::{multispace0, multispace1};
+use nom::combinator::{eof, map, opt, peek};
+use nom::error::{context, ParseError};
+use nom::multi::many0;
+use nom::sequence::terminated;
+use nom::InputTake;
+use nom_supreme::context::ContextError;
+
+use crate::parser::lexer::{Lexer, Token, TokenKind};
+use crate::parser::types::{CMakeBracketLiteral, CMakeCondition, Position, Span};
+use crate::parser::{
+    cmake_bracket_comment, cmake_comment, cmake_value, position, spanned, strings, ErrorType,
+    IResult, Input,
+};
+
+type ConditionParser = fn(Input) -> IResult<Input, CMakeCondition<'_>>;
 
-    context("condition_parentheses", inner)(input)
+pub(super) const UNARY_TESTS: [&str; 12] = [
+    "DEFINED",
+    "EXISTS",
+    "IS_READABLE",
+    "IS_WRITABLE",
+    "IS_EXECUTABLE",
+    "IS_ABSOLUTE",
+    "IS_DIRECTORY",
+    "IS_SYMLINK",
+    "COMMAND",
+    "POLICY",
+    "TEST",
+    "TARGET",
+];
+
+pub(super) const BINARY_TESTS: [&str; 22] = [
+    "EQUAL",
+    "LESS",
+    "LESS_EQUAL",
+    "GREATER",
+    "GREATER_EQUAL",
+    "STREQUAL",
+    "STRLESS",
+    "STRLESS_EQUAL",
+    "STRGREATER",
+    "STRGREATER_EQUAL",
+    "VERSION_EQUAL",
+    "VERSION_LESS",
+    "VERSION_LESS_EQUAL",
+    "VERSION_GREATER",
+    "VERSION_GREATER_EQUAL",
+    "PATH_EQUAL",
+    "MATCHES",
+    "IS_NEWER_THAN",
+    "IN_LIST",
+    "INLIST",
+    "NOTINLIST",
+    "NOT_IN_LIST",
+];
+
+/// Whether `word` is read as an operator in a condition.
+pub(super) fn is_operator(word: &str) -> bool {
+    ["NOT", "AND", "OR"]
+        .iter()
+        .chain(&UNARY_TESTS)
+        .chain(&BINARY_TESTS)
+        .any(|operator| operator.eq_ignore_ascii_case(word))
 }
 
-fn cmake_condition_unary_test(input: &str) -> IResult<&str, CMakeCondition> {
-    let operator = alt((
-        tag_no_case("EXISTS"),
-        tag_no_case("IS_ABSOLUTE"),
-        tag_no_case("IS_DIRECTORY"),
-        tag_no_case("IS_SYMLINK"),
-        tag_no_case("COMMAND"),
-        tag_no_case("DEFINED"),
-        tag_no_case("POLICY"),
-        tag_no_case("TEST"),
-        tag_no_case("TARGET"),
-    ));
-    let base = tuple((
-        operator,
-        nom::character::complete::multispace1,
-        cmake_condition,
-    ));
-    let inner = map(base, |(operator, _, value)| CMakeCondition::UnaryTest {
-        operator: operator.to_string(),
-        value: Box::new(value),
-    });
-
-    context("condition_unary_test", inner)(input)
+/// Binding powers, on the left and right of an operator. Higher powers bind
+/// tighter, and a right power above the left one makes operators of the
+/// same level nest to the left.
+const OR_POWER: (u8, u8) = (1, 2);
+const AND_POWER: (u8, u8) = (3, 4);
+const NOT_POWER: u8 = 5;
+const BINARY_TEST_POWER: (u8, u8) = (7, 8);
+
+/// A comment inside a condition, before it is attached to an operand.
+enum Comment<'a> {
+    Line(&'a str, Span),
+    Bracket(CMakeBracketLiteral<'a>, Span),
 }
 
-fn cmake_condition_binary_test(input: &str) -> IResult<&str, CMakeCondition> {
-    let operator = alt((
-        tag_no_case("EQUAL"),
-        tag_no_case("LESS_EQUAL"),
-        tag_no_case("LESS"),
-        tag_no_case("GREATER_EQUAL"),
-        tag_no_case("GREATER"),
-        tag_no_case("STREQUAL"),
-        tag_no_case("STRLESS_EQUAL"),
-        tag_no_case("STRLESS"),
-        tag_no_case("STRGREATER_EQUAL"),
-        tag_no_case("STRGREATER"),
-        tag_no_case("VERSION_EQUAL"),
-        tag_no_case("VERSION_LESS_EQUAL"),
-        tag_no_case("VERSION_LESS"),
-        tag_no_case("VERSION_GREATER_EQUAL"),
-        tag_no_case("VERSION_GREATER"),
-        tag_no_case("PATH_EQUAL"),
-        tag_no_case("MATCHES"),
-        tag_no_case("INLIST"),
-        tag_no_case("IN_LIST"),
-        tag_no_case("NOTINLIST"),
-        tag_no_case("NOT_IN_LIST"),
-    ));
-    let base = tuple((
-        cmake_condition_value,
-        nom::character::complete::multispace1,
-        operator,
-        nom::character::complete::multispace1,
-        cmake_condition_value,
-    ));
-    let inner = map(base, |(left, _, operator, _, right)| {
+fn condition_comment(input: Input<'_>) -> IResult<Input<'_>, Comment<'_>> {
+    alt((
+        map(spanned(cmake_bracket_comment(true)), |(comment, span)| {
+            Comment::Bracket(comment, span)
+        }),
+        map(spanned(cmake_comment), |(comment, span)| {
+            Comment::Line(comment, span)
+        }),
+    ))(input)
+}
+
+/// Nests `comments` in front of `tail`, the first comment outermost.
+fn attach_comments<'a>(
+    comments: Vec<Comment<'a>>,
+    tail: Option<CMakeCondition<'a>>,
+) -> Option<CMakeCondition<'a>> {
+    comments.into_iter().rev().fold(tail, |tail, comment| {
+        let end = tail.as_ref().map(|tail| tail.span().end);
+        let tail = tail.map(Box::new);
+        Some(match comment {
+            Comment::Line(content, span) => CMakeCondition::Comment {
+                content: content.into(),
+                tail,
+                span: Span::new(span.start, end.unwrap_or(span.end)),
+            },
+            Comment::Bracket(comment, span) => CMakeCondition::BracketComment {
+                comment,
+                tail,
+                span: Span::new(span.start, end.unwrap_or(span.end)),
+            },
+        })
+    })
+}
+
+/// Puts `comment`, which comes after `condition` and before the operator
+/// that follows it, after the last operand of `condition`.
+fn trail<'a>(condition: CMakeCondition<'a>, comment: CMakeCondition<'a>) -> CMakeCondition<'a> {
+    let end = comment.span().end;
+    match condition {
+        CMakeCondition::UnaryTest {
+            operator,
+            value,
+            span,
+        } => CMakeCondition::UnaryTest {
+            operator,
+            value: Box::new(trail(*value, comment)),
+            span: Span::new(span.start, end),
+        },
+        CMakeCondition::UnaryLogicalOperator {
+            operator,
+            value,
+            span,
+        } => CMakeCondition::UnaryLogicalOperator {
+            operator,
+            value: Box::new(trail(*value, comment)),
+            span: Span::new(span.start, end),
+        },
         CMakeCondition::BinaryTest {
-            operator: operator.to_string(),
-            left: Box::new(left),
-            right: Box::new(right),
+            operator,
+            left,
+            right,
+            span,
+        } => CMakeCondition::BinaryTest {
+            operator,
+            left,
+            right: Box::new(trail(*right, comment)),
+            span: Span::new(span.start, end),
+        },
+        CMakeCondition::BinaryLogicalOperator {
+            operator,
+            left,
+            right,
+            span,
+        } => CMakeCondition::BinaryLogicalOperator {
+            operator,
+            left,
+            right: Box::new(trail(*right, comment)),
+            span: Span::new(span.start, end),
+        },
+        CMakeCondition::Comment {
+            content,
+            tail: Some(tail),
+            span,
+        } => CMakeCondition::Comment {
+            content,
+            tail: Some(Box::new(trail(*tail, comment))),
+            span: Span::new(span.start, end),
+        },
+        CMakeCondition::BracketComment {
+            comment: bracket,
+            tail: Some(tail),
+            span,
+        } => CMakeCondition::BracketComment {
+            comment: bracket,
+            tail: Some(Box::new(trail(*tail, comment))),
+            span: Span::new(span.start, end),
+        },
+        value => {
+            let span = Span::new(value.span().start, end);
+            CMakeCondition::TrailingComment {
+                value: Box::new(value),
+                comment: Box::new(comment),
+                span,
+            }
         }
-    });
+    }
+}
 
-    context("condition_binary_test", inner)(input)
+/// Comments separated by whitespace, including the whitespace after them.
+fn condition_comments(input: Input<'_>) -> IResult<Input<'_>, Vec<Comment<'_>>> {
+    many0(terminated(condition_comment, multispace0))(input)
 }
 
-fn cmake_condition_value(input: &str) -> IResult<&str, CMakeCondition> {
+/// `operand`, after any number of comments.
+fn commented<'a>(
+    input: Input<'a>,
+    operand: ConditionParser,
+) -> IResult<Input<'a>, CMakeCondition<'a>> {
+    let (input, comments) = condition_comments(input)?;
+    if comments.is_empty() {
+        return operand(input);
+    }
+    let (input, value) = opt(operand)(input)?;
+    Ok((input, attach_comments(comments, value).unwrap()))
+}
+
+/// The operand of `DEFINED`, which may name an environment or cache
+/// variable.
+fn cmake_condition_defined_operand(input: Input<'_>) -> IResult<Input<'_>, CMakeCondition<'_>> {
+    let end = peek(alt((multispace1, tag(")"), tag("#"), eof)));
+    alt((
+        map(
+            terminated(strings::defined_variable, end),
+            CMakeCondition::Variable,
+        ),
+        cmake_condition_value,
+    ))(input)
+}
+
+fn cmake_condition_value(input: Input<'_>) -> IResult<Input<'_>, CMakeCondition<'_>> {
     let inner = map(cmake_value, CMakeCondition::Value);
     context("condition_value", inner)(input)
 }
 
-fn cmake_condition_unary_logical_operator(input: &str) -> IResult<&str, CMakeCondition> {
-    let base = tuple((
-        tag_no_case("NOT"),
-        nom::character::complete::multispace1,
-        cmake_condition,
-    ));
-    let inner = map(base, |(operator, _, value)| {
-        CMakeCondition::UnaryLogicalOperator {
-            operator: operator.to_string(),
-            value: Box::new(value),
-        }
-    });
+/// Skips whitespace and newlines, returning the input at the next token and
+/// the token.
+fn next_token(input: Input) -> (Input, Option<Token>) {
+    let token = Lexer::new(input.fragment(), position(&input))
+        .find(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Newline));
+    match token {
+        Some(token) => (advance(input, token.span.start), Some(token)),
+        None => (advance_to_end(input), None),
+    }
+}
 
-    context("condition_unary_logical_operator", inner)(input)
+fn advance(input: Input, to: Position) -> Input {
+    input.take_split(to.offset - input.location_offset()).0
 }
 
-fn cmake_condition_binary_logical_operator(input: &str) -> IResult<&str, CMakeCondition> {
-    let parse_operator = alt((tag_no_case("AND"), tag_no_case("OR")));
-    let parse_base = tuple((
-        cmake_condition_inner(Some(cmake_condition_binary_logical_operator)),
-        nom::character::complete::multispace1,
-        parse_operator,
-        nom::character::complete::multispace1,
-        cmake_condition,
-    ));
-    let inner = map(parse_base, |(left, _, operator, _, right)| {
-        CMakeCondition::BinaryLogicalOperator {
-            operator: operator.to_string(),
-            left: Box::new(left),
-            right: Box::new(right),
-        }
-    });
+fn advance_to_end(input: Input) -> Input {
+    input.take_split(input.fragment().len()).0
+}
 
-    context("binary_logical_operator", inner)(input)
+/// The text of `token`, which starts at `input`.
+fn token_text<'a>(input: Input<'a>, token: &Token) -> &'a str {
+    &input.fragment()[..token.span.end.offset - token.span.start.offset]
 }
 
-fn cmake_condition_comment(input: &str) -> IResult<&str, CMakeCondition> {
-    let inner = map(
-        tuple((cmake_comment, multispace0, opt(cmake_condition))),
-        |(comment, _, tail)| CMakeCondition::Comment {
-            content: comment.to_string(),
-            tail: tail.map(Box::new),
-        },
-    );
-    context("condition_comment", inner)(input)
+/// Whether `token` can start an operand.
+fn starts_operand(token: Option<Token>) -> bool {
+    token.is_some_and(|token| token.kind != TokenKind::RightParen)
 }
 
-fn cmake_condition_inner(
-    ignore: Option<fn(&str) -> IResult<&str, CMakeCondition>>,
-) -> impl Fn(&str) -> IResult<&str, CMakeCondition> {
-    let mut options = vec![
-        cmake_condition_binary_logical_operator,
-        cmake_condition_binary_test,
-        cmake_condition_unary_logical_operator,
-        cmake_condition_unary_test,
-        cmake_condition_parentheses,
-        cmake_condition_comment,
-        cmake_condition_value,
-    ];
-    if let Some(to_ignore) = ignore {
-        options = options
-            .into_iter()
-            .filter(|x| x != &to_ignore)
-            .collect::<Vec<_>>();
+/// Comments, each one after any whitespace.
+fn comments(input: Input<'_>) -> (Input<'_>, Vec<Comment<'_>>) {
+    let mut input = input;
+    let mut comments = Vec::new();
+    loop {
+        let (at_token, token) = next_token(input);
+        let is_comment = token.is_some_and(|token| {
+            matches!(token.kind, TokenKind::Comment | TokenKind::BracketComment)
+        });
+        if !is_comment {
+            return (input, comments);
+        }
+        match condition_comment(at_token) {
+            Ok((rest, comment)) => {
+                comments.push(comment);
+                input = rest;
+            }
+            Err(_) => return (input, comments),
+        }
     }
-    move |input| {
-        let parser = alt_list(&options);
-        parser(input)
+}
+
+fn cmake_condition_parentheses(input: Input<'_>) -> IResult<Input<'_>, CMakeCondition<'_>> {
+    let start = position(&input);
+    let (input, value) = cmake_condition(input.take_split(1).0)?;
+    let (input, token) = next_token(input);
+    match token {
+        Some(token) if token.kind == TokenKind::RightParen => Ok((
+            advance(input, token.span.end),
+            CMakeCondition::Parentheses {
+                value: Box::new(value),
+                span: Span::new(start, token.span.end),
+            },
+        )),
+        _ => Err(nom::Err::Error(ContextError::add_context(
+            input,
+            "condition_parentheses",
+            ErrorType::from_char(input, ')'),
+        ))),
     }
 }
 
-pub fn cmake_condition(input: &str) -> IResult<&str, CMakeCondition> {
-    let mut parser = context("cmake_condition", cmake_condition_inner(None));
-    parser(input)
+/// An operand, or an operator that comes before its operand. Comments before
+/// it are attached to it, along with everything up to `min_power`.
+fn cmake_condition_operand(
+    input: Input<'_>,
+    min_power: u8,
+) -> IResult<Input<'_>, CMakeCondition<'_>> {
+    let (input, token) = next_token(input);
+    let Some(token) = token else {
+        return cmake_condition_value(input);
+    };
+    match token.kind {
+        TokenKind::Comment | TokenKind::BracketComment => {
+            let (input, comments) = comments(input);
+            let (input, tail) = if starts_operand(next_token(input).1) {
+                let (input, tail) = cmake_condition_expression(input, min_power.max(NOT_POWER))?;
+                (input, Some(tail))
+            } else {
+                (input, None)
+            };
+            return Ok((input, attach_comments(comments, tail).unwrap()));
+        }
+        TokenKind::LeftParen => return cmake_condition_parentheses(input),
+        TokenKind::Identifier => {}
+        _ => return cmake_condition_value(input),
+    }
+
+    let operator = token_text(input, &token);
+    let after_operator = advance(input, token.span.end);
+    let (at_operand, next) = next_token(after_operator);
+    if !starts_operand(next) {
+        return cmake_condition_value(input);
+    }
+    if operator.eq_ignore_ascii_case("NOT") {
+        let (input, value) = cmake_condition_expression(after_operator, NOT_POWER)?;
+        let span = Span::new(token.span.start, value.span().end);
+        return Ok((
+            input,
+            CMakeCondition::UnaryLogicalOperator {
+                operator: operator.into(),
+                value: Box::new(value),
+                span,
+            },
+        ));
+    }
+    if UNARY_TESTS
+        .iter()
+        .any(|test| test.eq_ignore_ascii_case(operator))
+    {
+        let operand = if operator.eq_ignore_ascii_case("DEFINED") {
+            cmake_condition_defined_operand
+        } else {
+            cmake_condition_value
+        };
+        let (input, value) = commented(at_operand, operand)?;
+        let span = Span::new(token.span.start, value.span().end);
+        return Ok((
+            input,
+            CMakeCondition::UnaryTest {
+                operator: operator.into(),
+                value: Box::new(value),
+                span,
+            },
+        ));
+    }
+    cmake_condition_value(input)
 }
 
-fn alt_list<'a, T: Debug>(
-    parsers: &'a [impl Fn(&str) -> IResult<&str, T>],
-) -> impl Fn(&str) -> IResult<&str, T> + 'a {
-    return move |input| -> IResult<&str, T> {
-        let parser = &parsers[0];
-        let result = parser(input);
-        if let Ok(result) = result {
-            return Ok(result);
+fn infix_power(operator: &str) -> Option<(u8, u8)> {
+    if operator.eq_ignore_ascii_case("OR") {
+        Some(OR_POWER)
+    } else if operator.eq_ignore_ascii_case("AND") {
+        Some(AND_POWER)
+    } else if BINARY_TESTS
+        .iter()
+        .any(|test| test.eq_ignore_ascii_case(operator))
+    {
+        Some(BINARY_TEST_POWER)
+    } else {
+        None
+    }
+}
+
+/// A condition whose operators all bind at least as tight as `min_power`.
+/// Comments between an operand and the operator after it trail the operand.
+fn cmake_condition_expression(
+    input: Input<'_>,
+    min_power: u8,
+) -> IResult<Input<'_>, CMakeCondition<'_>> {
+    let (mut input, mut left) = cmake_condition_operand(input, min_power)?;
+    loop {
+        let (after_comments, comments) = comments(input);
+        let (at_operator, Some(token)) = next_token(after_comments) else {
+            break;
+        };
+        if token.kind != TokenKind::Identifier {
+            break;
         }
-        let mut error = result.unwrap_err();
-        for parser in parsers.iter().skip(1) {
-            let result = parser(input);
-            match result {
-                Ok(_) => return result,
-                Err(nom::Err::Error(err)) => error = error.map(|e| e.or(err)),
-                Err(nom::Err::Incomplete(_)) => {
-                    error = error.map(|e| e.or(ErrorType::from_error_kind(input, ErrorKind::Eof)));
-                }
-                Err(nom::Err::Failure(err)) => {
-                    error = error.map(|e| e.or(err));
-                }
-            }
+        let operator = token_text(at_operator, &token);
+        let Some((left_power, right_power)) = infix_power(operator) else {
+            break;
+        };
+        if left_power < min_power {
+            break;
         }
-        Err(error)
-    };
+        let after_operator = advance(at_operator, token.span.end);
+        let (rest, right) = cmake_condition_expression(after_operator, right_power)?;
+        let right = Box::new(right);
+        let operator = operator.into();
+        let left_operand = Box::new(match attach_comments(comments, None) {
+            Some(comment) => trail(left, comment),
+            None => left,
+        });
+        let span = Span::new(left_operand.span().start, right.span().end);
+        left = if left_power == BINARY_TEST_POWER.0 {
+            CMakeCondition::BinaryTest {
+                operator,
+                left: left_operand,
+                right,
+                span,
+            }
+        } else {
+            CMakeCondition::BinaryLogicalOperator {
+                operator,
+                left: left_operand,
+                right,
+                span,
+            }
+        };
+        input = rest;
+    }
+    Ok((input, left))
+}
+
+pub fn cmake_condition(input: Input<'_>) -> IResult<Input<'_>, CMakeCondition<'_>> {
+    let (input, condition) = context("cmake_condition", |input| {
+        cmake_condition_expression(input, 0)
+    })(input)?;
+    let (rest, comments) = comments(input);
+    if comments.is_empty() {
+        return Ok((input, condition));
+    }
+    let comment = attach_comments(comments, None).unwrap();
+    let span = Span::new(condition.span().start, comment.span().end);
+    Ok((
+        rest,
+        CMakeCondition::TrailingComment {
+            value: Box::new(condition),
+            comment: Box::new(comment),
+            span,
+        },
+    ))
 }
 
 #[cfg(test)]
 mod test {
     use nom::combinator::all_consuming;
 
-    use crate::parser::types::CMakeValue;
+    use crate::parser::types::{CMakeArgument, CMakeValue, Span};
+    use crate::parser::visit::Node;
 
     use super::*;
 
     #[test]
     fn test_parse_condition_value() {
         let input = "value";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
-            CMakeCondition::Value(CMakeValue::StringLiteral(String::from("value")))
+            result.without_spans(),
+            CMakeCondition::Value(CMakeValue::StringLiteral(
+                CMakeArgument::from("value"),
+                Span::default()
+            ))
         );
     }
 
     #[test]
     fn test_parse_condition_unary() {
         let input = "EXISTS /usr/include";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
+            result.without_spans(),
             CMakeCondition::UnaryTest {
-                operator: "EXISTS".to_string(),
+                operator: "EXISTS".into(),
                 value: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "/usr/include".to_string()
-                )))
+                    CMakeArgument::from("/usr/include"),
+                    Span::default()
+                ))),
+                span: Span::default()
             }
         );
     }
@@ -260,17 +521,20 @@ mod test {
     #[test]
     fn test_parse_condition_binary() {
         let input = "/usr/include STRLESS other";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
+            result.without_spans(),
             CMakeCondition::BinaryTest {
-                operator: "STRLESS".to_string(),
+                operator: "STRLESS".into(),
                 left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "/usr/include".to_string()
+                    CMakeArgument::from("/usr/include"),
+                    Span::default()
                 ))),
                 right: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "other".to_string()
-                )))
+                    CMakeArgument::from("other"),
+                    Span::default()
+                ))),
+                span: Span::default()
             }
         );
     }
@@ -278,17 +542,20 @@ mod test {
     #[test]
     fn test_parse_version_binary_condition() {
         let input = "CUDA_VERSION VERSION_GREATER_EQUAL 10.1";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
+            result.without_spans(),
             CMakeCondition::BinaryTest {
-                operator: "VERSION_GREATER_EQUAL".to_string(),
+                operator: "VERSION_GREATER_EQUAL".into(),
                 left: Box::new(CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
-                    "CUDA_VERSION".to_string()
+                    "CUDA_VERSION".into(),
+                    Span::default()
                 ))),
                 right: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "10.1".to_string()
-                )))
+                    CMakeArgument::from("10.1"),
+                    Span::default()
+                ))),
+                span: Span::default()
             }
         );
     }
@@ -296,19 +563,23 @@ mod test {
     #[test]
     fn test_parse_condition_unary_logical_operator() {
         let input = "NOT (EXISTS /usr/include)";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
+            result.without_spans(),
             CMakeCondition::UnaryLogicalOperator {
-                operator: "NOT".to_string(),
+                operator: "NOT".into(),
                 value: Box::new(CMakeCondition::Parentheses {
                     value: Box::new(CMakeCondition::UnaryTest {
-                        operator: "EXISTS".to_string(),
+                        operator: "EXISTS".into(),
                         value: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                            "/usr/include".to_string()
-                        )))
-                    })
+                            CMakeArgument::from("/usr/include"),
+                            Span::default()
+                        ))),
+                        span: Span::default()
+                    }),
+                    span: Span::default()
                 }),
+                span: Span::default(),
             }
         );
     }
@@ -316,45 +587,53 @@ mod test {
     #[test]
     fn test_parse_condition_binary_logical_operator() {
         let input = "true AND false";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         assert_eq!(
-            result,
+            result.without_spans(),
             CMakeCondition::BinaryLogicalOperator {
-                operator: "AND".to_string(),
+                operator: "AND".into(),
                 left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "true".to_string()
+                    CMakeArgument::from("true"),
+                    Span::default()
                 ))),
                 right: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                    "false".to_string()
-                )))
+                    CMakeArgument::from("false"),
+                    Span::default()
+                ))),
+                span: Span::default()
             }
         );
     }
 
-    #[ignore]
     #[test]
     fn test_parse_condition_with_command_before_operator() {
         let input = r#"true   # comment
             AND
             false"#;
-        let result = cmake_condition(input).unwrap();
+        let (rest, result) = cmake_condition(Input::new(input)).unwrap();
+        assert_eq!(*rest.fragment(), "");
         assert_eq!(
-            result,
-            (
-                "",
-                CMakeCondition::BinaryLogicalOperator {
-                    operator: "AND".to_string(),
-                    left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                        "true".to_string()
+            result.without_spans(),
+            CMakeCondition::BinaryLogicalOperator {
+                operator: "AND".into(),
+                left: Box::new(CMakeCondition::TrailingComment {
+                    value: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                        CMakeArgument::from("true"),
+                        Span::default()
                     ))),
-                    right: Box::new(CMakeCondition::Comment {
-                        content: " comment".to_string(),
-                        tail: Some(Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                            "false".to_string()
-                        ))))
-                    })
-                }
-            )
+                    comment: Box::new(CMakeCondition::Comment {
+                        content: " comment".into(),
+                        tail: None,
+                        span: Span::default()
+                    }),
+                    span: Span::default()
+                }),
+                right: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                    CMakeArgument::from("false"),
+                    Span::default()
+                ))),
+                span: Span::default()
+            }
         );
     }
 
@@ -363,106 +642,281 @@ mod test {
         let input = r#"true
             AND  # comment
             false"#;
-        let result = cmake_condition(input).unwrap();
+        let (rest, result) = cmake_condition(Input::new(input)).unwrap();
+        assert_eq!(*rest.fragment(), "");
         assert_eq!(
-            result,
-            (
-                "",
-                CMakeCondition::BinaryLogicalOperator {
-                    operator: "AND".to_string(),
-                    left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                        "true".to_string()
-                    ))),
-                    right: Box::new(CMakeCondition::Comment {
-                        content: " comment".to_string(),
-                        tail: Some(Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
-                            "false".to_string()
-                        ))))
-                    })
-                }
-            )
+            result.without_spans(),
+            CMakeCondition::BinaryLogicalOperator {
+                operator: "AND".into(),
+                left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                    CMakeArgument::from("true"),
+                    Span::default()
+                ))),
+                right: Box::new(CMakeCondition::Comment {
+                    content: " comment".into(),
+                    tail: Some(Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                        CMakeArgument::from("false"),
+                        Span::default()
+                    )))),
+                    span: Span::default()
+                }),
+                span: Span::default()
+            }
         );
     }
 
     #[test]
     fn test_parse_complex_expression() {
         let input = "(true AND false OR NOT true) OR true OR (true AND NOT (false OR true))";
-        let result = cmake_condition(input).unwrap().1;
+        let result = cmake_condition(Input::new(input)).unwrap().1;
         use CMakeCondition::*;
         use CMakeValue::*;
+        let value = |text: &'static str| {
+            Box::from(Value(StringLiteral(
+                CMakeArgument::from(text),
+                Span::default(),
+            )))
+        };
         assert_eq!(
-            result,
+            result.without_spans(),
             BinaryLogicalOperator {
-                operator: String::from("OR"),
-                left: Box::from(Parentheses {
-                    value: Box::from(BinaryLogicalOperator {
-                        operator: String::from("AND"),
-                        left: Box::from(Value(StringLiteral(String::from("true")))),
-                        right: Box::from(BinaryLogicalOperator {
-                            operator: String::from("OR"),
-                            left: Box::from(Value(StringLiteral(String::from("false")))),
-                            right: Box::from(UnaryLogicalOperator {
-                                operator: String::from("NOT"),
-                                value: Box::from(Value(StringLiteral(String::from("true"))))
-                            })
-                        })
-                    })
-                }),
-                right: Box::from(BinaryLogicalOperator {
-                    operator: String::from("OR"),
-                    left: Box::from(Value(StringLiteral(String::from("true")))),
-                    right: Box::from(Parentheses {
+                operator: "OR".into(),
+                left: Box::from(BinaryLogicalOperator {
+                    operator: "OR".into(),
+                    left: Box::from(Parentheses {
                         value: Box::from(BinaryLogicalOperator {
-                            operator: String::from("AND"),
-                            left: Box::from(Value(StringLiteral(String::from("true")))),
+                            operator: "OR".into(),
+                            left: Box::from(BinaryLogicalOperator {
+                                operator: "AND".into(),
+                                left: value("true"),
+                                right: value("false"),
+                                span: Span::default()
+                            }),
                             right: Box::from(UnaryLogicalOperator {
-                                operator: String::from("NOT"),
-                                value: Box::from(Parentheses {
-                                    value: Box::from(BinaryLogicalOperator {
-                                        operator: String::from("OR"),
-                                        left: Box::from(Value(StringLiteral(String::from(
-                                            "false"
-                                        )))),
-                                        right: Box::from(Value(StringLiteral(String::from(
-                                            "true"
-                                        ))))
-                                    })
-                                })
-                            })
-                        })
-                    })
-                })
+                                operator: "NOT".into(),
+                                value: value("true"),
+                                span: Span::default()
+                            }),
+                            span: Span::default()
+                        }),
+                        span: Span::default()
+                    }),
+                    right: value("true"),
+                    span: Span::default()
+                }),
+                right: Box::from(Parentheses {
+                    value: Box::from(BinaryLogicalOperator {
+                        operator: "AND".into(),
+                        left: value("true"),
+                        right: Box::from(UnaryLogicalOperator {
+                            operator: "NOT".into(),
+                            value: Box::from(Parentheses {
+                                value: Box::from(BinaryLogicalOperator {
+                                    operator: "OR".into(),
+                                    left: value("false"),
+                                    right: value("true"),
+                                    span: Span::default()
+                                }),
+                                span: Span::default()
+                            }),
+                            span: Span::default()
+                        }),
+                        span: Span::default()
+                    }),
+                    span: Span::default()
+                }),
+                span: Span::default()
             }
         );
     }
 
+    /// Prints the condition with parentheses around every operator.
+    fn structure(condition: &CMakeCondition<'_>) -> String {
+        match condition {
+            CMakeCondition::Parentheses { value, .. } => structure(value),
+            CMakeCondition::UnaryTest {
+                operator, value, ..
+            }
+            | CMakeCondition::UnaryLogicalOperator {
+                operator, value, ..
+            } => format!("({} {})", operator, structure(value)),
+            CMakeCondition::BinaryTest {
+                operator,
+                left,
+                right,
+                ..
+            }
+            | CMakeCondition::BinaryLogicalOperator {
+                operator,
+                left,
+                right,
+                ..
+            } => format!("({} {} {})", structure(left), operator, structure(right)),
+            CMakeCondition::Comment { content, tail, .. } => format!(
+                "#{}# {}",
+                content.trim(),
+                tail.as_deref().map(structure).unwrap_or_default()
+            ),
+            CMakeCondition::BracketComment { comment, tail, .. } => format!(
+                "#[{}]# {}",
+                comment.contents.trim(),
+                tail.as_deref().map(structure).unwrap_or_default()
+            ),
+            CMakeCondition::TrailingComment { value, comment, .. } => {
+                format!("{} {}", structure(value), structure(comment).trim_end())
+            }
+            CMakeCondition::Variable(variable) => format!(
+                "{:?}{{{}}}",
+                variable.kind,
+                variable.literal_name().unwrap()
+            ),
+            CMakeCondition::Value(value) => match value {
+                CMakeValue::ArgumentSpecifier(text, _) => text.to_string(),
+                CMakeValue::StringLiteral(argument, _) => argument.to_string(),
+                value => format!("{:?}", value),
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_condition_precedence() {
+        for (input, expected) in [
+            ("A OR B AND C", "(A OR (B AND C))"),
+            ("A AND B OR C", "((A AND B) OR C)"),
+            ("A OR B OR C", "((A OR B) OR C)"),
+            ("NOT A AND B", "((NOT A) AND B)"),
+            ("NOT NOT A", "(NOT (NOT A))"),
+            ("NOT A STREQUAL B", "(NOT (A STREQUAL B))"),
+            ("EXISTS a AND b", "((EXISTS a) AND b)"),
+            ("DEFINED X STREQUAL Y", "((DEFINED X) STREQUAL Y)"),
+            ("(A OR B) AND C", "((A OR B) AND C)"),
+            ("a LESS b LESS c", "((a LESS b) LESS c)"),
+            (
+                "NOTHING or ORANGE and ANDROID",
+                "(NOTHING or (ORANGE and ANDROID))",
+            ),
+        ] {
+            let (rest, result) = cmake_condition(Input::new(input)).unwrap();
+            assert_eq!(*rest.fragment(), "", "{}", input);
+            assert_eq!(structure(&result), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_long_chain() {
+        let operands: Vec<String> = (0..500).map(|i| format!("A{i}")).collect();
+        let input = format!("{})", operands.join(" AND "));
+        let (rest, mut result) = cmake_condition(Input::new(&input)).unwrap();
+        assert_eq!(*rest.fragment(), ")");
+        let mut depth = 0;
+        while let CMakeCondition::BinaryLogicalOperator { left, .. } = result {
+            result = *left;
+            depth += 1;
+        }
+        assert_eq!(depth, 499);
+    }
+
+    #[test]
+    fn test_parse_operators_next_to_parentheses() {
+        for (input, expected) in [
+            ("NOT(A)", "(NOT A)"),
+            ("(A)AND(B OR C)", "(A AND (B OR C))"),
+            ("NOT)", "NOT"),
+        ] {
+            let (_, result) = cmake_condition(Input::new(input)).unwrap();
+            assert_eq!(structure(&result), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_condition_spans() {
+        let input = "A AND B OR C";
+        let result = cmake_condition(Input::new(input)).unwrap().1;
+        let CMakeCondition::BinaryLogicalOperator { left, span, .. } = result else {
+            panic!("expected OR");
+        };
+        assert_eq!(span.range(), 0..12);
+        assert_eq!(left.span().range(), 0..7);
+    }
+
     #[test]
     fn test_multiline_condition() {
         let input = r#"CMAKE_C_COMPILER_ID STREQUAL "Clang" OR
 CMAKE_C_COMPILER_ID STREQUAL "AppleClang""#
             .trim();
-        let result = all_consuming(cmake_condition)(input).unwrap().1;
+        let result = all_consuming(cmake_condition)(Input::new(input)).unwrap().1;
         use CMakeCondition::*;
         use CMakeValue::*;
         assert_eq!(
-            result,
+            result.without_spans(),
             BinaryLogicalOperator {
-                operator: String::from("OR"),
+                operator: "OR".into(),
                 left: Box::from(BinaryTest {
-                    operator: String::from("STREQUAL"),
-                    left: Box::from(Value(ArgumentSpecifier(String::from(
-                        "CMAKE_C_COMPILER_ID"
-                    )))),
-                    right: Box::from(Value(QuotedString(String::from("Clang"))))
+                    operator: "STREQUAL".into(),
+                    left: Box::from(Value(ArgumentSpecifier(
+                        "CMAKE_C_COMPILER_ID".into(),
+                        Span::default()
+                    ))),
+                    right: Box::from(Value(QuotedString(
+                        CMakeArgument::from("Clang"),
+                        Span::default()
+                    ))),
+                    span: Span::default()
                 }),
                 right: Box::from(BinaryTest {
-                    operator: String::from("STREQUAL"),
-                    left: Box::from(Value(ArgumentSpecifier(String::from(
-                        "CMAKE_C_COMPILER_ID"
-                    )))),
-                    right: Box::from(Value(QuotedString(String::from("AppleClang"))))
-                })
+                    operator: "STREQUAL".into(),
+                    left: Box::from(Value(ArgumentSpecifier(
+                        "CMAKE_C_COMPILER_ID".into(),
+                        Span::default()
+                    ))),
+                    right: Box::from(Value(QuotedString(
+                        CMakeArgument::from("AppleClang"),
+                        Span::default()
+                    ))),
+                    span: Span::default()
+                }),
+                span: Span::default()
             }
         );
     }
+
+    #[test]
+    fn test_parse_all_operators() {
+        for (input, expected) in [
+            ("IS_READABLE f", "(IS_READABLE f)"),
+            ("IS_WRITABLE f", "(IS_WRITABLE f)"),
+            ("IS_EXECUTABLE f", "(IS_EXECUTABLE f)"),
+            ("a IS_NEWER_THAN b", "(a IS_NEWER_THAN b)"),
+            ("x IN_LIST y", "(x IN_LIST y)"),
+            ("x INLIST y", "(x INLIST y)"),
+            ("x NOTINLIST y", "(x NOTINLIST y)"),
+            ("x NOT_IN_LIST y", "(x NOT_IN_LIST y)"),
+            ("a PATH_EQUAL b", "(a PATH_EQUAL b)"),
+            ("DEFINED ENV{HOME}", "(DEFINED Env{HOME})"),
+            ("DEFINED CACHE{X} AND Y", "((DEFINED Cache{X}) AND Y)"),
+            ("DEFINED ENV_X", "(DEFINED ENV_X)"),
+        ] {
+            let (rest, result) = cmake_condition(Input::new(input)).unwrap();
+            assert_eq!(*rest.fragment(), "", "{}", input);
+            assert_eq!(structure(&result), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_comments_anywhere() {
+        for (input, expected) in [
+            ("# a\nA AND B", "(#a# A AND B)"),
+            ("A # a\nAND # b\nB", "(A #a# AND #b# B)"),
+            ("A AND NOT B # a\nOR C", "((A AND (NOT B #a#)) OR C)"),
+            ("NOT # a\nA", "(NOT #a# A)"),
+            ("EXISTS #[[a]] f", "(EXISTS #[a]# f)"),
+            ("A #[[a]] STREQUAL #[=[b]=] B", "(A #[a]# STREQUAL #[b]# B)"),
+            ("(A # a\n)", "A #a#"),
+            ("A OR B # a\n# b", "(A OR B) #a# #b#"),
+        ] {
+            let (rest, result) = cmake_condition(Input::new(input)).unwrap();
+            assert_eq!(*rest.fragment(), "", "{}", input);
+            assert_eq!(structure(&result), expected, "{}", input);
+        }
+    }
 }
@@ -21,64 +21,417 @@
 // THE SOFTWARE.
 
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::{char as parse_char, none_of};
-use nom::combinator::map;
-use nom::multi::many0;
-use nom::sequence::{delimited, preceded, tuple};
-
-use crate::parser::IResult;
-
-enum StringPart {
-    Char(char),
-    EscapedSlash,
-    EscapedQuote(char),
+use nom::bytes::complete::{tag, take_till1, take_while1};
+use nom::character::complete::{anychar, char as parse_char, line_ending, one_of};
+use nom::combinator::{all_consuming, map, opt, recognize, value};
+use nom::multi::separated_list1;
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, GeneratorExpression, VariableKind, VariableReference,
+};
+use crate::parser::{spanned, IResult, Input};
+
+fn text(input: Input<'_>) -> ArgumentPart<'_> {
+    ArgumentPart::Text((*input.fragment()).into())
 }
 
-pub fn parse_string(input: &str) -> IResult<&str, String> {
-    let parse_quote = map(
-        preceded(parse_char('\\'), parse_char('"')),
-        StringPart::EscapedQuote,
-    );
-    let parse_escaped_slash = map(tag("\\\\"), |_| StringPart::EscapedSlash);
-    let parse_string_char = map(none_of("\""), StringPart::Char);
-    map(
-        tuple((
-            delimited(
-                parse_char('"'),
-                many0(alt((parse_escaped_slash, parse_quote, parse_string_char))),
-                parse_char('"'),
-            ),
-            many0(parse_char(',')),
+/// An escape sequence or a line continuation.
+fn escape_sequence(input: Input<'_>) -> IResult<Input<'_>, ArgumentPart<'_>> {
+    preceded(
+        parse_char('\\'),
+        alt((
+            map(line_ending, |line_ending: Input| {
+                ArgumentPart::LineContinuation((*line_ending.fragment()).into())
+            }),
+            map(anychar, ArgumentPart::Escape),
         )),
-        |(parts, _)| {
-            parts
-                .iter()
-                .flat_map(|p| match p {
-                    StringPart::Char(c) => format!("{}", c).chars().collect::<Vec<char>>(),
-                    StringPart::EscapedQuote(_) => "\\\"".chars().collect(),
-                    StringPart::EscapedSlash => "\\\\".chars().collect(),
-                })
-                .collect::<String>()
+    )(input)
+}
+
+/// The name of a variable up to its closing `}`.
+fn variable_name(input: Input<'_>) -> IResult<Input<'_>, Vec<ArgumentPart<'_>>> {
+    let name_piece = alt((
+        map(variable_reference, |variable| {
+            Piece::Part(ArgumentPart::Variable(variable))
+        }),
+        map(escape_sequence, Piece::Part),
+        map(
+            take_till1(|c| c == '}' || c == '$' || c == '\\'),
+            Piece::Text,
+        ),
+        map(tag("$"), Piece::Text),
+    ));
+    terminated(merge_text(|c| c == '}', name_piece), parse_char('}'))(input)
+}
+
+/// `${NAME}`, `$ENV{NAME}` or `$CACHE{NAME}`.
+fn variable_reference(input: Input<'_>) -> IResult<Input<'_>, VariableReference<'_>> {
+    let open = alt((
+        value(VariableKind::Env, tag("$ENV{")),
+        value(VariableKind::Cache, tag("$CACHE{")),
+        value(VariableKind::Normal, tag("${")),
+    ));
+    map(
+        spanned(pair(open, variable_name)),
+        |((kind, name), span)| VariableReference { kind, name, span },
+    )(input)
+}
+
+/// `ENV{NAME}` or `CACHE{NAME}`, as tested by `if(DEFINED)`.
+pub fn defined_variable(input: Input<'_>) -> IResult<Input<'_>, VariableReference<'_>> {
+    let open = alt((
+        value(VariableKind::Env, tag("ENV{")),
+        value(VariableKind::Cache, tag("CACHE{")),
+    ));
+    map(
+        spanned(pair(open, variable_name)),
+        |((kind, name), span)| VariableReference { kind, name, span },
+    )(input)
+}
+
+/// `@NAME@`
+fn at_variable_reference(input: Input<'_>) -> IResult<Input<'_>, VariableReference<'_>> {
+    let name = take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_');
+    map(
+        spanned(delimited(parse_char('@'), name, parse_char('@'))),
+        |(name, span)| VariableReference {
+            kind: VariableKind::At,
+            name: vec![text(name)],
+            span,
         },
     )(input)
 }
 
+/// A part of an argument, or text to merge with the text next to it.
+enum Piece<'a> {
+    Text(Input<'a>),
+    Part(ArgumentPart<'a>),
+}
+
+/// Repeats `piece` until the end of the input or a character that `ends`,
+/// merging adjacent text into a single part that borrows from the input.
+fn merge_text<'a>(
+    ends: impl Fn(char) -> bool,
+    mut piece: impl FnMut(Input<'a>) -> IResult<Input<'a>, Piece<'a>>,
+) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, Vec<ArgumentPart<'a>>> {
+    move |start: Input<'a>| {
+        let offset = |input: Input| input.location_offset() - start.location_offset();
+        let mut parts = Vec::new();
+        // Where the text at the end of `parts` starts, if it ends with text
+        let mut text_start = None;
+        let mut input = start;
+        // Checking for the end first saves building the errors of every
+        // kind of piece
+        while !input.fragment().is_empty() && !input.fragment().starts_with(&ends) {
+            let (rest, piece) = match piece(input) {
+                Ok(result) => result,
+                Err(nom::Err::Error(_)) => return Ok((input, parts)),
+                Err(err) => return Err(err),
+            };
+            match piece {
+                Piece::Text(text) => {
+                    if text_start.is_some() {
+                        parts.pop();
+                    }
+                    let from = *text_start.get_or_insert(offset(text));
+                    let text = &start.fragment()[from..offset(rest)];
+                    parts.push(ArgumentPart::Text(text.into()));
+                }
+                Piece::Part(part) => {
+                    text_start = None;
+                    parts.push(part);
+                }
+            }
+            input = rest;
+        }
+        Ok((input, parts))
+    }
+}
+
+/// `$<NAME:PARAMETER,...>`. An expression without its closing `>` ends
+/// before any of the `terminators` or at the end of the input.
+fn generator_expression<'a>(
+    input: Input<'a>,
+    terminators: &'static str,
+) -> IResult<Input<'a>, GeneratorExpression<'a>> {
+    let name = preceded(tag("$<"), |input| parts(input, ":>", terminators));
+    let parameters = opt(preceded(
+        parse_char(':'),
+        separated_list1(parse_char(','), |input| parts(input, ",>", terminators)),
+    ));
+    map(
+        spanned(tuple((name, parameters, opt(parse_char('>'))))),
+        |((name, parameters, close), span)| GeneratorExpression {
+            name,
+            parameters: parameters.unwrap_or_default(),
+            terminated: close.is_some(),
+            span,
+        },
+    )(input)
+}
+
+/// Splits text into parts, stopping before any of the `stops` or
+/// `terminators`. Only the `terminators` end nested generator expressions.
+fn parts<'a>(
+    input: Input<'a>,
+    stops: &str,
+    terminators: &'static str,
+) -> IResult<Input<'a>, Vec<ArgumentPart<'a>>> {
+    let piece = alt((
+        // Plain text first, as it is the most common part
+        map(
+            take_till1(|c| stops.contains(c) || terminators.contains(c) || "$@\\".contains(c)),
+            Piece::Text,
+        ),
+        map(variable_reference, |variable| {
+            Piece::Part(ArgumentPart::Variable(variable))
+        }),
+        map(
+            |input| generator_expression(input, terminators),
+            |expression| Piece::Part(ArgumentPart::GeneratorExpression(expression)),
+        ),
+        map(at_variable_reference, |variable| {
+            Piece::Part(ArgumentPart::Variable(variable))
+        }),
+        map(escape_sequence, Piece::Part),
+        map(recognize(one_of("$@\\")), Piece::Text),
+    ));
+    merge_text(|c| stops.contains(c) || terminators.contains(c), piece)(input)
+}
+
+/// Splits argument text into parts, stopping before any of the `terminators`.
+fn argument_parts(
+    terminators: &'static str,
+) -> impl FnMut(Input) -> IResult<Input, CMakeArgument<'_>> {
+    move |input| {
+        map(
+            |input| parts(input, "", terminators),
+            |parts| CMakeArgument { parts },
+        )(input)
+    }
+}
+
+/// Splits the text of an unquoted argument into parts.
+pub fn parse_argument(input: Input) -> CMakeArgument {
+    all_consuming(argument_parts(""))(input)
+        .map(|(_, argument)| argument)
+        .unwrap_or_else(|_| CMakeArgument {
+            parts: vec![text(input)],
+        })
+}
+
+pub fn parse_string(input: Input<'_>) -> IResult<Input<'_>, CMakeArgument<'_>> {
+    delimited(parse_char('"'), argument_parts("\""), parse_char('"'))(input)
+}
+
+impl<'a> From<&'a str> for CMakeArgument<'a> {
+    fn from(text: &'a str) -> Self {
+        parse_argument(Input::new(text))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::parser::visit::Node;
 
     #[test]
     fn test_parse_string() {
         let input = "\"test 1234\"";
-        let (_, result) = parse_string(input).unwrap();
-        assert_eq!(result, "test 1234");
+        let (_, result) = parse_string(Input::new(input)).unwrap();
+        assert_eq!(result.to_string(), "test 1234");
     }
 
     #[test]
     fn test_parse_string_with_quotes() {
         let input = "\"test \\\"quote\\\" 1234\"";
-        let (_, result) = parse_string(input).unwrap();
-        assert_eq!(result, "test \\\"quote\\\" 1234");
+        let (_, result) = parse_string(Input::new(input)).unwrap();
+        assert_eq!(result.to_string(), "test \\\"quote\\\" 1234");
+    }
+
+    #[test]
+    fn test_parse_nested_variable_reference() {
+        let argument = CMakeArgument::from("lib${FOO_${BAR}}.a");
+        assert_eq!(
+            argument.parts.clone().without_spans(),
+            vec![
+                ArgumentPart::Text("lib".into()),
+                ArgumentPart::Variable(VariableReference {
+                    kind: VariableKind::Normal,
+                    name: vec![
+                        ArgumentPart::Text("FOO_".into()),
+                        ArgumentPart::Variable(VariableReference {
+                            kind: VariableKind::Normal,
+                            name: vec![ArgumentPart::Text("BAR".into())],
+                            span: Default::default(),
+                        }),
+                    ],
+                    span: Default::default(),
+                }),
+                ArgumentPart::Text(".a".into()),
+            ]
+        );
+        assert_eq!(argument.to_string(), "lib${FOO_${BAR}}.a");
+
+        let variables = argument.variables();
+        assert_eq!(variables.len(), 2);
+        assert_eq!(variables[0].literal_name(), None);
+        assert_eq!(variables[1].literal_name(), Some(String::from("BAR")));
+        assert_eq!(variables[1].span.range(), 9..15);
+    }
+
+    #[test]
+    fn test_parse_variable_kinds() {
+        let argument = CMakeArgument::from("$ENV{HOME}/$CACHE{X}/@VAR@");
+        let variables: Vec<(VariableKind, Option<String>)> = argument
+            .variables()
+            .iter()
+            .map(|variable| (variable.kind, variable.literal_name()))
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                (VariableKind::Env, Some(String::from("HOME"))),
+                (VariableKind::Cache, Some(String::from("X"))),
+                (VariableKind::At, Some(String::from("VAR"))),
+            ]
+        );
+        assert_eq!(argument.to_string(), "$ENV{HOME}/$CACHE{X}/@VAR@");
+    }
+
+    #[test]
+    fn test_incomplete_references_are_text() {
+        for text in ["${FOO", "$", "cost: $5", "user@example.com", "a\\"] {
+            let argument = CMakeArgument::from(text);
+            assert!(argument.variables().is_empty(), "{}", text);
+            assert_eq!(argument.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_parse_string_parts() {
+        let input = "\"\\\"${NAME}\\\" is \\n\"";
+        let (_, result) = parse_string(Input::new(input)).unwrap();
+        assert_eq!(
+            result.parts.without_spans(),
+            vec![
+                ArgumentPart::Escape('"'),
+                ArgumentPart::Variable(VariableReference {
+                    kind: VariableKind::Normal,
+                    name: vec![ArgumentPart::Text("NAME".into())],
+                    span: Default::default(),
+                }),
+                ArgumentPart::Escape('"'),
+                ArgumentPart::Text(" is ".into()),
+                ArgumentPart::Escape('n'),
+            ]
+        );
+    }
+
+    fn text_part(text: &str) -> ArgumentPart<'_> {
+        ArgumentPart::Text(text.into())
+    }
+
+    #[test]
+    fn test_parse_generator_expression() {
+        let argument = CMakeArgument::from("$<TARGET_PROPERTY:foo,INCLUDE_DIRECTORIES>");
+        assert_eq!(
+            argument.parts.without_spans(),
+            vec![ArgumentPart::GeneratorExpression(GeneratorExpression {
+                name: vec![text_part("TARGET_PROPERTY")],
+                parameters: vec![
+                    vec![text_part("foo")],
+                    vec![text_part("INCLUDE_DIRECTORIES")]
+                ],
+                terminated: true,
+                span: Default::default(),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_generator_expressions() {
+        let input = "-I$<$<CONFIG:Debug>:${DIR}/$<TARGET_FILE:foo>>";
+        let argument = CMakeArgument::from(input);
+        assert_eq!(argument.to_string(), input);
+
+        let expressions = argument.generator_expressions();
+        let names: Vec<Option<String>> = expressions
+            .iter()
+            .map(|expression| expression.literal_name())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                None,
+                Some(String::from("CONFIG")),
+                Some(String::from("TARGET_FILE"))
+            ]
+        );
+        assert_eq!(expressions[1].parameters, vec![vec![text_part("Debug")]]);
+        assert_eq!(expressions[2].span.range(), 27..45);
+        assert_eq!(
+            argument.variables()[0].literal_name(),
+            Some(String::from("DIR"))
+        );
+    }
+
+    #[test]
+    fn test_parse_generator_expression_parameters() {
+        let parameters = |input: &str| match CMakeArgument::from(input).parts.remove(0) {
+            ArgumentPart::GeneratorExpression(expression) => expression.parameters.len(),
+            part => panic!("{:?}", part),
+        };
+        assert_eq!(parameters("$<ANGLE-R>"), 0);
+        assert_eq!(parameters("$<JOIN:>"), 1);
+        assert_eq!(parameters("$<IF:a,b:c,d>"), 3);
+    }
+
+    #[test]
+    fn test_generator_expressions_in_strings() {
+        for input in [
+            "\"$<$<CONFIG:Debug>:-O0 -g>\"",
+            "\"$<TARGET_FILE:foo\"",
+            "\"\\$<NOT_AN_EXPRESSION>\"",
+        ] {
+            let (rest, result) = parse_string(Input::new(input)).unwrap();
+            assert_eq!(*rest.fragment(), "");
+            assert_eq!(format!("\"{}\"", result), input);
+        }
+        let (_, result) = parse_string(Input::new("\"$<TARGET_FILE:foo\"")).unwrap();
+        assert!(!result.generator_expressions()[0].terminated);
+        let (_, result) = parse_string(Input::new("\"\\$<NOT_AN_EXPRESSION>\"")).unwrap();
+        assert!(result.generator_expressions().is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_continuations() {
+        for line_ending in ["\n", "\r\n"] {
+            let input = format!("\"a \\{}b\"", line_ending);
+            let (rest, result) = parse_string(Input::new(&input)).unwrap();
+            assert_eq!(*rest.fragment(), "");
+            assert_eq!(
+                result.parts.clone().without_spans(),
+                vec![
+                    text_part("a "),
+                    ArgumentPart::LineContinuation(line_ending.into()),
+                    text_part("b"),
+                ]
+            );
+            assert_eq!(format!("\"{}\"", result), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_escape_sequences() {
+        let argument = CMakeArgument::from(r"\ \(\)\;\t\n\$\\");
+        assert_eq!(
+            argument.parts.without_spans(),
+            [' ', '(', ')', ';', 't', 'n', '$', '\\']
+                .into_iter()
+                .map(ArgumentPart::Escape)
+                .collect::<Vec<_>>()
+        );
     }
 }
@@ -1,143 +1,221 @@
-use crate::parser::types::CMakeCondition;
+use crate::parser::types::{CMakeArgument, CMakeCondition};
+use crate::parser::visit::Node;
 use nom::combinator::all_consuming;
 
 use super::*;
 
 #[test]
 fn test_parse_empty_string_literal() {
-    let result = all_consuming(cmake_string_literal)("").unwrap_err();
+    let result = all_consuming(cmake_string_literal)(Input::new("")).unwrap_err();
     assert!(matches!(result, nom::Err::Error(_)));
 }
 
 #[test]
 fn test_parse_string_literal_with_special_characters() {
-    let (_, result) = all_consuming(cmake_string_literal)("foo_bar123").unwrap();
-    assert_eq!(result, CMakeValue::StringLiteral("foo_bar123".to_string()));
+    let (_, result) = all_consuming(cmake_string_literal)(Input::new("foo_bar123")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("foo_bar123"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_interpolated_literal() {
-    let (_, result) = all_consuming(cmake_string_literal)("part/$(here)/there").unwrap();
+    let (_, result) =
+        all_consuming(cmake_string_literal)(Input::new("part/$(here)/there")).unwrap();
     assert_eq!(
-        result,
-        CMakeValue::StringLiteral("part/$(here)/there".to_string())
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("part/$(here)/there"), Span::default())
     );
 }
 
 #[test]
 fn test_parse_string_literal_with_parens() {
-    let result = all_consuming(cmake_string_literal)("(((foo").unwrap_err();
+    let result = all_consuming(cmake_string_literal)(Input::new("(((foo")).unwrap_err();
     assert!(matches!(result, nom::Err::Error(_)));
 }
 
+#[test]
+fn test_parse_string_literal_with_escapes() {
+    for input in [
+        r"a\ b",
+        r"\(x86\)",
+        r"a\;b",
+        r"\$HOME",
+        r"a\tb\n",
+        r"\#not_a_comment",
+    ] {
+        let (_, result) = all_consuming(cmake_string_literal)(Input::new(input)).unwrap();
+        assert_eq!(
+            result.without_spans(),
+            CMakeValue::StringLiteral(CMakeArgument::from(input), Span::default())
+        );
+    }
+}
+
+#[test]
+fn test_parse_legacy_unquoted_argument() {
+    let input = r#"-DFOO="a b (c)""#;
+    let (_, result) = all_consuming(cmake_string_literal)(Input::new(input)).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from(input), Span::default())
+    );
+}
+
+#[test]
+fn test_parse_string_literal_ends_at_comment() {
+    let (rest, result) = cmake_string_literal(Input::new("foo#bar")).unwrap();
+    assert_eq!(*rest.fragment(), "#bar");
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("foo"), Span::default())
+    );
+}
+
+#[test]
+fn test_parse_command_with_comment_after_argument() {
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(a#comment\n  b)")).unwrap();
+    assert_eq!(
+        result.args.without_spans(),
+        vec![
+            CMakeValue::StringLiteral(CMakeArgument::from("a"), Span::default()),
+            CMakeValue::Comment("comment".into(), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("b"), Span::default()),
+        ]
+    );
+}
+
 #[test]
 fn test_parse_quoted_string_with_escaped_quotes() {
-    let (_, result) = all_consuming(cmake_quoted_string_literal)(r#""foo""#).unwrap();
-    assert_eq!(result, CMakeValue::QuotedString(r#"foo"#.to_string()));
+    let (_, result) = all_consuming(cmake_quoted_string_literal)(Input::new(r#""foo""#)).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::QuotedString(CMakeArgument::from(r#"foo"#), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_quoted_string_with_inner_quotes() {
-    let (_, result) = all_consuming(cmake_quoted_string_literal)(r#""foo\"bar""#).unwrap();
-    assert_eq!(result, CMakeValue::QuotedString(r#"foo\"bar"#.to_string()));
+    let (_, result) =
+        all_consuming(cmake_quoted_string_literal)(Input::new(r#""foo\"bar""#)).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::QuotedString(CMakeArgument::from(r#"foo\"bar"#), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_quoted_string_empty() {
-    let (_, result) = all_consuming(cmake_quoted_string_literal)(r#""""#).unwrap();
-    assert_eq!(result, CMakeValue::QuotedString("".to_string()));
+    let (_, result) = all_consuming(cmake_quoted_string_literal)(Input::new(r#""""#)).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::QuotedString(CMakeArgument::from(""), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_quoted_string_with_newline() {
-    let (_, result) = all_consuming(cmake_quoted_string_literal)("\"foo\nbar\"").unwrap();
-    assert_eq!(result, CMakeValue::QuotedString("foo\nbar".to_string()));
+    let (_, result) =
+        all_consuming(cmake_quoted_string_literal)(Input::new("\"foo\nbar\"")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::QuotedString(CMakeArgument::from("foo\nbar"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_comment_empty() {
-    let (_, result) = all_consuming(cmake_comment)("#").unwrap();
+    let (_, result) = all_consuming(cmake_comment)(Input::new("#")).unwrap();
     assert_eq!(result, "");
 }
 
 #[test]
 fn test_parse_comment_with_spaces() {
-    let (_, result) = all_consuming(cmake_comment)("#    ").unwrap();
+    let (_, result) = all_consuming(cmake_comment)(Input::new("#    ")).unwrap();
     assert_eq!(result, "    ");
 }
 
 #[test]
 fn test_parse_comment_with_utf8() {
-    let (_, result) = all_consuming(cmake_comment)("# 解析外来字符").unwrap();
+    let (_, result) = all_consuming(cmake_comment)(Input::new("# 解析外来字符")).unwrap();
     assert_eq!(result, " 解析外来字符");
 }
 
 #[test]
 fn test_parse_comment_with_content() {
-    let (_, result) = all_consuming(cmake_comment)("# This is a test").unwrap();
+    let (_, result) = all_consuming(cmake_comment)(Input::new("# This is a test")).unwrap();
     assert_eq!(result, " This is a test");
 }
 
 #[test]
 fn test_parse_comment_with_special_characters() {
-    let (_, result) = all_consuming(cmake_comment)("# Special_chars: @#$%^&*()").unwrap();
+    let (_, result) =
+        all_consuming(cmake_comment)(Input::new("# Special_chars: @#$%^&*()")).unwrap();
     assert_eq!(result, " Special_chars: @#$%^&*()");
 }
 
 #[test]
 fn test_parse_command_name_simple() {
-    let (_, result) = all_consuming(cmake_command_name)("foo").unwrap();
-    assert_eq!(result, "foo");
+    let (_, result) = all_consuming(cmake_command_name)(Input::new("foo")).unwrap();
+    assert_eq!(*result.fragment(), "foo");
 }
 
 #[test]
 fn test_parse_command_name_with_underscores() {
-    let (_, result) = all_consuming(cmake_command_name)("foo_bar").unwrap();
-    assert_eq!(result, "foo_bar");
+    let (_, result) = all_consuming(cmake_command_name)(Input::new("foo_bar")).unwrap();
+    assert_eq!(*result.fragment(), "foo_bar");
 }
 
 #[test]
 fn test_parse_command_name_with_numbers() {
-    let (_, result) = all_consuming(cmake_command_name)("foo123").unwrap();
-    assert_eq!(result, "foo123");
+    let (_, result) = all_consuming(cmake_command_name)(Input::new("foo123")).unwrap();
+    assert_eq!(*result.fragment(), "foo123");
 }
 
 #[test]
 fn test_parse_command_name_fail_with_special_char() {
-    let result = all_consuming(cmake_command_name)("foo!bar").unwrap_err();
+    let result = all_consuming(cmake_command_name)(Input::new("foo!bar")).unwrap_err();
     assert!(matches!(result, nom::Err::Error(_)));
 }
 
 #[test]
 fn test_parse_command_name_fail_with_space() {
-    let result = all_consuming(cmake_command_name)("foo bar").unwrap_err();
+    let result = all_consuming(cmake_command_name)(Input::new("foo bar")).unwrap_err();
     assert!(matches!(result, nom::Err::Error(_)));
 }
 
 #[test]
 fn test_parse_string_unquoted() {
-    let (_, result) = all_consuming(cmake_value)("foo").unwrap();
-    assert_eq!(result, CMakeValue::StringLiteral("foo".to_string()));
+    let (_, result) = all_consuming(cmake_value)(Input::new("foo")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("foo"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_comment() {
-    let (_, result) = all_consuming(cmake_comment)("# This is a comment").unwrap();
+    let (_, result) = all_consuming(cmake_comment)(Input::new("# This is a comment")).unwrap();
     assert_eq!(result, " This is a comment");
 }
 
 #[test]
 fn test_parse_command_with_comment() {
-    let (_, result) = all_consuming(cmake_parser)("foo(bar) # comment").unwrap();
+    let (_, result) = all_consuming(cmake_parser)(Input::new("foo(bar) # comment")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![
                 CMakeStatement::Command(CMakeCommand {
-                    name: "foo".to_string(),
-                    args: vec![CMakeValue::StringLiteral("bar".to_string())],
+                    name: "foo".into(),
+                    args: vec![CMakeValue::StringLiteral(
+                        CMakeArgument::from("bar"),
+                        Span::default()
+                    )],
+                    span: Span::default(),
                 }),
-                CMakeStatement::Comment(" comment".to_string())
+                CMakeStatement::Comment(" comment".into(), Span::default())
             ]
         }
     );
@@ -145,87 +223,104 @@ fn test_parse_command_with_comment() {
 
 #[test]
 fn test_parse_string_quoted() {
-    let (_, result) = all_consuming(cmake_value)("\"foo\"").unwrap();
-    assert_eq!(result, CMakeValue::QuotedString("foo".to_string()));
+    let (_, result) = all_consuming(cmake_value)(Input::new("\"foo\"")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::QuotedString(CMakeArgument::from("foo"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_number_literal() {
-    let (_, result) = all_consuming(cmake_value)("1.2").unwrap();
-    assert_eq!(result, CMakeValue::StringLiteral("1.2".to_string()));
+    let (_, result) = all_consuming(cmake_value)(Input::new("1.2")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("1.2"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_version_literal() {
-    let (_, result) = all_consuming(cmake_value)("1.2.3").unwrap();
-    assert_eq!(result, CMakeValue::StringLiteral("1.2.3".to_string()));
+    let (_, result) = all_consuming(cmake_value)(Input::new("1.2.3")).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeValue::StringLiteral(CMakeArgument::from("1.2.3"), Span::default())
+    );
 }
 
 #[test]
 fn test_parse_empty_command() {
-    let (_, result) = all_consuming(cmake_command)("foo()").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo()")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![],
+            span: Span::default(),
         }
     );
 }
 
 #[test]
 fn test_parse_command() {
-    let (_, result) = all_consuming(cmake_command)("foo(bar)").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(bar)")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
-            args: vec![CMakeValue::StringLiteral("bar".to_string())],
+            name: "foo".into(),
+            args: vec![CMakeValue::StringLiteral(
+                CMakeArgument::from("bar"),
+                Span::default()
+            )],
+            span: Span::default(),
         }
     );
 }
 
 #[test]
 fn test_parse_command_with_string_argument() {
-    let (_, result) = all_consuming(cmake_command)("foo(\"here\" baz)").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(\"here\" baz)")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::QuotedString("here".to_string()),
-                CMakeValue::StringLiteral("baz".to_string()),
+                CMakeValue::QuotedString(CMakeArgument::from("here"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("baz"), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
 
 #[test]
 fn test_parse_command_with_dangling_comma() {
-    let (_, result) = all_consuming(cmake_command)("foo(\"here\", baz)").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(\"here\", baz)")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::QuotedString("here".to_string()),
-                CMakeValue::StringLiteral("baz".to_string()),
+                CMakeValue::QuotedString(CMakeArgument::from("here"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("baz"), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
 
 #[test]
 fn test_parse_command_multiple_args() {
-    let (_, result) = all_consuming(cmake_command)("foo(bar baz)").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(bar baz)")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral("bar".to_string()),
-                CMakeValue::StringLiteral("baz".to_string()),
+                CMakeValue::StringLiteral(CMakeArgument::from("bar"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("baz"), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
@@ -233,14 +328,17 @@ fn test_parse_command_multiple_args() {
 #[test]
 fn test_parse_command_with_variable() {
     let (_, result) =
-        all_consuming(cmake_command)("foo(\n  ${CMAKE_CURRENT_LIST_DIR}/vendor\n)").unwrap();
+        all_consuming(cmake_command)(Input::new("foo(\n  ${CMAKE_CURRENT_LIST_DIR}/vendor\n)"))
+            .unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![CMakeValue::StringLiteral(
-                "${CMAKE_CURRENT_LIST_DIR}/vendor".to_string()
+                CMakeArgument::from("${CMAKE_CURRENT_LIST_DIR}/vendor").without_spans(),
+                Span::default()
             ),],
+            span: Span::default(),
         }
     );
 }
@@ -248,18 +346,20 @@ fn test_parse_command_with_variable() {
 #[test]
 fn test_parse_command_with_line_break() {
     let (_, result) =
-        all_consuming(cmake_command)("foo(\n  name\n  VERSION bar\n  LANGUAGE ZIG\n)").unwrap();
+        all_consuming(cmake_command)(Input::new("foo(\n  name\n  VERSION bar\n  LANGUAGE ZIG\n)"))
+            .unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral("name".to_string()),
-                CMakeValue::ArgumentSpecifier("VERSION".to_string()),
-                CMakeValue::StringLiteral("bar".to_string()),
-                CMakeValue::ArgumentSpecifier("LANGUAGE".to_string()),
-                CMakeValue::ArgumentSpecifier("ZIG".to_string()),
+                CMakeValue::StringLiteral(CMakeArgument::from("name"), Span::default()),
+                CMakeValue::ArgumentSpecifier("VERSION".into(), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("bar"), Span::default()),
+                CMakeValue::ArgumentSpecifier("LANGUAGE".into(), Span::default()),
+                CMakeValue::ArgumentSpecifier("ZIG".into(), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
@@ -270,20 +370,23 @@ fn test_parse_complex_expr() {
 ((NOT MSVC) OR (${CMAKE_CXX_COMPILER_ID} MATCHES "Clang"))
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_args)(input).unwrap();
+    let (_, result) = all_consuming(cmake_args)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         vec![
-            CMakeValue::Parenthesis(String::from("(")),
-            CMakeValue::ArgumentSpecifier(String::from("NOT")),
-            CMakeValue::ArgumentSpecifier(String::from("MSVC")),
-            CMakeValue::Parenthesis(String::from(")")),
-            CMakeValue::ArgumentSpecifier(String::from("OR")),
-            CMakeValue::Parenthesis(String::from("(")),
-            CMakeValue::StringLiteral(String::from("${CMAKE_CXX_COMPILER_ID}")),
-            CMakeValue::ArgumentSpecifier(String::from("MATCHES")),
-            CMakeValue::QuotedString(String::from("Clang")),
-            CMakeValue::Parenthesis(String::from(")"))
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("NOT".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("MSVC".into(), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("OR".into(), Span::default()),
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("${CMAKE_CXX_COMPILER_ID}").without_spans(),
+                Span::default()
+            ),
+            CMakeValue::ArgumentSpecifier("MATCHES".into(), Span::default()),
+            CMakeValue::QuotedString(CMakeArgument::from("Clang"), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default())
         ]
     );
 }
@@ -294,15 +397,15 @@ fn test_parse_arg_list_inner() {
 (NOT MSVC) OR
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_arg_list_inner)(input).unwrap();
+    let (_, result) = all_consuming(cmake_arg_list_inner)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         vec![
-            CMakeValue::Parenthesis(String::from("(")),
-            CMakeValue::ArgumentSpecifier(String::from("NOT")),
-            CMakeValue::ArgumentSpecifier(String::from("MSVC")),
-            CMakeValue::Parenthesis(String::from(")")),
-            CMakeValue::ArgumentSpecifier(String::from("OR"))
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("NOT".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("MSVC".into(), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("OR".into(), Span::default())
         ]
     );
 }
@@ -313,14 +416,14 @@ fn test_parse_cmake_arg_parenthesis() {
 (NOT MSVC)
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_arg_parenthesis)(input).unwrap();
+    let (_, result) = all_consuming(cmake_arg_parenthesis)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         vec![
-            CMakeValue::Parenthesis("(".to_string()),
-            CMakeValue::ArgumentSpecifier("NOT".to_string()),
-            CMakeValue::ArgumentSpecifier("MSVC".to_string()),
-            CMakeValue::Parenthesis(")".to_string()),
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("NOT".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("MSVC".into(), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default()),
         ]
     );
 }
@@ -331,53 +434,56 @@ fn test_parse_cmake_nested_arg_parenthesis() {
 ((NOT MSVC) OR HERE)
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_arg_parenthesis)(input).unwrap();
+    let (_, result) = all_consuming(cmake_arg_parenthesis)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         vec![
-            CMakeValue::Parenthesis("(".to_string()),
-            CMakeValue::Parenthesis("(".to_string()),
-            CMakeValue::ArgumentSpecifier("NOT".to_string()),
-            CMakeValue::ArgumentSpecifier("MSVC".to_string()),
-            CMakeValue::Parenthesis(")".to_string()),
-            CMakeValue::ArgumentSpecifier("OR".to_string()),
-            CMakeValue::ArgumentSpecifier("HERE".to_string()),
-            CMakeValue::Parenthesis(")".to_string()),
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::Parenthesis("(".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("NOT".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("MSVC".into(), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("OR".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("HERE".into(), Span::default()),
+            CMakeValue::Parenthesis(")".into(), Span::default()),
         ]
     );
 }
 
 #[test]
 fn test_parse_command_with_two_args() {
-    let (_, result) = all_consuming(cmake_command)("foo(bar foo)").unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new("foo(bar foo)")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral("bar".to_string()),
-                CMakeValue::StringLiteral("foo".to_string()),
+                CMakeValue::StringLiteral(CMakeArgument::from("bar"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("foo"), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
 
 #[test]
 fn test_whitespace_gets_parsed() {
-    let (_, result) = all_consuming(cmake_parser)("foo()\n\nbar()").unwrap();
+    let (_, result) = all_consuming(cmake_parser)(Input::new("foo()\n\nbar()")).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![
                 CMakeStatement::Command(CMakeCommand {
-                    name: "foo".to_string(),
+                    name: "foo".into(),
                     args: vec![],
+                    span: Span::default(),
                 }),
-                CMakeStatement::Newline,
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
+                CMakeStatement::Newline(Span::default()),
                 CMakeStatement::Command(CMakeCommand {
-                    name: "bar".to_string(),
+                    name: "bar".into(),
                     args: vec![],
+                    span: Span::default(),
                 })
             ]
         }
@@ -386,7 +492,7 @@ fn test_whitespace_gets_parsed() {
 
 #[test]
 fn test_command_whitespace_is_parsed() {
-    let (_, result) = all_consuming(cmake_parser)(
+    let (_, result) = all_consuming(cmake_parser)(Input::new(
         "
 project(
 pyramid_envelope
@@ -394,20 +500,24 @@ VERSION 0.0.1
 LANGUAGES CXX
 )"
         .trim(),
-    )
+    ))
     .unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![CMakeStatement::Command(CMakeCommand {
-                name: "project".to_string(),
+                name: "project".into(),
                 args: vec![
-                    CMakeValue::StringLiteral("pyramid_envelope".to_string()),
-                    CMakeValue::ArgumentSpecifier("VERSION".to_string()),
-                    CMakeValue::StringLiteral("0.0.1".to_string()),
-                    CMakeValue::ArgumentSpecifier("LANGUAGES".to_string()),
-                    CMakeValue::ArgumentSpecifier("CXX".to_string()),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("pyramid_envelope"),
+                        Span::default()
+                    ),
+                    CMakeValue::ArgumentSpecifier("VERSION".into(), Span::default()),
+                    CMakeValue::StringLiteral(CMakeArgument::from("0.0.1"), Span::default()),
+                    CMakeValue::ArgumentSpecifier("LANGUAGES".into(), Span::default()),
+                    CMakeValue::ArgumentSpecifier("CXX".into(), Span::default()),
                 ],
+                span: Span::default(),
             }),]
         }
     );
@@ -426,38 +536,43 @@ endif ()
     "#
     .trim();
 
-    let (_, result) = all_consuming(cmake_parser)(input).unwrap();
+    let (_, result) = all_consuming(cmake_parser)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![CMakeStatement::If(CMakeIfStatement {
                 base: CMakeIfBase {
-                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                        "CMAKE_COMPILER_IS_GNUCXX"
-                    ))),
+                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                        "CMAKE_COMPILER_IS_GNUCXX".into(),
+                        Span::default()
+                    )),
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::Command(CMakeCommand {
-                            name: String::from("foo"),
-                            args: vec![]
+                            name: "foo".into(),
+                            args: vec![],
+                            span: Span::default()
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
                 },
                 else_ifs: vec![CMakeIfBase {
-                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                        "MSVC"
-                    ))),
+                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                        "MSVC".into(),
+                        Span::default()
+                    )),
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::Command(CMakeCommand {
-                            name: String::from("bar"),
-                            args: vec![]
+                            name: "bar".into(),
+                            args: vec![],
+                            span: Span::default()
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
                 }],
                 else_body: None,
+                span: Span::default(),
             }),]
         }
     )
@@ -466,23 +581,28 @@ endif ()
 #[test]
 fn test_parse_if_statement_with_single_condition() {
     let input = "if(ON)\nfoo()\nendif()";
-    let (_, result) = all_consuming(cmake_if_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_if_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::If(CMakeIfStatement {
             base: CMakeIfBase {
-                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from("ON"))),
+                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                    "ON".into(),
+                    Span::default()
+                )),
                 body: vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::Command(CMakeCommand {
-                        name: String::from("foo"),
-                        args: vec![]
+                        name: "foo".into(),
+                        args: vec![],
+                        span: Span::default()
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ],
             },
             else_ifs: vec![],
             else_body: None,
+            span: Span::default(),
         })
     );
 }
@@ -490,32 +610,36 @@ fn test_parse_if_statement_with_single_condition() {
 #[test]
 fn test_parse_if_statement_with_else() {
     let input = "if(OFF)\nfoo()\nelse()\nbar()\nendif()";
-    let (_, result) = all_consuming(cmake_if_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_if_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::If(CMakeIfStatement {
             base: CMakeIfBase {
-                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                    "OFF"
-                ))),
+                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                    "OFF".into(),
+                    Span::default()
+                )),
                 body: vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::Command(CMakeCommand {
-                        name: String::from("foo"),
-                        args: vec![]
+                        name: "foo".into(),
+                        args: vec![],
+                        span: Span::default()
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ],
             },
             else_ifs: vec![],
             else_body: Some(vec![
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
                 CMakeStatement::Command(CMakeCommand {
-                    name: String::from("bar"),
-                    args: vec![]
+                    name: "bar".into(),
+                    args: vec![],
+                    span: Span::default()
                 }),
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
             ]),
+            span: Span::default(),
         })
     );
 }
@@ -523,36 +647,43 @@ fn test_parse_if_statement_with_else() {
 #[test]
 fn test_parse_nested_if_statements() {
     let input = "if(ON)\nif(OFF)\nfoo()\nendif()\nendif()";
-    let (_, result) = all_consuming(cmake_if_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_if_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::If(CMakeIfStatement {
             base: CMakeIfBase {
-                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from("ON"))),
+                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                    "ON".into(),
+                    Span::default()
+                )),
                 body: vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::If(CMakeIfStatement {
                         base: CMakeIfBase {
                             condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
-                                String::from("OFF")
+                                "OFF".into(),
+                                Span::default()
                             )),
                             body: vec![
-                                CMakeStatement::Newline,
+                                CMakeStatement::Newline(Span::default()),
                                 CMakeStatement::Command(CMakeCommand {
-                                    name: String::from("foo"),
-                                    args: vec![]
+                                    name: "foo".into(),
+                                    args: vec![],
+                                    span: Span::default()
                                 }),
-                                CMakeStatement::Newline,
+                                CMakeStatement::Newline(Span::default()),
                             ],
                         },
                         else_ifs: vec![],
                         else_body: None,
+                        span: Span::default(),
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ],
             },
             else_ifs: vec![],
             else_body: None,
+            span: Span::default(),
         })
     );
 }
@@ -560,19 +691,21 @@ fn test_parse_nested_if_statements() {
 #[test]
 fn test_parse_multiple_commands() {
     let input = "foo()\nbar()";
-    let (_, result) = all_consuming(cmake_parser)(input).unwrap();
+    let (_, result) = all_consuming(cmake_parser)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![
                 CMakeStatement::Command(CMakeCommand {
-                    name: "foo".to_string(),
+                    name: "foo".into(),
                     args: vec![],
+                    span: Span::default(),
                 }),
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
                 CMakeStatement::Command(CMakeCommand {
-                    name: "bar".to_string(),
+                    name: "bar".into(),
                     args: vec![],
+                    span: Span::default(),
                 }),
             ]
         }
@@ -582,17 +715,18 @@ fn test_parse_multiple_commands() {
 #[test]
 fn test_parse_command_with_nested_parenthesis() {
     let input = "foo((bar baz))";
-    let (_, result) = all_consuming(cmake_command)(input).unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::Parenthesis("(".to_string()),
-                CMakeValue::StringLiteral("bar".to_string()),
-                CMakeValue::StringLiteral("baz".to_string()),
-                CMakeValue::Parenthesis(")".to_string()),
+                CMakeValue::Parenthesis("(".into(), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("bar"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("baz"), Span::default()),
+                CMakeValue::Parenthesis(")".into(), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
@@ -606,16 +740,17 @@ foo(
 )
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_command)(input).unwrap();
+    let (_, result) = all_consuming(cmake_command)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral("bar".to_string()),
-                CMakeValue::Comment(" here".to_string()),
-                CMakeValue::StringLiteral("baz".to_string()),
+                CMakeValue::StringLiteral(CMakeArgument::from("bar"), Span::default()),
+                CMakeValue::Comment(" here".into(), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("baz"), Span::default()),
             ],
+            span: Span::default(),
         }
     );
 }
@@ -627,17 +762,21 @@ foreach (line ${config_ac_contents})
 endforeach ()
 "#
     .trim();
-    let (_, result) = all_consuming(cmake_foreach_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_foreach_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::For(CMakeForEachStatement {
             group: CMakeCommandGroup {
                 clause: vec![
-                    CMakeValue::StringLiteral(String::from("line")),
-                    CMakeValue::StringLiteral(String::from("${config_ac_contents}"))
+                    CMakeValue::StringLiteral(CMakeArgument::from("line"), Span::default()),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("${config_ac_contents}").without_spans(),
+                        Span::default()
+                    )
                 ],
-                body: vec![CMakeStatement::Newline],
-                end_clause: vec![]
+                body: vec![CMakeStatement::Newline(Span::default())],
+                end_clause: vec![],
+                span: Span::default()
             }
         }),
     );
@@ -650,14 +789,18 @@ function (foo)
 endfunction ()
 "#
     .trim();
-    let (_, result) = all_consuming(cmake_function_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_function_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::Function(CMakeFunctionStatement {
             group: CMakeCommandGroup {
-                clause: vec![CMakeValue::StringLiteral(String::from("foo")),],
-                body: vec![CMakeStatement::Newline],
-                end_clause: vec![]
+                clause: vec![CMakeValue::StringLiteral(
+                    CMakeArgument::from("foo"),
+                    Span::default()
+                ),],
+                body: vec![CMakeStatement::Newline(Span::default())],
+                end_clause: vec![],
+                span: Span::default()
             }
         }),
     );
@@ -666,32 +809,36 @@ endfunction ()
 #[test]
 fn test_parse_else_with_args() {
     let input = "if(OFF)\nfoo()\nelse()\nbar()\nendif()";
-    let (_, result) = all_consuming(cmake_if_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_if_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::If(CMakeIfStatement {
             base: CMakeIfBase {
-                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                    "OFF"
-                ))),
+                condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                    "OFF".into(),
+                    Span::default()
+                )),
                 body: vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::Command(CMakeCommand {
-                        name: String::from("foo"),
-                        args: vec![]
+                        name: "foo".into(),
+                        args: vec![],
+                        span: Span::default()
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ],
             },
             else_ifs: vec![],
             else_body: Some(vec![
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
                 CMakeStatement::Command(CMakeCommand {
-                    name: String::from("bar"),
-                    args: vec![]
+                    name: "bar".into(),
+                    args: vec![],
+                    span: Span::default()
                 }),
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
             ]),
+            span: Span::default(),
         })
     );
 }
@@ -703,14 +850,18 @@ macro (foo)
 endmacro ()
 "#
     .trim();
-    let (_, result) = all_consuming(cmake_macro_group)(input).unwrap();
+    let (_, result) = all_consuming(cmake_macro_group::<false>)(Input::new(input)).unwrap();
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeStatement::Macro(CMakeMacroStatement {
             group: CMakeCommandGroup {
-                clause: vec![CMakeValue::StringLiteral(String::from("foo")),],
-                body: vec![CMakeStatement::Newline],
-                end_clause: vec![]
+                clause: vec![CMakeValue::StringLiteral(
+                    CMakeArgument::from("foo"),
+                    Span::default()
+                ),],
+                body: vec![CMakeStatement::Newline(Span::default())],
+                end_clause: vec![],
+                span: Span::default()
             }
         }),
     );
@@ -728,30 +879,192 @@ y z
 endfunction()
     "#
     .trim();
-    let (_, result) = all_consuming(cmake_parser)(input).unwrap();
+    let (_, result) = all_consuming(cmake_parser)(Input::new(input)).unwrap();
 
     assert_eq!(
-        result,
+        result.without_spans(),
         CMakeDocument {
             statements: vec![CMakeStatement::Function(CMakeFunctionStatement {
                 group: CMakeCommandGroup {
-                    clause: vec![CMakeValue::StringLiteral(String::from("foo")),],
+                    clause: vec![CMakeValue::StringLiteral(
+                        CMakeArgument::from("foo"),
+                        Span::default()
+                    ),],
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::Command(CMakeCommand {
-                            name: String::from("bar"),
+                            name: "bar".into(),
                             args: vec![
                                 // TODO we don't want these newlines
-                                CMakeValue::StringLiteral(String::from("x")),
-                                CMakeValue::StringLiteral(String::from("y")),
-                                CMakeValue::StringLiteral(String::from("z")),
+                                CMakeValue::StringLiteral(
+                                    CMakeArgument::from("x"),
+                                    Span::default()
+                                ),
+                                CMakeValue::StringLiteral(
+                                    CMakeArgument::from("y"),
+                                    Span::default()
+                                ),
+                                CMakeValue::StringLiteral(
+                                    CMakeArgument::from("z"),
+                                    Span::default()
+                                ),
                             ],
+                            span: Span::default(),
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
-                    end_clause: vec![]
+                    end_clause: vec![],
+                    span: Span::default()
                 }
             })],
         }
     )
 }
+
+#[test]
+fn test_parse_while() {
+    let input = "while(i LESS 10)\nbreak()\nendwhile()";
+    let (_, result) = all_consuming(cmake_while_group::<false>)(Input::new(input)).unwrap();
+    assert_eq!(
+        result.without_spans(),
+        CMakeStatement::While(CMakeWhileStatement {
+            condition: CMakeCondition::BinaryTest {
+                operator: "LESS".into(),
+                left: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                    CMakeArgument::from("i"),
+                    Span::default()
+                ))),
+                right: Box::new(CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                    "10".into(),
+                    Span::default()
+                ))),
+                span: Span::default(),
+            },
+            body: vec![
+                CMakeStatement::Newline(Span::default()),
+                CMakeStatement::Command(CMakeCommand {
+                    name: "break".into(),
+                    args: vec![],
+                    span: Span::default()
+                }),
+                CMakeStatement::Newline(Span::default()),
+            ],
+            span: Span::default(),
+        })
+    );
+}
+
+#[test]
+fn test_parse_while_is_not_a_command() {
+    assert!(cmake_command(Input::new("while(ON)")).is_err());
+    assert!(cmake_command(Input::new("endwhile()")).is_err());
+}
+
+#[test]
+fn test_spans_point_at_the_source() {
+    let input = "foo(a \"b\")\nif(NOT x)\n  # ünïcode\n  bar(c)\nendif()";
+    let (_, document) = all_consuming(cmake_parser)(Input::new(input)).unwrap();
+    let text = |span: Span| &input[span.range()];
+
+    let [CMakeStatement::Command(foo), CMakeStatement::Newline(newline), CMakeStatement::If(if_statement)] =
+        document.statements.as_slice()
+    else {
+        panic!("unexpected statements {:?}", document.statements);
+    };
+    assert_eq!(text(foo.span), "foo(a \"b\")");
+    assert_eq!(text(foo.args[0].span()), "a");
+    assert_eq!(text(foo.args[1].span()), "\"b\"");
+    assert_eq!(text(*newline), "\n");
+    assert_eq!(text(if_statement.span), &input[11..]);
+    assert_eq!(text(if_statement.base.condition.span()), "NOT x");
+
+    let body = &if_statement.base.body;
+    let comment = body[1].span();
+    assert_eq!(text(comment), "# ünïcode");
+    assert_eq!((comment.start.line, comment.start.column), (3, 3));
+    let bar = body[3].span();
+    assert_eq!(text(bar), "bar(c)");
+    assert_eq!(
+        (
+            bar.start.line,
+            bar.start.column,
+            bar.end.line,
+            bar.end.column
+        ),
+        (4, 3, 4, 9)
+    );
+}
+
+#[test]
+fn test_same_tree_ignores_spans() {
+    let (_, a) = all_consuming(cmake_command)(Input::new("foo(a   b)")).unwrap();
+    let (_, b) = all_consuming(cmake_command)(Input::new("foo(a b)")).unwrap();
+    assert_ne!(a.span.end, b.span.end);
+    assert_ne!(a, b);
+    assert!(a.same_tree(&b));
+    let (_, c) = all_consuming(cmake_command)(Input::new("foo(a c)")).unwrap();
+    assert!(!a.same_tree(&c));
+}
+
+#[test]
+fn test_strict_parser_fails_on_bad_statement() {
+    let result = cmake_parser(Input::new("foo(a\nbar()\n"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_recovering_parser_keeps_bad_regions_as_text() {
+    let input = "foo(a\n  b\nbar(c)\n\nendif()\nbaz()\n";
+    let (_, document) = cmake_parser_recovering(Input::new(input)).unwrap();
+    let kinds: Vec<_> = document
+        .statements
+        .iter()
+        .map(|statement| match statement {
+            CMakeStatement::Error(error) => format!("error {:?}", error.text),
+            CMakeStatement::Command(command) => format!("command {}", command.name),
+            CMakeStatement::Newline(_) => "newline".to_string(),
+            other => panic!("unexpected statement {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "error \"foo(a\\n  b\"",
+            "newline",
+            "command bar",
+            "newline",
+            "newline",
+            "error \"endif()\"",
+            "newline",
+            "command baz",
+        ]
+    );
+}
+
+#[test]
+fn test_recovering_parser_recovers_inside_blocks() {
+    let input = "if(A)\n  foo(\n  bar()\nelse()\n  baz(\nendif()\n";
+    let (_, document) = cmake_parser_recovering(Input::new(input)).unwrap();
+    let [CMakeStatement::If(if_statement)] = document.statements.as_slice() else {
+        panic!("expected an if block, got {:?}", document.statements);
+    };
+    let errors: Vec<_> = if_statement
+        .base
+        .body
+        .iter()
+        .chain(if_statement.else_body.iter().flatten())
+        .filter_map(|statement| match statement {
+            CMakeStatement::Error(error) => Some(&*error.text),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(errors, vec!["foo(", "baz("]);
+}
+
+#[test]
+fn test_recovering_parser_matches_strict_parser_on_valid_input() {
+    let input = "if(A)\n  foo(a b)\nendif()\n# comment\nbar()\n";
+    let (_, strict) = cmake_parser(Input::new(input)).unwrap();
+    let (_, recovering) = cmake_parser_recovering(Input::new(input)).unwrap();
+    assert_eq!(strict, recovering);
+}
@@ -0,0 +1,331 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! The syntax tree as indented text, for reading while debugging the parser.
+//!
+//! Every node is a line with its kind, using the names of
+//! [`crate::parser::json`], its text and its span as
+//! `line:column..line:column`, followed by its children indented by two
+//! spaces:
+//!
+//! ```text
+//! command "set" 1:1..1:14
+//!   unquoted "a" 1:5..1:6
+//!   quoted "${b}" 1:7..1:13
+//!     variable "${b}" 1:8..1:12
+//! ```
+//!
+//! The parts of an argument are only listed when it references variables or
+//! generator expressions.
+
+use std::fmt::{Display, Write};
+
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeBracketLiteral, CMakeCommandGroup, CMakeCondition,
+    CMakeDocument, CMakeStatement, CMakeValue, Span,
+};
+
+struct Tree {
+    output: String,
+    depth: usize,
+}
+
+impl Tree {
+    /// Writes a node, then the children written by `children` one level
+    /// deeper.
+    fn node(&mut self, label: impl Display, span: Option<Span>, children: impl FnOnce(&mut Self)) {
+        let _ = write!(self.output, "{}{}", "  ".repeat(self.depth), label);
+        if let Some(Span { start, end }) = span {
+            let _ = write!(
+                self.output,
+                " {}:{}..{}:{}",
+                start.line, start.column, end.line, end.column
+            );
+        }
+        self.output.push('\n');
+        self.depth += 1;
+        children(self);
+        self.depth -= 1;
+    }
+
+    fn leaf(&mut self, label: impl Display, span: Span) {
+        self.node(label, Some(span), |_| {});
+    }
+
+    fn statements(&mut self, statements: &[CMakeStatement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &CMakeStatement) {
+        match statement {
+            CMakeStatement::Command(command) => self.node(
+                format!("command {:?}", command.name),
+                Some(command.span),
+                |tree| tree.values(&command.args),
+            ),
+            CMakeStatement::If(statement) => self.node("if", Some(statement.span), |tree| {
+                tree.condition(&statement.base.condition);
+                tree.body(&statement.base.body);
+                for else_if in &statement.else_ifs {
+                    tree.node("else_if", None, |tree| {
+                        tree.condition(&else_if.condition);
+                        tree.body(&else_if.body);
+                    });
+                }
+                if let Some(body) = &statement.else_body {
+                    tree.node("else_body", None, |tree| tree.statements(body));
+                }
+            }),
+            CMakeStatement::While(statement) => self.node("while", Some(statement.span), |tree| {
+                tree.condition(&statement.condition);
+                tree.body(&statement.body);
+            }),
+            CMakeStatement::For(statement) => self.group("foreach", &statement.group),
+            CMakeStatement::Function(statement) => self.group("function", &statement.group),
+            CMakeStatement::Macro(statement) => self.group("macro", &statement.group),
+            CMakeStatement::Block(statement) => self.group("block", &statement.group),
+            CMakeStatement::Comment(text, span) => self.leaf(format!("comment {:?}", text), *span),
+            CMakeStatement::BracketComment(literal, span) => {
+                self.leaf(format!("bracket_comment {:?}", bracket(literal)), *span)
+            }
+            CMakeStatement::Newline(span) => self.leaf("newline", *span),
+            CMakeStatement::Error(error) => self.node(
+                format!("error {:?}", error.text),
+                Some(error.span),
+                |tree| {
+                    tree.leaf(
+                        format!(
+                            "{:?} {:?}",
+                            error.diagnostic.severity, error.diagnostic.message
+                        )
+                        .to_lowercase(),
+                        error.diagnostic.span,
+                    )
+                },
+            ),
+        }
+    }
+
+    fn body(&mut self, body: &[CMakeStatement]) {
+        self.node("body", None, |tree| tree.statements(body));
+    }
+
+    fn group(&mut self, kind: &str, group: &CMakeCommandGroup) {
+        self.node(kind, Some(group.span), |tree| {
+            tree.node("arguments", None, |tree| tree.values(&group.clause));
+            tree.body(&group.body);
+            tree.node("end_arguments", None, |tree| tree.values(&group.end_clause));
+        });
+    }
+
+    fn values(&mut self, values: &[CMakeValue]) {
+        for value in values {
+            self.value(value);
+        }
+    }
+
+    fn value(&mut self, value: &CMakeValue) {
+        match value {
+            CMakeValue::ArgumentSpecifier(text, span) => {
+                self.leaf(format!("keyword {:?}", text), *span)
+            }
+            CMakeValue::QuotedString(argument, span) => self.argument("quoted", argument, *span),
+            CMakeValue::StringLiteral(argument, span) => self.argument("unquoted", argument, *span),
+            CMakeValue::BracketQuotedString(literal, span) => {
+                self.leaf(format!("bracket {:?}", bracket(literal)), *span)
+            }
+            CMakeValue::Comment(text, span) => self.leaf(format!("comment {:?}", text), *span),
+            CMakeValue::BracketComment(literal, span) => {
+                self.leaf(format!("bracket_comment {:?}", bracket(literal)), *span)
+            }
+            CMakeValue::Parenthesis(text, span) => {
+                self.leaf(format!("parenthesis {:?}", text), *span)
+            }
+        }
+    }
+
+    fn argument(&mut self, kind: &str, argument: &CMakeArgument, span: Span) {
+        self.node(
+            format!("{} {:?}", kind, argument.to_string()),
+            Some(span),
+            |tree| tree.parts(&argument.parts),
+        );
+    }
+
+    /// Writes `parts`, unless they are all text.
+    fn parts(&mut self, parts: &[ArgumentPart]) {
+        let expand = parts.iter().any(|part| {
+            matches!(
+                part,
+                ArgumentPart::Variable(_) | ArgumentPart::GeneratorExpression(_)
+            )
+        });
+        if !expand {
+            return;
+        }
+        for part in parts {
+            match part {
+                ArgumentPart::Text(text) => self.node(format!("text {:?}", text), None, |_| {}),
+                ArgumentPart::Escape(_) => {
+                    self.node(format!("escape {:?}", part.to_string()), None, |_| {})
+                }
+                ArgumentPart::LineContinuation(_) => self.node(
+                    format!("line_continuation {:?}", part.to_string()),
+                    None,
+                    |_| {},
+                ),
+                ArgumentPart::Variable(variable) => self.node(
+                    format!("variable {:?}", variable.to_string()),
+                    Some(variable.span),
+                    |tree| tree.parts(&variable.name),
+                ),
+                ArgumentPart::GeneratorExpression(expression) => self.node(
+                    format!("generator_expression {:?}", expression.to_string()),
+                    Some(expression.span),
+                    |tree| {
+                        tree.parts(&expression.name);
+                        for parameter in &expression.parameters {
+                            let text: String =
+                                parameter.iter().map(|part| part.to_string()).collect();
+                            tree.node(format!("parameter {:?}", text), None, |tree| {
+                                tree.parts(parameter)
+                            });
+                        }
+                    },
+                ),
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &CMakeCondition) {
+        let span = Some(condition.span());
+        match condition {
+            CMakeCondition::Parentheses { value, .. } => {
+                self.node("parentheses", span, |tree| tree.condition(value))
+            }
+            CMakeCondition::UnaryTest {
+                operator, value, ..
+            } => self.node(format!("unary_test {:?}", operator), span, |tree| {
+                tree.condition(value)
+            }),
+            CMakeCondition::UnaryLogicalOperator {
+                operator, value, ..
+            } => self.node(format!("unary_logical {:?}", operator), span, |tree| {
+                tree.condition(value)
+            }),
+            CMakeCondition::BinaryTest {
+                operator,
+                left,
+                right,
+                ..
+            } => self.node(format!("binary_test {:?}", operator), span, |tree| {
+                tree.condition(left);
+                tree.condition(right);
+            }),
+            CMakeCondition::BinaryLogicalOperator {
+                operator,
+                left,
+                right,
+                ..
+            } => self.node(format!("binary_logical {:?}", operator), span, |tree| {
+                tree.condition(left);
+                tree.condition(right);
+            }),
+            CMakeCondition::Comment { content, tail, .. } => {
+                self.node(format!("comment {:?}", content), span, |tree| {
+                    if let Some(tail) = tail {
+                        tree.condition(tail);
+                    }
+                })
+            }
+            CMakeCondition::BracketComment { comment, tail, .. } => self.node(
+                format!("bracket_comment {:?}", bracket(comment)),
+                span,
+                |tree| {
+                    if let Some(tail) = tail {
+                        tree.condition(tail);
+                    }
+                },
+            ),
+            CMakeCondition::TrailingComment { value, comment, .. } => {
+                self.node("trailing_comment", span, |tree| {
+                    tree.condition(value);
+                    tree.condition(comment);
+                })
+            }
+            CMakeCondition::Variable(variable) => self.node(
+                format!("variable {:?}", variable.to_string()),
+                span,
+                |tree| tree.parts(&variable.name),
+            ),
+            CMakeCondition::Value(value) => self.value(value),
+        }
+    }
+}
+
+/// A bracket literal as written, without the `#` of a comment.
+fn bracket(literal: &CMakeBracketLiteral) -> String {
+    format!("[{0}[{1}]{0}]", literal.delimiter, literal.contents)
+}
+
+/// Writes `document` as an indented tree.
+pub fn to_tree(document: &CMakeDocument<'_>) -> String {
+    let mut tree = Tree {
+        output: String::new(),
+        depth: 0,
+    };
+    tree.statements(&document.statements);
+    tree.output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::format::parse_document;
+
+    #[test]
+    fn test_to_tree() {
+        let source = "set(a \"${b}\") # c\nif(NOT d)\nelse()\nendif()\n";
+        assert_eq!(
+            to_tree(&parse_document(source).unwrap()),
+            [
+                "command \"set\" 1:1..1:14",
+                "  unquoted \"a\" 1:5..1:6",
+                "  quoted \"${b}\" 1:7..1:13",
+                "    variable \"${b}\" 1:8..1:12",
+                "comment \" c\" 1:15..1:18",
+                "newline 1:18..2:1",
+                "if 2:1..4:8",
+                "  unary_logical \"NOT\" 2:4..2:9",
+                "    unquoted \"d\" 2:8..2:9",
+                "  body",
+                "    newline 2:10..3:1",
+                "  else_body",
+                "    newline 3:7..4:1",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
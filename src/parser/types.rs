@@ -20,119 +20,452 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum CMakeValue {
-    ArgumentSpecifier(String),
-    QuotedString(String),
-    BracketQuotedString(CMakeBracketLiteral),
-    StringLiteral(String),
-    Comment(String),
-    BracketComment(CMakeBracketLiteral),
-    Parenthesis(String),
-}
-
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum CMakeCondition {
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::parser::diagnostics::Diagnostic;
+
+/// A location in the source text.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Position {
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub column: usize,
+}
+
+/// The region of the source text a node was parsed from. Nodes that are
+/// built by hand have an empty default span; use
+/// [`Node::same_tree`](crate::parser::visit::Node::same_tree) to compare
+/// nodes without their spans.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+
+    /// The byte range of the source text covered by this span.
+    pub fn range(&self) -> Range<usize> {
+        self.start.offset..self.end.offset
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum VariableKind {
+    /// `${NAME}`
+    Normal,
+    /// `$ENV{NAME}`
+    Env,
+    /// `$CACHE{NAME}`
+    Cache,
+    /// `@NAME@`, as expanded by `configure_file` and `string(CONFIGURE)`.
+    At,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct VariableReference<'a> {
+    pub kind: VariableKind,
+    /// The parts of the variable name, which may reference other variables
+    /// as in `${FOO_${BAR}}`.
+    pub name: Vec<ArgumentPart<'a>>,
+    pub span: Span,
+}
+
+impl VariableReference<'_> {
+    /// The variable name, unless it is built from other variables.
+    pub fn literal_name(&self) -> Option<String> {
+        literal(&self.name)
+    }
+}
+
+/// A generator expression such as `$<TARGET_FILE:foo>`.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct GeneratorExpression<'a> {
+    /// The parts before the first `:`, which may be a generator expression
+    /// itself as in `$<$<CONFIG:Debug>:-O0>`.
+    pub name: Vec<ArgumentPart<'a>>,
+    /// The parts after the first `:`, split on `,`. `$<NAME>` has no
+    /// parameters and `$<NAME:>` has a single empty one.
+    pub parameters: Vec<Vec<ArgumentPart<'a>>>,
+    /// Whether the closing `>` was found. Unterminated expressions run until
+    /// the end of the argument.
+    pub terminated: bool,
+    pub span: Span,
+}
+
+impl GeneratorExpression<'_> {
+    /// The expression name, unless it is built from variables or other
+    /// expressions.
+    pub fn literal_name(&self) -> Option<String> {
+        literal(&self.name)
+    }
+}
+
+fn literal(parts: &[ArgumentPart]) -> Option<String> {
+    parts
+        .iter()
+        .all(|part| {
+            matches!(
+                part,
+                ArgumentPart::Text(_) | ArgumentPart::Escape(_) | ArgumentPart::LineContinuation(_)
+            )
+        })
+        .then(|| parts.iter().map(|part| part.to_string()).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum ArgumentPart<'a> {
+    Text(Cow<'a, str>),
+    /// A backslash followed by the escaped character, such as `\"`.
+    Escape(char),
+    /// A backslash at the end of a line inside a quoted argument, which joins
+    /// the line with the next one. Holds the line ending.
+    LineContinuation(Cow<'a, str>),
+    Variable(VariableReference<'a>),
+    GeneratorExpression(GeneratorExpression<'a>),
+}
+
+/// The contents of a quoted or unquoted argument.
+#[derive(Debug, Clone, Default, PartialEq, PartialOrd)]
+pub struct CMakeArgument<'a> {
+    pub parts: Vec<ArgumentPart<'a>>,
+}
+
+impl<'a> CMakeArgument<'a> {
+    /// Every variable referenced by this argument, including the ones nested
+    /// in the names of other variables, outermost first.
+    pub fn variables(&self) -> Vec<&VariableReference<'a>> {
+        fn collect<'b, 'a>(
+            parts: &'b [ArgumentPart<'a>],
+            output: &mut Vec<&'b VariableReference<'a>>,
+        ) {
+            for part in parts {
+                match part {
+                    ArgumentPart::Variable(variable) => {
+                        output.push(variable);
+                        collect(&variable.name, output);
+                    }
+                    ArgumentPart::GeneratorExpression(expression) => {
+                        collect(&expression.name, output);
+                        for parameter in &expression.parameters {
+                            collect(parameter, output);
+                        }
+                    }
+                    ArgumentPart::Text(_)
+                    | ArgumentPart::Escape(_)
+                    | ArgumentPart::LineContinuation(_) => {}
+                }
+            }
+        }
+        let mut output = Vec::new();
+        collect(&self.parts, &mut output);
+        output
+    }
+
+    /// Every generator expression in this argument, including the nested
+    /// ones, outermost first.
+    pub fn generator_expressions(&self) -> Vec<&GeneratorExpression<'a>> {
+        fn collect<'b, 'a>(
+            parts: &'b [ArgumentPart<'a>],
+            output: &mut Vec<&'b GeneratorExpression<'a>>,
+        ) {
+            for part in parts {
+                match part {
+                    ArgumentPart::GeneratorExpression(expression) => {
+                        output.push(expression);
+                        collect(&expression.name, output);
+                        for parameter in &expression.parameters {
+                            collect(parameter, output);
+                        }
+                    }
+                    ArgumentPart::Variable(variable) => collect(&variable.name, output),
+                    ArgumentPart::Text(_)
+                    | ArgumentPart::Escape(_)
+                    | ArgumentPart::LineContinuation(_) => {}
+                }
+            }
+        }
+        let mut output = Vec::new();
+        collect(&self.parts, &mut output);
+        output
+    }
+}
+
+impl Display for VariableReference<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (open, close) = match self.kind {
+            VariableKind::Normal => ("${", "}"),
+            VariableKind::Env => ("$ENV{", "}"),
+            VariableKind::Cache => ("$CACHE{", "}"),
+            VariableKind::At => ("@", "@"),
+        };
+        f.write_str(open)?;
+        for part in &self.name {
+            write!(f, "{}", part)?;
+        }
+        f.write_str(close)
+    }
+}
+
+impl Display for GeneratorExpression<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("$<")?;
+        for part in &self.name {
+            write!(f, "{}", part)?;
+        }
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            f.write_str(if i == 0 { ":" } else { "," })?;
+            for part in parameter {
+                write!(f, "{}", part)?;
+            }
+        }
+        if self.terminated {
+            f.write_str(">")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ArgumentPart<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentPart::Text(text) => f.write_str(text),
+            ArgumentPart::Escape(c) => write!(f, "\\{}", c),
+            ArgumentPart::LineContinuation(line_ending) => write!(f, "\\{}", line_ending),
+            ArgumentPart::Variable(variable) => write!(f, "{}", variable),
+            ArgumentPart::GeneratorExpression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+/// Prints the argument as it appears in the source, without quotes.
+impl Display for CMakeArgument<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.parts.iter().try_for_each(|part| write!(f, "{}", part))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum CMakeValue<'a> {
+    ArgumentSpecifier(Cow<'a, str>, Span),
+    QuotedString(CMakeArgument<'a>, Span),
+    BracketQuotedString(CMakeBracketLiteral<'a>, Span),
+    StringLiteral(CMakeArgument<'a>, Span),
+    Comment(Cow<'a, str>, Span),
+    BracketComment(CMakeBracketLiteral<'a>, Span),
+    Parenthesis(Cow<'a, str>, Span),
+}
+
+impl CMakeValue<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            CMakeValue::ArgumentSpecifier(_, span)
+            | CMakeValue::QuotedString(_, span)
+            | CMakeValue::BracketQuotedString(_, span)
+            | CMakeValue::StringLiteral(_, span)
+            | CMakeValue::Comment(_, span)
+            | CMakeValue::BracketComment(_, span)
+            | CMakeValue::Parenthesis(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum CMakeCondition<'a> {
     Parentheses {
         // Parentheses ( and ).
-        value: Box<CMakeCondition>,
+        value: Box<CMakeCondition<'a>>,
+        span: Span,
     },
     UnaryTest {
         // Unary tests such as EXISTS, COMMAND, and DEFINED.
-        operator: String,
-        value: Box<CMakeCondition>,
+        operator: Cow<'a, str>,
+        value: Box<CMakeCondition<'a>>,
+        span: Span,
     },
     BinaryTest {
         // Binary tests such as EQUAL, LESS, LESS_EQUAL, GREATER, GREATER_EQUAL, STREQUAL, STRLESS, STRLESS_EQUAL, STRGREATER, STRGREATER_EQUAL, VERSION_EQUAL, VERSION_LESS, VERSION_LESS_EQUAL, VERSION_GREATER, VERSION_GREATER_EQUAL, PATH_EQUAL, and MATCHES.
-        operator: String,
-        left: Box<CMakeCondition>,
-        right: Box<CMakeCondition>,
+        operator: Cow<'a, str>,
+        left: Box<CMakeCondition<'a>>,
+        right: Box<CMakeCondition<'a>>,
+        span: Span,
     },
     UnaryLogicalOperator {
         // Unary logical operator NOT.
-        operator: String,
-        value: Box<CMakeCondition>,
+        operator: Cow<'a, str>,
+        value: Box<CMakeCondition<'a>>,
+        span: Span,
     },
     BinaryLogicalOperator {
         // Binary logical operators AND and OR, from left to right, without any short-circuit.
-        operator: String,
-        left: Box<CMakeCondition>,
-        right: Box<CMakeCondition>,
+        operator: Cow<'a, str>,
+        left: Box<CMakeCondition<'a>>,
+        right: Box<CMakeCondition<'a>>,
+        span: Span,
     },
     Comment {
-        content: String,
-        tail: Option<Box<CMakeCondition>>,
+        // A comment before an operand, which is the tail. Comments before an
+        // operator go before the operand that follows it.
+        content: Cow<'a, str>,
+        tail: Option<Box<CMakeCondition<'a>>>,
+        span: Span,
     },
-    Value(CMakeValue),
+    BracketComment {
+        // A bracket comment before an operand, like `Comment`.
+        comment: CMakeBracketLiteral<'a>,
+        tail: Option<Box<CMakeCondition<'a>>>,
+        span: Span,
+    },
+    TrailingComment {
+        // Comments after an operand, before the operator that follows it or
+        // the closing parenthesis. The comment is a `Comment` or
+        // `BracketComment` without a tail, or one whose tail is the next
+        // comment.
+        value: Box<CMakeCondition<'a>>,
+        comment: Box<CMakeCondition<'a>>,
+        span: Span,
+    },
+    // The `ENV{NAME}` or `CACHE{NAME}` operand of `DEFINED`.
+    Variable(VariableReference<'a>),
+    Value(CMakeValue<'a>),
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeCommand {
-    pub name: String,
-    pub args: Vec<CMakeValue>,
+impl CMakeCondition<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            CMakeCondition::Parentheses { span, .. }
+            | CMakeCondition::UnaryTest { span, .. }
+            | CMakeCondition::BinaryTest { span, .. }
+            | CMakeCondition::UnaryLogicalOperator { span, .. }
+            | CMakeCondition::BinaryLogicalOperator { span, .. }
+            | CMakeCondition::Comment { span, .. }
+            | CMakeCondition::BracketComment { span, .. }
+            | CMakeCondition::TrailingComment { span, .. } => *span,
+            CMakeCondition::Variable(variable) => variable.span,
+            CMakeCondition::Value(value) => value.span(),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeIfBase {
-    pub condition: CMakeCondition,
-    pub body: Vec<CMakeStatement>,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeCommand<'a> {
+    pub name: Cow<'a, str>,
+    pub args: Vec<CMakeValue<'a>>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeIfStatement {
-    pub base: CMakeIfBase,
-    pub else_ifs: Vec<CMakeIfBase>,
-    pub else_body: Option<Vec<CMakeStatement>>,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeIfBase<'a> {
+    pub condition: CMakeCondition<'a>,
+    pub body: Vec<CMakeStatement<'a>>,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeCommandGroup {
-    pub clause: Vec<CMakeValue>,
-    pub body: Vec<CMakeStatement>,
-    pub end_clause: Vec<CMakeValue>,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeIfStatement<'a> {
+    pub base: CMakeIfBase<'a>,
+    pub else_ifs: Vec<CMakeIfBase<'a>>,
+    pub else_body: Option<Vec<CMakeStatement<'a>>>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeForEachStatement {
-    pub group: CMakeCommandGroup,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeWhileStatement<'a> {
+    pub condition: CMakeCondition<'a>,
+    pub body: Vec<CMakeStatement<'a>>,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeFunctionStatement {
-    pub group: CMakeCommandGroup,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeCommandGroup<'a> {
+    pub clause: Vec<CMakeValue<'a>>,
+    pub body: Vec<CMakeStatement<'a>>,
+    pub end_clause: Vec<CMakeValue<'a>>,
+    pub span: Span,
+}
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeForEachStatement<'a> {
+    pub group: CMakeCommandGroup<'a>,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeMacroStatement {
-    pub group: CMakeCommandGroup,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeFunctionStatement<'a> {
+    pub group: CMakeCommandGroup<'a>,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeBlockStatement {
-    pub group: CMakeCommandGroup,
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeMacroStatement<'a> {
+    pub group: CMakeCommandGroup<'a>,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeBracketLiteral {
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeBlockStatement<'a> {
+    pub group: CMakeCommandGroup<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeBracketLiteral<'a> {
     pub is_comment: bool,
-    pub delimiter: String,
-    pub contents: String,
-}
-
-#[derive(Debug, PartialEq, PartialOrd)]
-pub enum CMakeStatement {
-    If(CMakeIfStatement),
-    For(CMakeForEachStatement),
-    Function(CMakeFunctionStatement),
-    Block(CMakeBlockStatement),
-    Macro(CMakeMacroStatement),
-    Command(CMakeCommand),
-    BracketComment(CMakeBracketLiteral),
-    Comment(String),
-    Newline,
-}
-
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct CMakeDocument {
-    pub statements: Vec<CMakeStatement>,
+    pub delimiter: Cow<'a, str>,
+    pub contents: Cow<'a, str>,
+}
+
+/// A region that could not be parsed, kept as raw text.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeErrorStatement<'a> {
+    pub text: Cow<'a, str>,
+    /// Why the region failed to parse.
+    pub diagnostic: Diagnostic,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum CMakeStatement<'a> {
+    If(CMakeIfStatement<'a>),
+    For(CMakeForEachStatement<'a>),
+    While(CMakeWhileStatement<'a>),
+    Function(CMakeFunctionStatement<'a>),
+    Block(CMakeBlockStatement<'a>),
+    Macro(CMakeMacroStatement<'a>),
+    Command(CMakeCommand<'a>),
+    BracketComment(CMakeBracketLiteral<'a>, Span),
+    Comment(Cow<'a, str>, Span),
+    Newline(Span),
+    Error(CMakeErrorStatement<'a>),
+}
+
+impl CMakeStatement<'_> {
+    pub fn span(&self) -> Span {
+        match self {
+            CMakeStatement::If(statement) => statement.span,
+            CMakeStatement::For(statement) => statement.group.span,
+            CMakeStatement::While(statement) => statement.span,
+            CMakeStatement::Function(statement) => statement.group.span,
+            CMakeStatement::Block(statement) => statement.group.span,
+            CMakeStatement::Macro(statement) => statement.group.span,
+            CMakeStatement::Command(command) => command.span,
+            CMakeStatement::Error(error) => error.span,
+            CMakeStatement::BracketComment(_, span)
+            | CMakeStatement::Comment(_, span)
+            | CMakeStatement::Newline(span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct CMakeDocument<'a> {
+    pub statements: Vec<CMakeStatement<'a>>,
 }
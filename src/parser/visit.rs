@@ -0,0 +1,846 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Traversal of the syntax tree in [`crate::parser::types`].
+//!
+//! [`Visit`] and [`VisitMut`] have a method for every node type. Each one
+//! calls the matching `walk_*` function by default, which visits the node's
+//! children, so an implementation only overrides the methods for the nodes
+//! it cares about. An override that should keep going into the children
+//! calls the `walk_*` function itself. `visit_span` is called for every
+//! span in the tree, a node's own span before its children.
+
+use crate::parser::types::{
+    ArgumentPart, CMakeArgument, CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand,
+    CMakeCommandGroup, CMakeCondition, CMakeDocument, CMakeErrorStatement, CMakeForEachStatement,
+    CMakeFunctionStatement, CMakeIfBase, CMakeIfStatement, CMakeMacroStatement, CMakeStatement,
+    CMakeValue, CMakeWhileStatement, GeneratorExpression, Span, VariableReference,
+};
+
+pub trait Visit<'ast> {
+    fn visit_document(&mut self, node: &'ast CMakeDocument<'ast>) {
+        walk_document(self, node)
+    }
+    fn visit_statement(&mut self, node: &'ast CMakeStatement<'ast>) {
+        walk_statement(self, node)
+    }
+    fn visit_if(&mut self, node: &'ast CMakeIfStatement<'ast>) {
+        walk_if(self, node)
+    }
+    /// The condition and body of an `if()` or `elseif()`.
+    fn visit_if_base(&mut self, node: &'ast CMakeIfBase<'ast>) {
+        walk_if_base(self, node)
+    }
+    fn visit_while(&mut self, node: &'ast CMakeWhileStatement<'ast>) {
+        walk_while(self, node)
+    }
+    fn visit_foreach(&mut self, node: &'ast CMakeForEachStatement<'ast>) {
+        walk_foreach(self, node)
+    }
+    fn visit_function(&mut self, node: &'ast CMakeFunctionStatement<'ast>) {
+        walk_function(self, node)
+    }
+    fn visit_macro(&mut self, node: &'ast CMakeMacroStatement<'ast>) {
+        walk_macro(self, node)
+    }
+    fn visit_block(&mut self, node: &'ast CMakeBlockStatement<'ast>) {
+        walk_block(self, node)
+    }
+    fn visit_command(&mut self, node: &'ast CMakeCommand<'ast>) {
+        walk_command(self, node)
+    }
+    fn visit_error(&mut self, node: &'ast CMakeErrorStatement<'ast>) {
+        walk_error(self, node)
+    }
+    fn visit_condition(&mut self, node: &'ast CMakeCondition<'ast>) {
+        walk_condition(self, node)
+    }
+    fn visit_value(&mut self, node: &'ast CMakeValue<'ast>) {
+        walk_value(self, node)
+    }
+    fn visit_bracket_literal(&mut self, _node: &'ast CMakeBracketLiteral<'ast>) {}
+    fn visit_argument(&mut self, node: &'ast CMakeArgument<'ast>) {
+        walk_argument(self, node)
+    }
+    fn visit_argument_part(&mut self, node: &'ast ArgumentPart<'ast>) {
+        walk_argument_part(self, node)
+    }
+    fn visit_variable(&mut self, node: &'ast VariableReference<'ast>) {
+        walk_variable(self, node)
+    }
+    fn visit_generator_expression(&mut self, node: &'ast GeneratorExpression<'ast>) {
+        walk_generator_expression(self, node)
+    }
+    fn visit_span(&mut self, _span: &'ast Span) {}
+}
+
+pub fn walk_document<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeDocument<'ast>,
+) {
+    walk_statements(visitor, &node.statements);
+}
+
+fn walk_statements<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    statements: &'ast [CMakeStatement<'ast>],
+) {
+    for statement in statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeStatement<'ast>,
+) {
+    match node {
+        CMakeStatement::If(statement) => visitor.visit_if(statement),
+        CMakeStatement::For(statement) => visitor.visit_foreach(statement),
+        CMakeStatement::While(statement) => visitor.visit_while(statement),
+        CMakeStatement::Function(statement) => visitor.visit_function(statement),
+        CMakeStatement::Block(statement) => visitor.visit_block(statement),
+        CMakeStatement::Macro(statement) => visitor.visit_macro(statement),
+        CMakeStatement::Command(command) => visitor.visit_command(command),
+        CMakeStatement::BracketComment(comment, span) => {
+            visitor.visit_span(span);
+            visitor.visit_bracket_literal(comment);
+        }
+        CMakeStatement::Error(error) => visitor.visit_error(error),
+        CMakeStatement::Comment(_, span) | CMakeStatement::Newline(span) => {
+            visitor.visit_span(span)
+        }
+    }
+}
+
+pub fn walk_if<'ast, V: Visit<'ast> + ?Sized>(visitor: &mut V, node: &'ast CMakeIfStatement<'ast>) {
+    visitor.visit_span(&node.span);
+    visitor.visit_if_base(&node.base);
+    for else_if in &node.else_ifs {
+        visitor.visit_if_base(else_if);
+    }
+    if let Some(body) = &node.else_body {
+        walk_statements(visitor, body);
+    }
+}
+
+pub fn walk_if_base<'ast, V: Visit<'ast> + ?Sized>(visitor: &mut V, node: &'ast CMakeIfBase<'ast>) {
+    visitor.visit_condition(&node.condition);
+    walk_statements(visitor, &node.body);
+}
+
+pub fn walk_while<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeWhileStatement<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    visitor.visit_condition(&node.condition);
+    walk_statements(visitor, &node.body);
+}
+
+pub fn walk_foreach<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeForEachStatement<'ast>,
+) {
+    walk_command_group(visitor, &node.group);
+}
+
+pub fn walk_function<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeFunctionStatement<'ast>,
+) {
+    walk_command_group(visitor, &node.group);
+}
+
+pub fn walk_macro<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeMacroStatement<'ast>,
+) {
+    walk_command_group(visitor, &node.group);
+}
+
+pub fn walk_block<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeBlockStatement<'ast>,
+) {
+    walk_command_group(visitor, &node.group);
+}
+
+/// Visits the arguments of the opening and closing commands, and the body.
+pub fn walk_command_group<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeCommandGroup<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    for value in &node.clause {
+        visitor.visit_value(value);
+    }
+    walk_statements(visitor, &node.body);
+    for value in &node.end_clause {
+        visitor.visit_value(value);
+    }
+}
+
+pub fn walk_command<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeCommand<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    for value in &node.args {
+        visitor.visit_value(value);
+    }
+}
+
+/// Visits the span of the statement and the spans of its diagnostic.
+pub fn walk_error<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeErrorStatement<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    visitor.visit_span(&node.diagnostic.span);
+    for label in &node.diagnostic.labels {
+        visitor.visit_span(&label.span);
+    }
+}
+
+pub fn walk_condition<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeCondition<'ast>,
+) {
+    match node {
+        CMakeCondition::Parentheses { value, span }
+        | CMakeCondition::UnaryTest { value, span, .. }
+        | CMakeCondition::UnaryLogicalOperator { value, span, .. } => {
+            visitor.visit_span(span);
+            visitor.visit_condition(value);
+        }
+        CMakeCondition::BinaryTest {
+            left, right, span, ..
+        }
+        | CMakeCondition::BinaryLogicalOperator {
+            left, right, span, ..
+        } => {
+            visitor.visit_span(span);
+            visitor.visit_condition(left);
+            visitor.visit_condition(right);
+        }
+        CMakeCondition::Comment { tail, span, .. } => {
+            visitor.visit_span(span);
+            if let Some(tail) = tail {
+                visitor.visit_condition(tail);
+            }
+        }
+        CMakeCondition::BracketComment {
+            comment,
+            tail,
+            span,
+        } => {
+            visitor.visit_span(span);
+            visitor.visit_bracket_literal(comment);
+            if let Some(tail) = tail {
+                visitor.visit_condition(tail);
+            }
+        }
+        CMakeCondition::TrailingComment {
+            value,
+            comment,
+            span,
+        } => {
+            visitor.visit_span(span);
+            visitor.visit_condition(value);
+            visitor.visit_condition(comment);
+        }
+        CMakeCondition::Variable(variable) => visitor.visit_variable(variable),
+        CMakeCondition::Value(value) => visitor.visit_value(value),
+    }
+}
+
+pub fn walk_value<'ast, V: Visit<'ast> + ?Sized>(visitor: &mut V, node: &'ast CMakeValue<'ast>) {
+    match node {
+        CMakeValue::QuotedString(argument, span) | CMakeValue::StringLiteral(argument, span) => {
+            visitor.visit_span(span);
+            visitor.visit_argument(argument);
+        }
+        CMakeValue::BracketQuotedString(literal, span)
+        | CMakeValue::BracketComment(literal, span) => {
+            visitor.visit_span(span);
+            visitor.visit_bracket_literal(literal);
+        }
+        CMakeValue::ArgumentSpecifier(_, span)
+        | CMakeValue::Comment(_, span)
+        | CMakeValue::Parenthesis(_, span) => visitor.visit_span(span),
+    }
+}
+
+pub fn walk_argument<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast CMakeArgument<'ast>,
+) {
+    for part in &node.parts {
+        visitor.visit_argument_part(part);
+    }
+}
+
+pub fn walk_argument_part<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast ArgumentPart<'ast>,
+) {
+    match node {
+        ArgumentPart::Variable(variable) => visitor.visit_variable(variable),
+        ArgumentPart::GeneratorExpression(expression) => {
+            visitor.visit_generator_expression(expression)
+        }
+        ArgumentPart::Text(_) | ArgumentPart::Escape(_) | ArgumentPart::LineContinuation(_) => {}
+    }
+}
+
+pub fn walk_variable<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast VariableReference<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    for part in &node.name {
+        visitor.visit_argument_part(part);
+    }
+}
+
+pub fn walk_generator_expression<'ast, V: Visit<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast GeneratorExpression<'ast>,
+) {
+    visitor.visit_span(&node.span);
+    for part in node.name.iter().chain(node.parameters.iter().flatten()) {
+        visitor.visit_argument_part(part);
+    }
+}
+
+/// Like [`Visit`], with mutable access to every node.
+pub trait VisitMut {
+    fn visit_document_mut(&mut self, node: &mut CMakeDocument<'_>) {
+        walk_document_mut(self, node)
+    }
+    fn visit_statement_mut(&mut self, node: &mut CMakeStatement<'_>) {
+        walk_statement_mut(self, node)
+    }
+    fn visit_if_mut(&mut self, node: &mut CMakeIfStatement<'_>) {
+        walk_if_mut(self, node)
+    }
+    /// The condition and body of an `if()` or `elseif()`.
+    fn visit_if_base_mut(&mut self, node: &mut CMakeIfBase<'_>) {
+        walk_if_base_mut(self, node)
+    }
+    fn visit_while_mut(&mut self, node: &mut CMakeWhileStatement<'_>) {
+        walk_while_mut(self, node)
+    }
+    fn visit_foreach_mut(&mut self, node: &mut CMakeForEachStatement<'_>) {
+        walk_foreach_mut(self, node)
+    }
+    fn visit_function_mut(&mut self, node: &mut CMakeFunctionStatement<'_>) {
+        walk_function_mut(self, node)
+    }
+    fn visit_macro_mut(&mut self, node: &mut CMakeMacroStatement<'_>) {
+        walk_macro_mut(self, node)
+    }
+    fn visit_block_mut(&mut self, node: &mut CMakeBlockStatement<'_>) {
+        walk_block_mut(self, node)
+    }
+    fn visit_command_mut(&mut self, node: &mut CMakeCommand<'_>) {
+        walk_command_mut(self, node)
+    }
+    fn visit_error_mut(&mut self, node: &mut CMakeErrorStatement<'_>) {
+        walk_error_mut(self, node)
+    }
+    fn visit_condition_mut(&mut self, node: &mut CMakeCondition<'_>) {
+        walk_condition_mut(self, node)
+    }
+    fn visit_value_mut(&mut self, node: &mut CMakeValue<'_>) {
+        walk_value_mut(self, node)
+    }
+    fn visit_bracket_literal_mut(&mut self, _node: &mut CMakeBracketLiteral<'_>) {}
+    fn visit_argument_mut(&mut self, node: &mut CMakeArgument<'_>) {
+        walk_argument_mut(self, node)
+    }
+    fn visit_argument_part_mut(&mut self, node: &mut ArgumentPart<'_>) {
+        walk_argument_part_mut(self, node)
+    }
+    fn visit_variable_mut(&mut self, node: &mut VariableReference<'_>) {
+        walk_variable_mut(self, node)
+    }
+    fn visit_generator_expression_mut(&mut self, node: &mut GeneratorExpression<'_>) {
+        walk_generator_expression_mut(self, node)
+    }
+    fn visit_span_mut(&mut self, _span: &mut Span) {}
+}
+
+pub fn walk_document_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeDocument<'_>) {
+    walk_statements_mut(visitor, &mut node.statements);
+}
+
+fn walk_statements_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    statements: &mut [CMakeStatement<'_>],
+) {
+    for statement in statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeStatement<'_>) {
+    match node {
+        CMakeStatement::If(statement) => visitor.visit_if_mut(statement),
+        CMakeStatement::For(statement) => visitor.visit_foreach_mut(statement),
+        CMakeStatement::While(statement) => visitor.visit_while_mut(statement),
+        CMakeStatement::Function(statement) => visitor.visit_function_mut(statement),
+        CMakeStatement::Block(statement) => visitor.visit_block_mut(statement),
+        CMakeStatement::Macro(statement) => visitor.visit_macro_mut(statement),
+        CMakeStatement::Command(command) => visitor.visit_command_mut(command),
+        CMakeStatement::BracketComment(comment, span) => {
+            visitor.visit_span_mut(span);
+            visitor.visit_bracket_literal_mut(comment);
+        }
+        CMakeStatement::Error(error) => visitor.visit_error_mut(error),
+        CMakeStatement::Comment(_, span) | CMakeStatement::Newline(span) => {
+            visitor.visit_span_mut(span)
+        }
+    }
+}
+
+pub fn walk_if_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeIfStatement<'_>) {
+    visitor.visit_span_mut(&mut node.span);
+    visitor.visit_if_base_mut(&mut node.base);
+    for else_if in &mut node.else_ifs {
+        visitor.visit_if_base_mut(else_if);
+    }
+    if let Some(body) = &mut node.else_body {
+        walk_statements_mut(visitor, body);
+    }
+}
+
+pub fn walk_if_base_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeIfBase<'_>) {
+    visitor.visit_condition_mut(&mut node.condition);
+    walk_statements_mut(visitor, &mut node.body);
+}
+
+pub fn walk_while_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeWhileStatement<'_>) {
+    visitor.visit_span_mut(&mut node.span);
+    visitor.visit_condition_mut(&mut node.condition);
+    walk_statements_mut(visitor, &mut node.body);
+}
+
+pub fn walk_foreach_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut CMakeForEachStatement<'_>,
+) {
+    walk_command_group_mut(visitor, &mut node.group);
+}
+
+pub fn walk_function_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut CMakeFunctionStatement<'_>,
+) {
+    walk_command_group_mut(visitor, &mut node.group);
+}
+
+pub fn walk_macro_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeMacroStatement<'_>) {
+    walk_command_group_mut(visitor, &mut node.group);
+}
+
+pub fn walk_block_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeBlockStatement<'_>) {
+    walk_command_group_mut(visitor, &mut node.group);
+}
+
+/// Visits the arguments of the opening and closing commands, and the body.
+pub fn walk_command_group_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut CMakeCommandGroup<'_>,
+) {
+    visitor.visit_span_mut(&mut node.span);
+    for value in &mut node.clause {
+        visitor.visit_value_mut(value);
+    }
+    walk_statements_mut(visitor, &mut node.body);
+    for value in &mut node.end_clause {
+        visitor.visit_value_mut(value);
+    }
+}
+
+pub fn walk_command_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeCommand<'_>) {
+    visitor.visit_span_mut(&mut node.span);
+    for value in &mut node.args {
+        visitor.visit_value_mut(value);
+    }
+}
+
+/// Visits the span of the statement and the spans of its diagnostic.
+pub fn walk_error_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeErrorStatement<'_>) {
+    visitor.visit_span_mut(&mut node.span);
+    visitor.visit_span_mut(&mut node.diagnostic.span);
+    for label in &mut node.diagnostic.labels {
+        visitor.visit_span_mut(&mut label.span);
+    }
+}
+
+pub fn walk_condition_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeCondition<'_>) {
+    match node {
+        CMakeCondition::Parentheses { value, span }
+        | CMakeCondition::UnaryTest { value, span, .. }
+        | CMakeCondition::UnaryLogicalOperator { value, span, .. } => {
+            visitor.visit_span_mut(span);
+            visitor.visit_condition_mut(value);
+        }
+        CMakeCondition::BinaryTest {
+            left, right, span, ..
+        }
+        | CMakeCondition::BinaryLogicalOperator {
+            left, right, span, ..
+        } => {
+            visitor.visit_span_mut(span);
+            visitor.visit_condition_mut(left);
+            visitor.visit_condition_mut(right);
+        }
+        CMakeCondition::Comment { tail, span, .. } => {
+            visitor.visit_span_mut(span);
+            if let Some(tail) = tail {
+                visitor.visit_condition_mut(tail);
+            }
+        }
+        CMakeCondition::BracketComment {
+            comment,
+            tail,
+            span,
+        } => {
+            visitor.visit_span_mut(span);
+            visitor.visit_bracket_literal_mut(comment);
+            if let Some(tail) = tail {
+                visitor.visit_condition_mut(tail);
+            }
+        }
+        CMakeCondition::TrailingComment {
+            value,
+            comment,
+            span,
+        } => {
+            visitor.visit_span_mut(span);
+            visitor.visit_condition_mut(value);
+            visitor.visit_condition_mut(comment);
+        }
+        CMakeCondition::Variable(variable) => visitor.visit_variable_mut(variable),
+        CMakeCondition::Value(value) => visitor.visit_value_mut(value),
+    }
+}
+
+pub fn walk_value_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeValue<'_>) {
+    match node {
+        CMakeValue::QuotedString(argument, span) | CMakeValue::StringLiteral(argument, span) => {
+            visitor.visit_span_mut(span);
+            visitor.visit_argument_mut(argument);
+        }
+        CMakeValue::BracketQuotedString(literal, span)
+        | CMakeValue::BracketComment(literal, span) => {
+            visitor.visit_span_mut(span);
+            visitor.visit_bracket_literal_mut(literal);
+        }
+        CMakeValue::ArgumentSpecifier(_, span)
+        | CMakeValue::Comment(_, span)
+        | CMakeValue::Parenthesis(_, span) => visitor.visit_span_mut(span),
+    }
+}
+
+pub fn walk_argument_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut CMakeArgument<'_>) {
+    for part in &mut node.parts {
+        visitor.visit_argument_part_mut(part);
+    }
+}
+
+pub fn walk_argument_part_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut ArgumentPart<'_>) {
+    match node {
+        ArgumentPart::Variable(variable) => visitor.visit_variable_mut(variable),
+        ArgumentPart::GeneratorExpression(expression) => {
+            visitor.visit_generator_expression_mut(expression)
+        }
+        ArgumentPart::Text(_) | ArgumentPart::Escape(_) | ArgumentPart::LineContinuation(_) => {}
+    }
+}
+
+pub fn walk_variable_mut<V: VisitMut + ?Sized>(visitor: &mut V, node: &mut VariableReference<'_>) {
+    visitor.visit_span_mut(&mut node.span);
+    for part in &mut node.name {
+        visitor.visit_argument_part_mut(part);
+    }
+}
+
+pub fn walk_generator_expression_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    node: &mut GeneratorExpression<'_>,
+) {
+    visitor.visit_span_mut(&mut node.span);
+    for part in node
+        .name
+        .iter_mut()
+        .chain(node.parameters.iter_mut().flatten())
+    {
+        visitor.visit_argument_part_mut(part);
+    }
+}
+
+/// Sets every span to the default, empty span.
+struct ClearSpans;
+
+impl VisitMut for ClearSpans {
+    fn visit_span_mut(&mut self, span: &mut Span) {
+        *span = Span::default();
+    }
+}
+
+/// A node of the syntax tree, which can be compared without its spans.
+///
+/// `==` compares the spans too, so a parsed tree is never equal to the same
+/// tree built by hand, or to the tree parsed from differently formatted
+/// source.
+pub trait Node: Clone + PartialEq {
+    /// Visits this node with `visitor`, starting with its `visit_*_mut`
+    /// method.
+    fn visit_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V);
+
+    /// This node with every span set to the default, empty span, as in nodes
+    /// built by hand.
+    fn without_spans(mut self) -> Self {
+        self.visit_mut(&mut ClearSpans);
+        self
+    }
+
+    /// Whether the two nodes are the same tree, wherever they were parsed
+    /// from.
+    fn same_tree(&self, other: &Self) -> bool {
+        self.clone().without_spans() == other.clone().without_spans()
+    }
+}
+
+macro_rules! impl_node {
+    ($($node:ident => $visit:ident,)*) => {
+        $(
+            impl Node for $node<'_> {
+                fn visit_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+                    visitor.$visit(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_node! {
+    CMakeDocument => visit_document_mut,
+    CMakeStatement => visit_statement_mut,
+    CMakeIfStatement => visit_if_mut,
+    CMakeWhileStatement => visit_while_mut,
+    CMakeForEachStatement => visit_foreach_mut,
+    CMakeFunctionStatement => visit_function_mut,
+    CMakeMacroStatement => visit_macro_mut,
+    CMakeBlockStatement => visit_block_mut,
+    CMakeCommand => visit_command_mut,
+    CMakeCondition => visit_condition_mut,
+    CMakeValue => visit_value_mut,
+    CMakeArgument => visit_argument_mut,
+    ArgumentPart => visit_argument_part_mut,
+    VariableReference => visit_variable_mut,
+    GeneratorExpression => visit_generator_expression_mut,
+}
+
+impl<T: Node> Node for Vec<T> {
+    fn visit_mut<V: VisitMut + ?Sized>(&mut self, visitor: &mut V) {
+        for node in self {
+            node.visit_mut(visitor);
+        }
+    }
+}
+
+impl CMakeDocument<'_> {
+    /// Every command named `name`, compared without case as CMake does, in
+    /// source order. Includes the commands nested in blocks.
+    pub fn commands_named(&self, name: &str) -> Vec<&CMakeCommand<'_>> {
+        struct Commands<'a, 'ast> {
+            name: &'a str,
+            found: Vec<&'ast CMakeCommand<'ast>>,
+        }
+
+        impl<'ast> Visit<'ast> for Commands<'_, 'ast> {
+            fn visit_command(&mut self, node: &'ast CMakeCommand<'ast>) {
+                if node.name.eq_ignore_ascii_case(self.name) {
+                    self.found.push(node);
+                }
+            }
+        }
+
+        let mut commands = Commands {
+            name,
+            found: Vec::new(),
+        };
+        commands.visit_document(self);
+        commands.found
+    }
+
+    /// Every variable reference, in arguments and conditions, including the
+    /// ones nested in the names of other variables. Outermost first.
+    pub fn variable_references(&self) -> Vec<&VariableReference<'_>> {
+        struct Variables<'ast>(Vec<&'ast VariableReference<'ast>>);
+
+        impl<'ast> Visit<'ast> for Variables<'ast> {
+            fn visit_variable(&mut self, node: &'ast VariableReference<'ast>) {
+                self.0.push(node);
+                walk_variable(self, node);
+            }
+        }
+
+        let mut variables = Variables(Vec::new());
+        variables.visit_document(self);
+        variables.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nom_supreme::final_parser::final_parser;
+
+    use super::*;
+    use crate::parser::{cmake_parser, Input};
+
+    fn parse(source: &str) -> CMakeDocument<'_> {
+        final_parser(cmake_parser)(Input::new(source))
+            .map_err(|err: nom_supreme::error::ErrorTree<Input>| err.to_string())
+            .unwrap()
+    }
+
+    fn print(document: &CMakeDocument<'_>) -> String {
+        let mut output = String::new();
+        document.print().render_fmt(80, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_commands_named() {
+        let document = parse(
+            "add_library(a)\nif(X)\n  ADD_LIBRARY(b)\nelse()\n  function(f)\n    add_library(c)\n  endfunction()\nendif()\n",
+        );
+        let names: Vec<String> = document
+            .commands_named("add_library")
+            .iter()
+            .map(|command| match &command.args[0] {
+                CMakeValue::StringLiteral(argument, _) => argument.to_string(),
+                value => panic!("unexpected argument {value:?}"),
+            })
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_variable_references() {
+        let document = parse(
+            "if(DEFINED ENV{HOME} AND \"${A}\" STREQUAL x)\n  set(B ${C_${D}} $<$<BOOL:${E}>:f>)\nendif()\n",
+        );
+        let names: Vec<String> = document
+            .variable_references()
+            .iter()
+            .map(|variable| variable.to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["$ENV{HOME}", "${A}", "${C_${D}}", "${D}", "${E}"]
+        );
+    }
+
+    #[test]
+    fn test_visit_spans() {
+        struct Spans(Vec<std::ops::Range<usize>>);
+
+        impl<'ast> Visit<'ast> for Spans {
+            fn visit_span(&mut self, span: &'ast Span) {
+                self.0.push(span.range());
+            }
+        }
+
+        let mut spans = Spans(Vec::new());
+        spans.visit_document(&parse("# a\nset(b ${c})\n"));
+        assert_eq!(spans.0, vec![0..3, 3..4, 4..15, 8..9, 10..14, 10..14]);
+    }
+
+    #[test]
+    fn test_rename_variable() {
+        struct Rename;
+
+        impl VisitMut for Rename {
+            fn visit_variable_mut(&mut self, node: &mut VariableReference<'_>) {
+                if node.literal_name().as_deref() == Some("OLD") {
+                    node.name = vec![ArgumentPart::Text("NEW".into())];
+                }
+                walk_variable_mut(self, node);
+            }
+        }
+
+        let mut document = parse("if(${OLD})\n  message(\"${OLD}/${X_${OLD}}\")\nendif()\n");
+        Rename.visit_document_mut(&mut document);
+        assert_eq!(
+            print(&document),
+            "if(${NEW})\n  message(\"${NEW}/${X_${NEW}}\")\nendif()"
+        );
+    }
+
+    #[test]
+    fn test_normalize_keywords() {
+        struct LowerCaseCommands;
+
+        impl VisitMut for LowerCaseCommands {
+            fn visit_command_mut(&mut self, node: &mut CMakeCommand<'_>) {
+                node.name = node.name.to_lowercase().into();
+            }
+        }
+
+        let mut document = parse("PROJECT(foo)\nforeach(x a b)\n  MESSAGE(${x})\nendforeach()\n");
+        LowerCaseCommands.visit_document_mut(&mut document);
+        assert_eq!(
+            print(&document),
+            "project(foo)\nforeach(x a b)\n  message(${x})\nendforeach()"
+        );
+    }
+
+    #[test]
+    fn test_walk_function_visits_the_body() {
+        struct FunctionCommands(Vec<String>, bool);
+
+        impl<'ast> Visit<'ast> for FunctionCommands {
+            fn visit_function(&mut self, node: &'ast CMakeFunctionStatement<'ast>) {
+                self.1 = true;
+                walk_function(self, node);
+                self.1 = false;
+            }
+
+            fn visit_command(&mut self, node: &'ast CMakeCommand<'ast>) {
+                if self.1 {
+                    self.0.push(node.name.to_string());
+                }
+            }
+        }
+
+        let mut commands = FunctionCommands(Vec::new(), false);
+        commands.visit_document(&parse("a()\nfunction(f)\n  b()\nendfunction()\nc()\n"));
+        assert_eq!(commands.0, vec!["b"]);
+    }
+}
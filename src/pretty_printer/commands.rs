@@ -0,0 +1,89 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Knowledge about the argument structure of well-known CMake commands.
+
+/// Describes how the arguments of a command should be laid out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandSpec {
+    /// Align the values of every keyword section in a column when the argument
+    /// list breaks.
+    pub align_keywords: bool,
+    /// Keywords followed by `key value` pairs until the end of the arguments,
+    /// such as `PROPERTIES` in `set_target_properties`.
+    pub pair_keywords: Vec<String>,
+    /// Keywords followed by a single key and its list of values until the end
+    /// of the arguments, such as `PROPERTY` in `set_property`.
+    pub key_values_keywords: Vec<String>,
+}
+
+impl CommandSpec {
+    pub fn is_pair_keyword(&self, keyword: &str) -> bool {
+        self.pair_keywords.iter().any(|k| k == keyword)
+    }
+
+    pub fn is_key_values_keyword(&self, keyword: &str) -> bool {
+        self.key_values_keywords.iter().any(|k| k == keyword)
+    }
+}
+
+const PROPERTIES_COMMANDS: [&str; 4] = [
+    "set_target_properties",
+    "set_source_files_properties",
+    "set_directory_properties",
+    "set_tests_properties",
+];
+
+const KEYWORD_COMMANDS: [&str; 8] = [
+    "install",
+    "export",
+    "add_custom_command",
+    "add_custom_target",
+    "fetchcontent_declare",
+    "externalproject_add",
+    "configure_package_config_file",
+    "write_basic_package_version_file",
+];
+
+/// Returns the built-in layout knowledge for a command. Command names are
+/// matched case-insensitively, like CMake does.
+pub fn builtin_command_spec(name: &str) -> CommandSpec {
+    let name = name.to_lowercase();
+    if PROPERTIES_COMMANDS.contains(&name.as_str()) {
+        CommandSpec {
+            pair_keywords: vec!["PROPERTIES".to_string()],
+            ..Default::default()
+        }
+    } else if name == "set_property" {
+        CommandSpec {
+            key_values_keywords: vec!["PROPERTY".to_string()],
+            ..Default::default()
+        }
+    } else if KEYWORD_COMMANDS.contains(&name.as_str()) {
+        CommandSpec {
+            align_keywords: true,
+            ..Default::default()
+        }
+    } else {
+        CommandSpec::default()
+    }
+}
@@ -20,271 +20,743 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-use pretty::RcDoc;
+use std::fmt::{Display, Write};
+
+use pretty::{RcAllocator, RcDoc, SmallText};
+
+use crate::pretty_printer::commands::CommandSpec;
+use crate::pretty_printer::style::{ListLayout, Style};
 
 use crate::parser::types::{
-    CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand, CMakeCommandGroup, CMakeCondition,
-    CMakeDocument, CMakeForEachStatement, CMakeFunctionStatement, CMakeIfStatement,
-    CMakeMacroStatement, CMakeStatement, CMakeValue,
+    ArgumentPart, CMakeBlockStatement, CMakeBracketLiteral, CMakeCommand, CMakeCommandGroup,
+    CMakeCondition, CMakeDocument, CMakeForEachStatement, CMakeFunctionStatement, CMakeIfStatement,
+    CMakeMacroStatement, CMakeStatement, CMakeValue, CMakeWhileStatement, VariableKind,
 };
 
-impl CMakeBracketLiteral {
-    fn print(&self) -> RcDoc<'static, ()> {
-        if self.is_comment {
-            RcDoc::text(format!(
-                "#[{}[{}]{}]",
-                self.delimiter, self.contents, self.delimiter
-            ))
-        } else {
-            RcDoc::text(format!(
-                "[{}[{}]{}]",
-                self.delimiter, self.contents, self.delimiter
-            ))
+/// `token` in a small text, which is stored inline, if it fits in one, or
+/// else the document `pieces` builds from text borrowed from the syntax tree.
+fn short_or<'a>(token: impl Display, pieces: impl FnOnce() -> RcDoc<'a, ()>) -> RcDoc<'a, ()> {
+    let mut text = SmallText::new();
+    match write!(text, "{}", token) {
+        Ok(()) => RcDoc::as_string(text),
+        Err(_) => pieces(),
+    }
+}
+
+/// `parts` as they appear in the source, borrowing the text from it.
+fn print_parts<'a>(parts: &'a [ArgumentPart<'a>]) -> RcDoc<'a, ()> {
+    match parts {
+        [part] => print_part(part),
+        parts => RcDoc::concat(parts.iter().map(print_part)),
+    }
+}
+
+fn print_part<'a>(part: &'a ArgumentPart<'a>) -> RcDoc<'a, ()> {
+    match part {
+        ArgumentPart::Text(text) => RcDoc::text(&**text),
+        // A backslash and a character or line ending fit in a small text
+        ArgumentPart::Escape(_) | ArgumentPart::LineContinuation(_) => RcDoc::as_string(part),
+        ArgumentPart::Variable(variable) => {
+            let (open, close) = match variable.kind {
+                VariableKind::Normal => ("${", "}"),
+                VariableKind::Env => ("$ENV{", "}"),
+                VariableKind::Cache => ("$CACHE{", "}"),
+                VariableKind::At => ("@", "@"),
+            };
+            RcDoc::text(open)
+                .append(print_parts(&variable.name))
+                .append(RcDoc::text(close))
+        }
+        ArgumentPart::GeneratorExpression(expression) => {
+            let parameters = expression
+                .parameters
+                .iter()
+                .enumerate()
+                .map(|(i, parameter)| {
+                    RcDoc::text(if i == 0 { ":" } else { "," }).append(print_parts(parameter))
+                });
+            RcDoc::text("$<")
+                .append(print_parts(&expression.name))
+                .append(RcDoc::concat(parameters))
+                .append(if expression.terminated {
+                    RcDoc::text(">")
+                } else {
+                    RcDoc::nil()
+                })
         }
     }
 }
 
-impl CMakeValue {
-    fn to_doc(&self) -> RcDoc<'static, ()> {
+impl CMakeBracketLiteral<'_> {
+    fn print(&self) -> RcDoc<'_, ()> {
+        let open = if self.is_comment { "#[" } else { "[" };
+        short_or(
+            format_args!(
+                "{}{}[{}]{}]",
+                open, self.delimiter, self.contents, self.delimiter
+            ),
+            || {
+                RcDoc::text(open)
+                    .append(RcDoc::text(&*self.delimiter))
+                    .append(RcDoc::text("["))
+                    .append(RcDoc::text(&*self.contents))
+                    .append(RcDoc::text("]"))
+                    .append(RcDoc::text(&*self.delimiter))
+                    .append(RcDoc::text("]"))
+            },
+        )
+    }
+}
+
+/// A line comment, `#` followed by `comment`.
+fn print_comment(comment: &str) -> RcDoc<'_, ()> {
+    short_or(format_args!("#{}", comment), || {
+        RcDoc::text("#").append(RcDoc::text(comment))
+    })
+}
+
+impl CMakeValue<'_> {
+    fn to_doc(&self) -> RcDoc<'_, ()> {
         match self {
-            CMakeValue::QuotedString(str) => RcDoc::text(format!("\"{}\"", str)),
-            CMakeValue::StringLiteral(str) => RcDoc::text(str.to_string()),
-            CMakeValue::Comment(str) => RcDoc::text(format!("#{}", str))
-                .flat_alt(RcDoc::text(format!("#{}", str)).append(RcDoc::hardline())),
-            CMakeValue::ArgumentSpecifier(arg) => RcDoc::text(arg.to_string()),
-            CMakeValue::Parenthesis(char) => RcDoc::text(char.to_string()),
-            CMakeValue::BracketComment(comment) => comment.print(),
-            CMakeValue::BracketQuotedString(s) => s.print(),
+            CMakeValue::QuotedString(str, _) => short_or(format_args!("\"{}\"", str), || {
+                RcDoc::text("\"")
+                    .append(print_parts(&str.parts))
+                    .append(RcDoc::text("\""))
+            }),
+            CMakeValue::StringLiteral(str, _) => match str.parts.as_slice() {
+                [ArgumentPart::Text(text)] => RcDoc::text(&**text),
+                parts => short_or(str, || print_parts(parts)),
+            },
+            CMakeValue::Comment(str, _) => {
+                let comment = print_comment(str);
+                comment.clone().flat_alt(comment.append(RcDoc::hardline()))
+            }
+            CMakeValue::ArgumentSpecifier(arg, _) => RcDoc::text(&**arg),
+            CMakeValue::Parenthesis(char, _) => RcDoc::text(&**char),
+            CMakeValue::BracketComment(comment, _) => comment.print(),
+            CMakeValue::BracketQuotedString(s, _) => s.print(),
         }
     }
 }
 
-impl CMakeCommand {
-    fn print(&self) -> RcDoc<'static, ()> {
-        let args = print_args(&self.args, false);
+impl CMakeCommand<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_, ()> {
+        let name = RcDoc::text(style.command_case.apply(&self.name));
+        let has_comments = self
+            .args
+            .iter()
+            .any(|arg| matches!(arg, CMakeValue::Comment(_, _)));
+        if style.never_break_for(&self.name) && !has_comments {
+            return name
+                .append("(")
+                .append(RcDoc::intersperse(
+                    self.args.iter().map(|arg| arg.to_doc()),
+                    RcDoc::space(),
+                ))
+                .append(")");
+        }
 
-        RcDoc::text(self.name.to_string())
-            .append("(")
-            .append(args)
-            .append(")")
-            .group()
+        let spec = style.command_spec_for(&self.name);
+        let layout = style.list_layout_for(&self.name);
+        let grouping_disabled = !style.keyword_grouping_for(&self.name);
+        let args = print_args(&self.args, grouping_disabled, &spec, layout, style);
+
+        name.append("(").append(args).append(")").group()
     }
 }
 
-fn print_args(args: &[CMakeValue], grouping_disabled: bool) -> RcDoc<'static> {
-    let args = print_args_to_vec(args, grouping_disabled);
+fn print_args<'a>(
+    args: &'a [CMakeValue<'a>],
+    grouping_disabled: bool,
+    spec: &CommandSpec,
+    layout: ListLayout,
+    style: &Style,
+) -> RcDoc<'a> {
+    // A trailing comment has to end its line, even if parens don't dangle
+    let ends_with_comment = matches!(args.last(), Some(CMakeValue::Comment(_, _)));
+    let args = print_args_to_vec(args, grouping_disabled, spec, layout, style);
     RcDoc::line_()
         .append(RcDoc::intersperse(args, RcDoc::line()))
-        .append(RcDoc::line_())
-        .nest(2)
+        .append(if style.dangle_parens || ends_with_comment {
+            RcDoc::line_()
+        } else {
+            RcDoc::nil()
+        })
+        .nest(style.indent as isize)
         .group()
 }
 
-fn print_args_to_vec(args: &[CMakeValue], grouping_disabled: bool) -> Vec<RcDoc<'static>> {
-    if args.is_empty() {
-        vec![]
-    } else {
-        let mut groups = vec![vec![&args[0]]];
-        for arg in args.iter().skip(1) {
-            if grouping_disabled {
-                groups.last_mut().unwrap().push(arg);
-            } else {
-                if let CMakeValue::ArgumentSpecifier(_) = arg {
-                    groups.push(vec![arg]);
-                } else if let CMakeValue::Comment(_) = arg {
-                    groups.push(vec![arg]);
-                } else {
-                    groups.last_mut().unwrap().push(arg);
-                }
+/// A run of arguments that is laid out together.
+enum ArgGroup<'a> {
+    /// A keyword, or the leading positional arguments, followed by its values.
+    Values(Vec<&'a CMakeValue<'a>>),
+    /// A keyword followed by entries, each made of a key and its values.
+    Entries {
+        keyword: &'a CMakeValue<'a>,
+        entries: Vec<Vec<&'a CMakeValue<'a>>>,
+    },
+}
+
+fn split_arg_groups<'a>(
+    args: &'a [CMakeValue<'a>],
+    grouping_disabled: bool,
+    spec: &CommandSpec,
+) -> Vec<ArgGroup<'a>> {
+    let mut groups: Vec<ArgGroup> = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let starts_group = !grouping_disabled
+            && matches!(
+                arg,
+                CMakeValue::ArgumentSpecifier(_, _) | CMakeValue::Comment(_, _)
+            );
+        match (arg, groups.last_mut()) {
+            (CMakeValue::ArgumentSpecifier(keyword, _), _)
+                if !grouping_disabled && spec.is_pair_keyword(keyword) =>
+            {
+                groups.push(ArgGroup::Entries {
+                    keyword: arg,
+                    entries: split_entries(args.by_ref(), Some(2)),
+                });
             }
+            (CMakeValue::ArgumentSpecifier(keyword, _), _)
+                if !grouping_disabled && spec.is_key_values_keyword(keyword) =>
+            {
+                groups.push(ArgGroup::Entries {
+                    keyword: arg,
+                    entries: split_entries(args.by_ref(), None),
+                });
+            }
+            (_, Some(ArgGroup::Values(values))) if !starts_group => values.push(arg),
+            _ => groups.push(ArgGroup::Values(vec![arg])),
+        }
+    }
+    groups
+}
+
+/// Splits the arguments into entries of `entry_size` values. Comments don't
+/// count towards the size of an entry.
+fn split_entries<'a>(
+    args: impl Iterator<Item = &'a CMakeValue<'a>>,
+    entry_size: Option<usize>,
+) -> Vec<Vec<&'a CMakeValue<'a>>> {
+    let is_comment = |value: &CMakeValue| matches!(value, CMakeValue::Comment(_, _));
+    let mut entries: Vec<Vec<&CMakeValue>> = vec![];
+    for arg in args {
+        let is_complete = |entry: &Vec<&CMakeValue>| {
+            let size = entry.iter().filter(|value| !is_comment(value)).count();
+            size == 0 || entry_size.is_some_and(|entry_size| size >= entry_size)
+        };
+        match entries.last_mut() {
+            Some(entry) if !is_complete(entry) => entry.push(arg),
+            _ => entries.push(vec![arg]),
         }
+    }
+    entries
+}
 
-        groups
-            .iter()
-            .map(|values| {
-                let result = RcDoc::intersperse(
-                    values.iter().map(|value| value.to_doc()),
-                    if grouping_disabled {
-                        RcDoc::softline()
-                    } else {
-                        RcDoc::line()
-                    },
-                );
-                if grouping_disabled {
-                    result
-                } else {
-                    result.group()
-                }
-            })
-            .collect::<Vec<RcDoc>>()
+/// Width of a value when printed on a single line.
+fn value_width(value: &CMakeValue<'_>) -> usize {
+    value
+        .to_doc()
+        .pretty(usize::MAX)
+        .to_string()
+        .chars()
+        .count()
+}
+
+/// Returns the key of a group of values if it should be aligned with others.
+fn alignment_key<'a>(values: &[&'a CMakeValue<'a>]) -> Option<&'a CMakeValue<'a>> {
+    match values {
+        [key @ (CMakeValue::ArgumentSpecifier(_, _)
+        | CMakeValue::StringLiteral(_, _)
+        | CMakeValue::QuotedString(_, _)), _, ..] => Some(*key),
+        _ => None,
     }
 }
 
-impl CMakeCondition {
-    fn print(&self) -> RcDoc<'static> {
+/// Values wider than this are never filled by [ListLayout::Auto].
+const AUTO_FILL_MAX_WIDTH: usize = 24;
+
+/// Joins values with the separator `layout` calls for. Groups with comments
+/// are always printed one value per line, as a comment has to end its line.
+/// When filling a group that starts with a keyword, the values hang after it.
+fn print_values<'a>(values: &[&'a CMakeValue<'a>], layout: ListLayout) -> RcDoc<'a> {
+    let has_comments = values
+        .iter()
+        .any(|value| matches!(value, CMakeValue::Comment(_, _)));
+    let fill = !has_comments
+        && match layout {
+            ListLayout::OnePerLine => false,
+            ListLayout::Fill => true,
+            ListLayout::Auto => values
+                .iter()
+                .all(|value| value_width(value) <= AUTO_FILL_MAX_WIDTH),
+        };
+    if !fill {
+        return RcDoc::intersperse(values.iter().map(|value| value.to_doc()), RcDoc::line())
+            .group();
+    }
+    let fill_values = |values: &[&'a CMakeValue<'a>]| {
+        RcDoc::intersperse(values.iter().map(|value| value.to_doc()), RcDoc::softline())
+    };
+    match values {
+        [keyword @ CMakeValue::ArgumentSpecifier(_, _), rest @ ..] if !rest.is_empty() => keyword
+            .to_doc()
+            .append(RcDoc::space())
+            .append(align(fill_values(rest)))
+            .group(),
+        _ => fill_values(values).group(),
+    }
+}
+
+/// Prints `key` followed by its values, which start at `column` when the
+/// enclosing group breaks, and are separated by a single space otherwise.
+fn print_aligned<'a>(
+    key: &'a CMakeValue<'a>,
+    values: &[&'a CMakeValue<'a>],
+    column: usize,
+    layout: ListLayout,
+) -> RcDoc<'a> {
+    let padding = column.saturating_sub(value_width(key)).max(1);
+    key.to_doc()
+        .append(RcDoc::text(" ".repeat(padding)).flat_alt(RcDoc::space()))
+        .append(align(print_values(values, layout)))
+}
+
+/// Sets the indentation of `doc` to the column it starts at.
+fn align(doc: RcDoc<'_>) -> RcDoc<'_> {
+    pretty::Pretty::pretty(doc, &RcAllocator).align().into_doc()
+}
+
+fn print_args_to_vec<'a>(
+    args: &'a [CMakeValue<'a>],
+    grouping_disabled: bool,
+    spec: &CommandSpec,
+    layout: ListLayout,
+    style: &Style,
+) -> Vec<RcDoc<'a>> {
+    let groups = split_arg_groups(args, grouping_disabled, spec);
+    let keyword_column = groups
+        .iter()
+        .filter_map(|group| match group {
+            ArgGroup::Values(values) if spec.align_keywords => match values.as_slice() {
+                [CMakeValue::ArgumentSpecifier(keyword, _), _, ..] => Some(keyword.len() + 1),
+                _ => None,
+            },
+            _ => None,
+        })
+        .max();
+
+    groups
+        .iter()
+        .map(|group| match group {
+            ArgGroup::Values(values) => match (values.as_slice(), keyword_column) {
+                ([keyword @ CMakeValue::ArgumentSpecifier(_, _), rest @ ..], Some(column))
+                    if !rest.is_empty() =>
+                {
+                    print_aligned(keyword, rest, column, layout)
+                }
+                _ if grouping_disabled => {
+                    RcDoc::intersperse(values.iter().map(|value| value.to_doc()), RcDoc::softline())
+                }
+                _ => print_values(values, layout),
+            },
+            ArgGroup::Entries { keyword, entries } if entries.is_empty() => keyword.to_doc(),
+            ArgGroup::Entries { keyword, entries } => {
+                let key_column = entries
+                    .iter()
+                    .filter_map(|entry| alignment_key(entry))
+                    .map(|key| value_width(key) + 1)
+                    .max()
+                    .unwrap_or(0);
+                let entries = entries.iter().map(|entry| match alignment_key(entry) {
+                    Some(key) => print_aligned(key, &entry[1..], key_column, layout),
+                    None => {
+                        RcDoc::intersperse(entry.iter().map(|value| value.to_doc()), RcDoc::line())
+                            .group()
+                    }
+                });
+                keyword.to_doc().append(
+                    RcDoc::line()
+                        .append(RcDoc::intersperse(entries, RcDoc::line()))
+                        .nest(style.indent as isize),
+                )
+            }
+        })
+        .collect::<Vec<RcDoc>>()
+}
+
+impl CMakeCondition<'_> {
+    fn print(&self) -> RcDoc<'_> {
         match self {
-            CMakeCondition::Parentheses { value } => RcDoc::text("(")
+            CMakeCondition::Parentheses { value, .. } => RcDoc::text("(")
                 .append(value.print())
                 .append(RcDoc::text(")")),
-            CMakeCondition::UnaryTest { value, operator } => RcDoc::text(operator.to_string())
+            CMakeCondition::UnaryTest {
+                value, operator, ..
+            } => RcDoc::text(&**operator)
                 .append(RcDoc::space())
                 .append(value.print()),
             CMakeCondition::BinaryTest {
                 operator,
                 left,
                 right,
+                ..
             } => left
                 .print()
-                .append(RcDoc::space())
-                .append(RcDoc::text(operator.to_string()))
+                .append(left.separator(RcDoc::space()))
+                .append(RcDoc::text(&**operator))
                 .append(RcDoc::space())
                 .append(right.print()),
-            CMakeCondition::UnaryLogicalOperator { value, operator } => {
-                RcDoc::text(operator.to_string())
-                    .append(RcDoc::space())
-                    .append(value.print())
-            }
-            CMakeCondition::BinaryLogicalOperator {
-                operator,
-                left,
-                right,
-            } => left
-                .print()
+            CMakeCondition::UnaryLogicalOperator {
+                value, operator, ..
+            } => RcDoc::text(&**operator)
                 .append(RcDoc::space())
-                .append(RcDoc::text(operator.to_string()))
-                .append(RcDoc::space())
-                .append(right.print()),
+                .append(value.print()),
+            CMakeCondition::BinaryLogicalOperator { .. } => {
+                let mut operands = Vec::new();
+                let first = self.logical_operands(&mut operands);
+                let mut previous = first;
+                let rest = operands.into_iter().map(|(operator, operand)| {
+                    let separator = previous.separator(RcDoc::line());
+                    previous = operand;
+                    separator
+                        .append(RcDoc::text(operator))
+                        .append(RcDoc::space())
+                        .append(operand.print())
+                });
+                align(first.print().append(RcDoc::concat(rest))).group()
+            }
             CMakeCondition::Value(value) => value.to_doc(),
-            CMakeCondition::Comment { content, tail } => RcDoc::text(format!("#{}", content))
-                .append(RcDoc::line())
+            CMakeCondition::Comment { content, tail, .. } => print_comment(content)
+                .append(RcDoc::hardline())
                 .append(tail.as_ref().map(|t| t.print()).unwrap_or(RcDoc::text(""))),
+            CMakeCondition::BracketComment { comment, tail, .. } => match tail {
+                Some(tail) => comment
+                    .print()
+                    .append(RcDoc::line())
+                    .append(tail.print())
+                    .group(),
+                None => comment.print(),
+            },
+            CMakeCondition::TrailingComment { value, comment, .. } => {
+                // Keep comments that were written on their own line there
+                let separator = if comment.span().start.line > value.span().end.line {
+                    RcDoc::hardline()
+                } else {
+                    RcDoc::space()
+                };
+                value.print().append(separator).append(comment.print())
+            }
+            CMakeCondition::Variable(variable) => {
+                let name = print_parts(&variable.name);
+                match variable.kind {
+                    VariableKind::Env => RcDoc::text("ENV{").append(name).append(RcDoc::text("}")),
+                    VariableKind::Cache => {
+                        RcDoc::text("CACHE{").append(name).append(RcDoc::text("}"))
+                    }
+                    VariableKind::Normal | VariableKind::At => name,
+                }
+            }
         }
     }
+
+    /// `separator`, unless this condition ends with a line comment, which
+    /// already ends the line.
+    fn separator<'a>(&self, separator: RcDoc<'a>) -> RcDoc<'a> {
+        if self.ends_with_line_comment() {
+            RcDoc::nil()
+        } else {
+            separator
+        }
+    }
+
+    fn ends_with_line_comment(&self) -> bool {
+        match self {
+            CMakeCondition::Comment { tail: None, .. } => true,
+            CMakeCondition::Comment {
+                tail: Some(last), ..
+            }
+            | CMakeCondition::BracketComment {
+                tail: Some(last), ..
+            }
+            | CMakeCondition::UnaryTest { value: last, .. }
+            | CMakeCondition::UnaryLogicalOperator { value: last, .. }
+            | CMakeCondition::BinaryTest { right: last, .. }
+            | CMakeCondition::BinaryLogicalOperator { right: last, .. }
+            | CMakeCondition::TrailingComment { comment: last, .. } => {
+                last.ends_with_line_comment()
+            }
+            CMakeCondition::BracketComment { tail: None, .. }
+            | CMakeCondition::Parentheses { .. }
+            | CMakeCondition::Variable(_)
+            | CMakeCondition::Value(_) => false,
+        }
+    }
+
+    /// Splits a chain of the same logical operator, such as `A OR B OR C`,
+    /// into its first operand and the operators and operands that follow it.
+    fn logical_operands<'a>(&'a self, operands: &mut Vec<(&'a str, &'a Self)>) -> &'a Self {
+        let CMakeCondition::BinaryLogicalOperator {
+            operator,
+            left,
+            right,
+            ..
+        } = self
+        else {
+            return self;
+        };
+        let first = match &**left {
+            CMakeCondition::BinaryLogicalOperator {
+                operator: left_operator,
+                ..
+            } if left_operator.eq_ignore_ascii_case(operator) => left.logical_operands(operands),
+            _ => left,
+        };
+        operands.push((&**operator, right));
+        first
+    }
 }
 
-impl CMakeIfStatement {
-    fn print(&self) -> RcDoc<'static> {
-        let make_body = |body: &Vec<CMakeStatement>| {
-            RcDoc::intersperse(body.iter().map(|statement| statement.print()), RcDoc::nil())
-                .nest(2)
+impl CMakeIfStatement<'_> {
+    fn print<'a>(&'a self, style: &Style) -> RcDoc<'a> {
+        let make_body = |body: &'a [CMakeStatement<'a>]| {
+            print_statements(body, style)
+                .nest(style.indent as isize)
                 .group()
         };
-        let mut output = RcDoc::text("if(")
-            .append(self.base.condition.print())
+        let keyword = |keyword: &'static str| RcDoc::text(style.command_case.apply(keyword));
+        let mut output = keyword("if")
+            .append("(")
+            .append(align(self.base.condition.print()))
             .append(RcDoc::text(")"))
             .append(make_body(&self.base.body));
 
         for else_if in &self.else_ifs {
             output = output
-                .append("elseif(")
-                .append(else_if.condition.print())
+                .append(keyword("elseif"))
+                .append("(")
+                .append(align(else_if.condition.print()))
                 .append(RcDoc::text(")"))
                 .append(make_body(&else_if.body));
         }
 
         if let Some(else_body) = &self.else_body {
             output = output
-                .append("else(")
+                .append(keyword("else"))
+                .append("(")
                 .append(RcDoc::text(")"))
-                .append(make_body(&else_body));
+                .append(make_body(else_body));
         }
 
-        output = output.append(RcDoc::text("endif()"));
+        output = output.append(keyword("endif")).append("()");
         output
     }
 }
 
-impl CMakeCommandGroup {
-    fn print(&self, name: &str) -> RcDoc<'static> {
-        print_clause_body(name, &self.clause, &self.body)
+impl CMakeWhileStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_> {
+        let keyword = |keyword: &'static str| RcDoc::text(style.command_case.apply(keyword));
+        keyword("while")
+            .append("(")
+            .append(align(self.condition.print()))
+            .append(RcDoc::text(")"))
+            .append(
+                print_statements(&self.body, style)
+                    .nest(style.indent as isize)
+                    .group(),
+            )
+            .append(keyword("endwhile"))
+            .append("()")
+    }
+}
+
+impl CMakeCommandGroup<'_> {
+    fn print(&self, name: &str, style: &Style) -> RcDoc<'_> {
+        print_clause_body(name, &self.clause, &self.body, style)
     }
 }
 
-fn print_clause_body(
+/// The number of leading arguments of a block that stay on its opening line,
+/// such as the name of a function or the loop variable of a `foreach`.
+fn clause_head_len(keyword: &str, clause: &[CMakeValue<'_>]) -> usize {
+    let is_keyword = |value: Option<&CMakeValue>, keywords: &[&str]| match value {
+        Some(CMakeValue::ArgumentSpecifier(value, _)) => keywords.contains(&&**value),
+        _ => false,
+    };
+    match (keyword, clause.first()) {
+        (_, None | Some(CMakeValue::Comment(_, _))) => 0,
+        ("function" | "macro", _) => 1,
+        ("foreach", _) if is_keyword(clause.get(1), &["IN"]) => {
+            if is_keyword(clause.get(2), &["LISTS", "ITEMS", "ZIP_LISTS"]) {
+                3
+            } else {
+                2
+            }
+        }
+        ("foreach", _) => 1,
+        _ => 0,
+    }
+}
+
+/// Prints the arguments of a block's opening command. The head of the clause
+/// stays next to the parenthesis and the rest hangs below it.
+fn print_clause_args<'a>(keyword: &str, clause: &'a [CMakeValue<'a>], style: &Style) -> RcDoc<'a> {
+    let grouping_disabled = !style.keyword_grouping_for(keyword);
+    let layout = style.list_layout_for(keyword);
+    let (head, rest) = clause.split_at(clause_head_len(keyword, clause));
+    if head.is_empty() {
+        return print_args(
+            clause,
+            grouping_disabled,
+            &CommandSpec::default(),
+            layout,
+            style,
+        );
+    }
+
+    let head = RcDoc::intersperse(head.iter().map(|value| value.to_doc()), RcDoc::space());
+    if rest.is_empty() {
+        return head;
+    }
+    let ends_with_comment = matches!(rest.last(), Some(CMakeValue::Comment(_, _)));
+    let rest = print_args_to_vec(
+        rest,
+        grouping_disabled,
+        &CommandSpec::default(),
+        layout,
+        style,
+    );
+    head.append(
+        RcDoc::line()
+            .append(RcDoc::intersperse(rest, RcDoc::line()))
+            .append(if style.dangle_parens || ends_with_comment {
+                RcDoc::line_()
+            } else {
+                RcDoc::nil()
+            })
+            .nest(style.indent as isize),
+    )
+    .group()
+}
+
+fn print_clause_body<'a>(
     keyword: &str,
-    clause: &[CMakeValue],
-    body: &[CMakeStatement],
-) -> RcDoc<'static> {
+    clause: &'a [CMakeValue<'a>],
+    body: &'a [CMakeStatement<'a>],
+    style: &Style,
+) -> RcDoc<'a> {
+    let end_keyword = format!("end{}", keyword);
     RcDoc::intersperse(
         [
-            RcDoc::text(format!("{}(", keyword))
-                .append(print_args(clause, false))
+            RcDoc::text(style.command_case.apply(keyword).into_owned())
+                .append("(")
+                .append(print_clause_args(keyword, clause, style))
                 .append(RcDoc::text(")"))
                 .group(),
-            RcDoc::intersperse(body.iter().map(|statement| statement.print()), RcDoc::nil())
-                .nest(2)
+            print_statements(body, style)
+                .nest(style.indent as isize)
+                .group(),
+            RcDoc::text(style.command_case.apply(&end_keyword).into_owned())
+                .append("()")
                 .group(),
-            RcDoc::text(format!("end{}()", keyword)).group(),
         ],
         RcDoc::nil(),
     )
 }
 
-impl CMakeForEachStatement {
-    fn print(&self) -> RcDoc<'static> {
-        self.group.print("foreach")
+impl CMakeForEachStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_> {
+        self.group.print("foreach", style)
     }
 }
 
-impl CMakeFunctionStatement {
-    fn print(&self) -> RcDoc<'static> {
-        self.group.print("function")
+impl CMakeFunctionStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_> {
+        self.group.print("function", style)
     }
 }
 
-impl CMakeMacroStatement {
-    fn print(&self) -> RcDoc<'static> {
-        self.group.print("macro")
+impl CMakeMacroStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_> {
+        self.group.print("macro", style)
     }
 }
 
-impl CMakeBlockStatement {
-    fn print(&self) -> RcDoc<'static> {
-        self.group.print("block")
+impl CMakeBlockStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_> {
+        self.group.print("block", style)
     }
 }
 
-impl CMakeStatement {
-    fn print(&self) -> RcDoc<'static, ()> {
+impl CMakeStatement<'_> {
+    fn print(&self, style: &Style) -> RcDoc<'_, ()> {
         match self {
-            CMakeStatement::Command(command) => command.print(),
-            CMakeStatement::Comment(comment) => RcDoc::text(format!("#{}", comment)),
-            CMakeStatement::Newline => RcDoc::hardline(),
-            CMakeStatement::If(if_statement) => if_statement.print(),
-            CMakeStatement::For(for_statement) => for_statement.print(),
-            CMakeStatement::Function(fn_statement) => fn_statement.print(),
-            CMakeStatement::Macro(m_statement) => m_statement.print(),
-            CMakeStatement::Block(s) => s.print(),
-            CMakeStatement::BracketComment(s) => s.print(),
+            CMakeStatement::Command(command) => command.print(style),
+            CMakeStatement::Comment(comment, _) => RcDoc::text("#").append(RcDoc::text(&**comment)),
+            CMakeStatement::Newline(_) => RcDoc::hardline(),
+            CMakeStatement::Error(error) => RcDoc::text(&*error.text),
+            CMakeStatement::If(if_statement) => if_statement.print(style),
+            CMakeStatement::For(for_statement) => for_statement.print(style),
+            CMakeStatement::While(while_statement) => while_statement.print(style),
+            CMakeStatement::Function(fn_statement) => fn_statement.print(style),
+            CMakeStatement::Macro(m_statement) => m_statement.print(style),
+            CMakeStatement::Block(s) => s.print(style),
+            CMakeStatement::BracketComment(s, _) => s.print(),
         }
         .group()
     }
 }
 
-impl CMakeDocument {
-    pub fn print(&self) -> RcDoc<'static, ()> {
-        RcDoc::intersperse(
-            {
-                let mut result = vec![];
-                let mut newline_count = 0;
-                for statement in self.statements.iter() {
-                    if let CMakeStatement::Newline = statement {
-                        if newline_count > 2 {
-                            continue;
-                        }
-                        newline_count += 1;
-                    } else {
-                        newline_count = 0;
-                    }
+impl CMakeDocument<'_> {
+    pub fn print(&self) -> RcDoc<'_, ()> {
+        self.print_with_style(&Style::default())
+    }
 
-                    result.push(statement.print().group())
-                }
-                result
-            },
-            RcDoc::nil(),
-        )
+    pub fn print_with_style(&self, style: &Style) -> RcDoc<'_, ()> {
+        print_statements(&self.statements, style)
     }
 }
 
+/// Prints a list of statements, dropping blank lines over the
+/// `max_blank_lines` limit.
+fn print_statements<'a>(statements: &'a [CMakeStatement<'a>], style: &Style) -> RcDoc<'a, ()> {
+    RcDoc::intersperse(
+        {
+            let mut result = vec![];
+            let mut newline_count = 0;
+            for statement in statements.iter() {
+                if let CMakeStatement::Newline(_) = statement {
+                    if newline_count > style.max_blank_lines {
+                        continue;
+                    }
+                    newline_count += 1;
+                } else {
+                    newline_count = 0;
+                }
+
+                result.push(statement.print(style).group())
+            }
+            result
+        },
+        RcDoc::nil(),
+    )
+}
+
+/// Prints top-level statements that make up a single line, without the
+/// blank line limit of [`print_statements`]. Top-level lines don't affect how
+/// each other are laid out, so they can be printed separately.
+pub(crate) fn print_line<'a>(statements: &'a [CMakeStatement<'a>], style: &Style) -> RcDoc<'a, ()> {
+    RcDoc::concat(
+        statements
+            .iter()
+            .map(|statement| statement.print(style).group()),
+    )
+}
+
+pub mod commands;
+pub mod style;
 #[cfg(test)]
 mod test;
+pub mod tree;
@@ -0,0 +1,269 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Settings that control the layout decisions of the pretty printer.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::pretty_printer::commands::{builtin_command_spec, CommandSpec};
+
+/// How the values of an argument group are laid out when they don't fit in a
+/// single line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListLayout {
+    /// Every value goes on its own line.
+    #[default]
+    OnePerLine,
+    /// Values fill each line up to the column limit.
+    Fill,
+    /// Fill when all values are short and there are no comments, otherwise
+    /// one value per line.
+    Auto,
+}
+
+impl FromStr for ListLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one_per_line" => Ok(ListLayout::OnePerLine),
+            "fill" => Ok(ListLayout::Fill),
+            "auto" => Ok(ListLayout::Auto),
+            _ => Err(format!(
+                "invalid layout `{}`, expected one of: one_per_line, fill, auto",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for ListLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListLayout::OnePerLine => write!(f, "one_per_line"),
+            ListLayout::Fill => write!(f, "fill"),
+            ListLayout::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// How command names are cased.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandCase {
+    #[default]
+    Unchanged,
+    Lower,
+    Upper,
+}
+
+impl CommandCase {
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            CommandCase::Unchanged => name.into(),
+            CommandCase::Lower => name.to_lowercase().into(),
+            CommandCase::Upper => name.to_uppercase().into(),
+        }
+    }
+}
+
+impl FromStr for CommandCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unchanged" => Ok(CommandCase::Unchanged),
+            "lower" => Ok(CommandCase::Lower),
+            "upper" => Ok(CommandCase::Upper),
+            _ => Err(format!(
+                "invalid command case `{}`, expected one of: unchanged, lower, upper",
+                s
+            )),
+        }
+    }
+}
+
+/// Named bundles of style settings, approximating the output of other CMake
+/// formatters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    #[default]
+    Default,
+    CmakeFormat,
+    GersemiLike,
+    Compact,
+}
+
+impl Preset {
+    pub fn style(&self) -> Style {
+        let default = Style {
+            indent: 2,
+            dangle_parens: true,
+            command_case: CommandCase::Unchanged,
+            keyword_grouping: true,
+            max_blank_lines: 2,
+            list_layout: ListLayout::OnePerLine,
+            commands: HashMap::new(),
+        };
+        match self {
+            Preset::Default => default,
+            Preset::CmakeFormat => Style {
+                dangle_parens: false,
+                command_case: CommandCase::Lower,
+                max_blank_lines: 1,
+                list_layout: ListLayout::Auto,
+                ..default
+            },
+            Preset::GersemiLike => Style {
+                indent: 4,
+                command_case: CommandCase::Lower,
+                max_blank_lines: 1,
+                ..default
+            },
+            Preset::Compact => Style {
+                dangle_parens: false,
+                max_blank_lines: 1,
+                list_layout: ListLayout::Fill,
+                ..default
+            },
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Preset::Default),
+            "cmake-format" => Ok(Preset::CmakeFormat),
+            "gersemi-like" => Ok(Preset::GersemiLike),
+            "compact" => Ok(Preset::Compact),
+            _ => Err(format!(
+                "invalid style `{}`, expected one of: default, cmake-format, gersemi-like, compact",
+                s
+            )),
+        }
+    }
+}
+
+/// Settings that only apply to a single command. Unset settings fall back to
+/// the global style and to the built-in knowledge about the command.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandStyle {
+    #[serde(rename = "layout")]
+    pub list_layout: Option<ListLayout>,
+    /// Always print the command in a single line, unless it has comments.
+    pub never_break: Option<bool>,
+    pub keyword_grouping: Option<bool>,
+    /// Align the values of keyword sections in a column.
+    pub align_keywords: Option<bool>,
+    /// Keywords followed by `key value` pairs, such as `PROPERTIES`.
+    pub pair_keywords: Option<Vec<String>>,
+}
+
+impl CommandStyle {
+    /// Returns the settings of `other` with unset ones taken from `self`.
+    pub fn merge(self, other: CommandStyle) -> CommandStyle {
+        CommandStyle {
+            list_layout: other.list_layout.or(self.list_layout),
+            never_break: other.never_break.or(self.never_break),
+            keyword_grouping: other.keyword_grouping.or(self.keyword_grouping),
+            align_keywords: other.align_keywords.or(self.align_keywords),
+            pair_keywords: other.pair_keywords.or(self.pair_keywords),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    /// Number of spaces used for each indentation level.
+    pub indent: usize,
+    /// Put the closing parenthesis of a broken command on its own line.
+    pub dangle_parens: bool,
+    pub command_case: CommandCase,
+    /// Group upper-case keywords with the arguments that follow them.
+    pub keyword_grouping: bool,
+    /// Consecutive blank lines above this are removed.
+    pub max_blank_lines: usize,
+    pub list_layout: ListLayout,
+    /// Per-command settings, keyed by lower-case command name.
+    pub commands: HashMap<String, CommandStyle>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Preset::Default.style()
+    }
+}
+
+impl Style {
+    pub fn command_style(&self, name: &str) -> Option<&CommandStyle> {
+        self.commands.get(&name.to_lowercase())
+    }
+
+    pub fn command_style_mut(&mut self, name: &str) -> &mut CommandStyle {
+        self.commands.entry(name.to_lowercase()).or_default()
+    }
+
+    /// The list layout for the arguments of the command called `name`.
+    pub fn list_layout_for(&self, name: &str) -> ListLayout {
+        self.command_style(name)
+            .and_then(|command| command.list_layout)
+            .unwrap_or(self.list_layout)
+    }
+
+    pub fn keyword_grouping_for(&self, name: &str) -> bool {
+        self.command_style(name)
+            .and_then(|command| command.keyword_grouping)
+            .unwrap_or(self.keyword_grouping)
+    }
+
+    pub fn never_break_for(&self, name: &str) -> bool {
+        self.command_style(name)
+            .and_then(|command| command.never_break)
+            .unwrap_or(false)
+    }
+
+    /// The built-in knowledge about the command called `name`, with the
+    /// overrides of this style applied.
+    pub fn command_spec_for(&self, name: &str) -> CommandSpec {
+        let mut spec = builtin_command_spec(name);
+        if let Some(command) = self.command_style(name) {
+            if let Some(align_keywords) = command.align_keywords {
+                spec.align_keywords = align_keywords;
+            }
+            if let Some(pair_keywords) = &command.pair_keywords {
+                spec.pair_keywords = pair_keywords.clone();
+            }
+        }
+        spec
+    }
+}
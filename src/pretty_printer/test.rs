@@ -1,15 +1,20 @@
 use super::*;
-use crate::parser::types::{CMakeIfBase, CMakeIfStatement};
+use crate::parser::types::{CMakeArgument, CMakeIfBase, CMakeIfStatement, Span};
+use crate::pretty_printer::style::Preset;
 
 #[test]
 fn pretty_print_command_without_args() {
     let mut vec_writer = Vec::new();
     {
         let command = CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![],
+            span: Span::default(),
         };
-        command.print().render(80, &mut vec_writer).unwrap();
+        command
+            .print(&Style::default())
+            .render(80, &mut vec_writer)
+            .unwrap();
     }
     let str = String::from_utf8(vec_writer).unwrap();
     assert_eq!(str, "foo()");
@@ -20,10 +25,17 @@ fn pretty_print_command_with_single_arg() {
     let mut vec_writer = Vec::new();
     {
         let command = CMakeCommand {
-            name: "cmake_version".to_string(),
-            args: vec![CMakeValue::StringLiteral(String::from("1.2.3"))],
+            name: "cmake_version".into(),
+            args: vec![CMakeValue::StringLiteral(
+                CMakeArgument::from("1.2.3"),
+                Span::default(),
+            )],
+            span: Span::default(),
         };
-        command.print().render(80, &mut vec_writer).unwrap();
+        command
+            .print(&Style::default())
+            .render(80, &mut vec_writer)
+            .unwrap();
     }
     let str = String::from_utf8(vec_writer).unwrap();
     assert_eq!(str, "cmake_version(1.2.3)");
@@ -34,16 +46,20 @@ fn pretty_print_command_with_multiple_args() {
     let mut vec_writer = Vec::new();
     {
         let command = CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral(String::from("a")),
-                CMakeValue::StringLiteral(String::from("b")),
-                CMakeValue::StringLiteral(String::from("c")),
-                CMakeValue::StringLiteral(String::from("d")),
-                CMakeValue::StringLiteral(String::from("e")),
+                CMakeValue::StringLiteral(CMakeArgument::from("a"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("b"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("c"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("d"), Span::default()),
+                CMakeValue::StringLiteral(CMakeArgument::from("e"), Span::default()),
             ],
+            span: Span::default(),
         };
-        command.print().render(80, &mut vec_writer).unwrap();
+        command
+            .print(&Style::default())
+            .render(80, &mut vec_writer)
+            .unwrap();
     }
     let str = String::from_utf8(vec_writer).unwrap();
     assert_eq!(str, "foo(a b c d e)");
@@ -54,26 +70,35 @@ fn pretty_print_command_with_long_line_args() {
     let mut vec_writer = Vec::new();
     {
         let command = CMakeCommand {
-            name: "foo".to_string(),
+            name: "foo".into(),
             args: vec![
-                CMakeValue::StringLiteral(String::from(
-                    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
-                )),
-                CMakeValue::StringLiteral(String::from(
-                    "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
-                )),
-                CMakeValue::StringLiteral(String::from(
-                    "cccccccccccccccccccccccccccccccccccccccccccccccccc",
-                )),
-                CMakeValue::StringLiteral(String::from(
-                    "dddddddddddddddddddddddddddddddddddddddddddddddddd",
-                )),
-                CMakeValue::StringLiteral(String::from(
-                    "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee",
-                )),
+                CMakeValue::StringLiteral(
+                    CMakeArgument::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                    Span::default(),
+                ),
+                CMakeValue::StringLiteral(
+                    CMakeArgument::from("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                    Span::default(),
+                ),
+                CMakeValue::StringLiteral(
+                    CMakeArgument::from("cccccccccccccccccccccccccccccccccccccccccccccccccc"),
+                    Span::default(),
+                ),
+                CMakeValue::StringLiteral(
+                    CMakeArgument::from("dddddddddddddddddddddddddddddddddddddddddddddddddd"),
+                    Span::default(),
+                ),
+                CMakeValue::StringLiteral(
+                    CMakeArgument::from("eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee"),
+                    Span::default(),
+                ),
             ],
+            span: Span::default(),
         };
-        command.print().render(80, &mut vec_writer).unwrap();
+        command
+            .print(&Style::default())
+            .render(80, &mut vec_writer)
+            .unwrap();
     }
     let str = String::from_utf8(vec_writer).unwrap();
     assert_eq!(str, "foo(\n  aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\n  bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\n  cccccccccccccccccccccccccccccccccccccccccccccccccc\n  dddddddddddddddddddddddddddddddddddddddddddddddddd\n  eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee\n)");
@@ -86,13 +111,15 @@ fn test_pretty_print_two_statements_has_no_blank_line() {
         let document = CMakeDocument {
             statements: vec![
                 CMakeStatement::Command(CMakeCommand {
-                    name: "foo".to_string(),
+                    name: "foo".into(),
                     args: vec![],
+                    span: Span::default(),
                 }),
-                CMakeStatement::Newline,
+                CMakeStatement::Newline(Span::default()),
                 CMakeStatement::Command(CMakeCommand {
-                    name: "bar".to_string(),
+                    name: "bar".into(),
                     args: vec![],
+                    span: Span::default(),
                 }),
             ],
         };
@@ -108,11 +135,12 @@ fn test_args_still_stay_in_one_line_if_there_is_space() {
     {
         let document = CMakeDocument {
             statements: vec![CMakeStatement::Command(CMakeCommand {
-                name: "foo".to_string(),
+                name: "foo".into(),
                 args: vec![
-                    CMakeValue::ArgumentSpecifier(String::from("LANGUAGE")),
-                    CMakeValue::ArgumentSpecifier(String::from("VERSION")),
+                    CMakeValue::ArgumentSpecifier("LANGUAGE".into(), Span::default()),
+                    CMakeValue::ArgumentSpecifier("VERSION".into(), Span::default()),
                 ],
+                span: Span::default(),
             })],
         };
         document.print().render(80, &mut vec_writer).unwrap();
@@ -125,13 +153,17 @@ fn test_args_still_stay_in_one_line_if_there_is_space() {
 fn test_sample() {
     let mut vec_writer = Vec::new();
     let input = CMakeStatement::Command(CMakeCommand {
-        name: String::from("set"),
+        name: "set".into(),
         args: vec![
-            CMakeValue::ArgumentSpecifier(String::from("CMAKE_CXX_STANDARD_REQUIRED")),
-            CMakeValue::ArgumentSpecifier(String::from("ON")),
+            CMakeValue::ArgumentSpecifier("CMAKE_CXX_STANDARD_REQUIRED".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("ON".into(), Span::default()),
         ],
+        span: Span::default(),
     });
-    input.print().render(80, &mut vec_writer).unwrap();
+    input
+        .print(&Style::default())
+        .render(80, &mut vec_writer)
+        .unwrap();
     let str = String::from_utf8(vec_writer).unwrap();
     assert_eq!(str, "set(CMAKE_CXX_STANDARD_REQUIRED ON)");
 }
@@ -142,17 +174,21 @@ fn test_args_are_grouped_with_upper_case_args() {
     {
         let document = CMakeDocument {
             statements: vec![CMakeStatement::Command(CMakeCommand {
-                name: "foo".to_string(),
+                name: "foo".into(),
                 args: vec![
-                    CMakeValue::ArgumentSpecifier(String::from("LANGUAGE")),
-                    CMakeValue::StringLiteral(String::from("cxx")),
-                    CMakeValue::ArgumentSpecifier(String::from("VERSION")),
-                    CMakeValue::StringLiteral(String::from("1234")),
-                    CMakeValue::ArgumentSpecifier(String::from("OTHER")),
-                    CMakeValue::StringLiteral(String::from("here")),
-                    CMakeValue::ArgumentSpecifier(String::from("THING")),
-                    CMakeValue::StringLiteral(String::from("0000000000000000000000000000000000")),
+                    CMakeValue::ArgumentSpecifier("LANGUAGE".into(), Span::default()),
+                    CMakeValue::StringLiteral(CMakeArgument::from("cxx"), Span::default()),
+                    CMakeValue::ArgumentSpecifier("VERSION".into(), Span::default()),
+                    CMakeValue::StringLiteral(CMakeArgument::from("1234"), Span::default()),
+                    CMakeValue::ArgumentSpecifier("OTHER".into(), Span::default()),
+                    CMakeValue::StringLiteral(CMakeArgument::from("here"), Span::default()),
+                    CMakeValue::ArgumentSpecifier("THING".into(), Span::default()),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("0000000000000000000000000000000000"),
+                        Span::default(),
+                    ),
                 ],
+                span: Span::default(),
             })],
         };
         document.print().render(80, &mut vec_writer).unwrap();
@@ -168,32 +204,37 @@ fn test_print_if_statement() {
         let document = CMakeDocument {
             statements: vec![CMakeStatement::If(CMakeIfStatement {
                 base: CMakeIfBase {
-                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                        "CMAKE_COMPILER_IS_GNUCXX",
-                    ))),
+                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                        "CMAKE_COMPILER_IS_GNUCXX".into(),
+                        Span::default(),
+                    )),
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::Command(CMakeCommand {
-                            name: String::from("foo"),
+                            name: "foo".into(),
                             args: vec![],
+                            span: Span::default(),
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
                 },
                 else_ifs: vec![CMakeIfBase {
-                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                        "MSVC",
-                    ))),
+                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                        "MSVC".into(),
+                        Span::default(),
+                    )),
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::Command(CMakeCommand {
-                            name: String::from("bar"),
+                            name: "bar".into(),
                             args: vec![],
+                            span: Span::default(),
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
                 }],
                 else_body: None,
+                span: Span::default(),
             })],
         };
         document.print().render(80, &mut vec_writer).unwrap();
@@ -219,40 +260,46 @@ fn test_print_nested_if_statement() {
         let document = CMakeDocument {
             statements: vec![CMakeStatement::If(CMakeIfStatement {
                 base: CMakeIfBase {
-                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(String::from(
-                        "a",
-                    ))),
+                    condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
+                        "a".into(),
+                        Span::default(),
+                    )),
                     body: vec![
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                         CMakeStatement::If(CMakeIfStatement {
                             base: CMakeIfBase {
                                 condition: CMakeCondition::Value(CMakeValue::ArgumentSpecifier(
-                                    String::from("b"),
+                                    "b".into(),
+                                    Span::default(),
                                 )),
                                 body: vec![
-                                    CMakeStatement::Newline,
+                                    CMakeStatement::Newline(Span::default()),
                                     CMakeStatement::Command(CMakeCommand {
-                                        name: String::from("foo"),
+                                        name: "foo".into(),
                                         args: vec![],
+                                        span: Span::default(),
                                     }),
-                                    CMakeStatement::Newline,
+                                    CMakeStatement::Newline(Span::default()),
                                 ],
                             },
                             else_ifs: vec![],
                             else_body: None,
+                            span: Span::default(),
                         }),
-                        CMakeStatement::Newline,
+                        CMakeStatement::Newline(Span::default()),
                     ],
                 },
                 else_ifs: vec![],
                 else_body: Some(vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::Command(CMakeCommand {
-                        name: String::from("bar"),
+                        name: "bar".into(),
                         args: vec![],
+                        span: Span::default(),
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ]),
+                span: Span::default(),
             })],
         };
         document.print().render(80, &mut vec_writer).unwrap();
@@ -278,21 +325,26 @@ fn test_preseving_newlines() {
     let document = CMakeDocument {
         statements: vec![CMakeStatement::Function(CMakeFunctionStatement {
             group: CMakeCommandGroup {
-                clause: vec![CMakeValue::StringLiteral(String::from("foo"))],
+                clause: vec![CMakeValue::StringLiteral(
+                    CMakeArgument::from("foo"),
+                    Span::default(),
+                )],
                 body: vec![
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                     CMakeStatement::Command(CMakeCommand {
-                        name: String::from("bar"),
+                        name: "bar".into(),
                         args: vec![
                             // TODO we don't want these newlines
-                            CMakeValue::StringLiteral(String::from("x")),
-                            CMakeValue::StringLiteral(String::from("y")),
-                            CMakeValue::StringLiteral(String::from("z")),
+                            CMakeValue::StringLiteral(CMakeArgument::from("x"), Span::default()),
+                            CMakeValue::StringLiteral(CMakeArgument::from("y"), Span::default()),
+                            CMakeValue::StringLiteral(CMakeArgument::from("z"), Span::default()),
                         ],
+                        span: Span::default(),
                     }),
-                    CMakeStatement::Newline,
+                    CMakeStatement::Newline(Span::default()),
                 ],
                 end_clause: vec![],
+                span: Span::default(),
             },
         })],
     };
@@ -311,3 +363,577 @@ endfunction()
         .trim()
     )
 }
+
+#[test]
+fn test_property_pairs_are_aligned_when_breaking() {
+    let command = CMakeCommand {
+        name: "set_target_properties".into(),
+        args: vec![
+            CMakeValue::StringLiteral(CMakeArgument::from("${PROJECT_NAME}"), Span::default()),
+            CMakeValue::ArgumentSpecifier("PROPERTIES".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("PREFIX".into(), Span::default()),
+            CMakeValue::QuotedString(CMakeArgument::from(""), Span::default()),
+            CMakeValue::ArgumentSpecifier("OUTPUT_NAME".into(), Span::default()),
+            CMakeValue::QuotedString(CMakeArgument::from("lib${PROJECT_NAME}"), Span::default()),
+            CMakeValue::ArgumentSpecifier("POSITION_INDEPENDENT_CODE".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("ON".into(), Span::default()),
+        ],
+        span: Span::default(),
+    };
+    let mut vec_writer = Vec::new();
+    command
+        .print(&Style::default())
+        .render(80, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+set_target_properties(
+  ${PROJECT_NAME}
+  PROPERTIES
+    PREFIX                    ""
+    OUTPUT_NAME               "lib${PROJECT_NAME}"
+    POSITION_INDEPENDENT_CODE ON
+)
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_property_pairs_stay_in_one_line_if_there_is_space() {
+    let command = CMakeCommand {
+        name: "set_target_properties".into(),
+        args: vec![
+            CMakeValue::StringLiteral(CMakeArgument::from("foo"), Span::default()),
+            CMakeValue::ArgumentSpecifier("PROPERTIES".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("CXX_STANDARD".into(), Span::default()),
+            CMakeValue::ArgumentSpecifier("17".into(), Span::default()),
+        ],
+        span: Span::default(),
+    };
+    let mut vec_writer = Vec::new();
+    command
+        .print(&Style::default())
+        .render(80, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(str, "set_target_properties(foo PROPERTIES CXX_STANDARD 17)");
+}
+
+#[test]
+fn test_keyword_without_entries_has_no_trailing_space() {
+    let source = "set_property(TARGET foo PROPERTY)";
+    assert_eq!(format_source(source, 80), source);
+    assert_eq!(
+        format_source(source, 20),
+        "set_property(\n  TARGET foo\n  PROPERTY\n)"
+    );
+    let source = "set_target_properties(foo PROPERTIES)";
+    assert_eq!(format_source(source, 80), source);
+}
+
+#[test]
+fn test_tokens_are_printed_as_written() {
+    // Short and long tokens, which are printed in different ways
+    for source in [
+        "message(\"${a}\" $<b:c> [[d]] #[[e]])",
+        "message(\"${CMAKE_CURRENT_BINARY_DIR}/a\\\"b\\;c\" $ENV{HOME}/é)",
+        "message($<$<CONFIG:Debug>:-DDEBUG_BUILD,-g> [==[a long ]] bracket]==])",
+        "# a comment that is longer than a short text\nmessage(a)\n# ç",
+        "if($ENV{CMAKE_BUILD_PARALLEL_LEVEL} AND \"a\\\nb\")\nendif()",
+    ] {
+        assert_eq!(format_source(source, 80), source);
+    }
+}
+
+#[test]
+fn test_keyword_values_are_aligned_for_install() {
+    let command = CMakeCommand {
+        name: "install".into(),
+        args: vec![
+            CMakeValue::ArgumentSpecifier("FILES".into(), Span::default()),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("${CMAKE_CURRENT_BINARY_DIR}/fooConfig.cmake"),
+                Span::default(),
+            ),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("${CMAKE_CURRENT_BINARY_DIR}/fooConfigVersion.cmake"),
+                Span::default(),
+            ),
+            CMakeValue::ArgumentSpecifier("DESTINATION".into(), Span::default()),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("${CMAKE_INSTALL_LIBDIR}/cmake/foo"),
+                Span::default(),
+            ),
+            CMakeValue::ArgumentSpecifier("COMPONENT".into(), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("dev"), Span::default()),
+        ],
+        span: Span::default(),
+    };
+    let mut vec_writer = Vec::new();
+    command
+        .print(&Style::default())
+        .render(80, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+install(
+  FILES       ${CMAKE_CURRENT_BINARY_DIR}/fooConfig.cmake
+              ${CMAKE_CURRENT_BINARY_DIR}/fooConfigVersion.cmake
+  DESTINATION ${CMAKE_INSTALL_LIBDIR}/cmake/foo
+  COMPONENT   dev
+)
+    "#
+        .trim()
+    );
+}
+
+fn compile_options_command() -> CMakeCommand<'static> {
+    CMakeCommand {
+        name: "target_compile_options".into(),
+        args: vec![
+            CMakeValue::StringLiteral(CMakeArgument::from("foo"), Span::default()),
+            CMakeValue::ArgumentSpecifier("PRIVATE".into(), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Wall"), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Wextra"), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Wpedantic"), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Werror"), Span::default()),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("-Wno-unused-parameter"),
+                Span::default(),
+            ),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Wshadow"), Span::default()),
+            CMakeValue::StringLiteral(CMakeArgument::from("-Wconversion"), Span::default()),
+        ],
+        span: Span::default(),
+    }
+}
+
+#[test]
+fn test_fill_list_layout() {
+    let style = Style {
+        list_layout: ListLayout::Fill,
+        ..Default::default()
+    };
+    let mut vec_writer = Vec::new();
+    compile_options_command()
+        .print(&style)
+        .render(60, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+target_compile_options(
+  foo
+  PRIVATE -Wall -Wextra -Wpedantic -Werror
+          -Wno-unused-parameter -Wshadow -Wconversion
+)
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_auto_list_layout_does_not_fill_long_values() {
+    let style = Style {
+        list_layout: ListLayout::Auto,
+        ..Default::default()
+    };
+    let mut command = compile_options_command();
+    command.args.push(CMakeValue::StringLiteral(
+        CMakeArgument::from("-fmacro-prefix-map=${CMAKE_SOURCE_DIR}=."),
+        Span::default(),
+    ));
+    let mut vec_writer = Vec::new();
+    command.print(&style).render(60, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+target_compile_options(
+  foo
+  PRIVATE
+  -Wall
+  -Wextra
+  -Wpedantic
+  -Werror
+  -Wno-unused-parameter
+  -Wshadow
+  -Wconversion
+  -fmacro-prefix-map=${CMAKE_SOURCE_DIR}=.
+)
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_per_command_list_layout_overrides_global_layout() {
+    let mut style = Style::default();
+    style
+        .command_style_mut("TARGET_COMPILE_OPTIONS")
+        .list_layout = Some(ListLayout::Auto);
+    assert_eq!(
+        style.list_layout_for("target_compile_options"),
+        ListLayout::Auto
+    );
+    assert_eq!(
+        style.list_layout_for("target_sources"),
+        ListLayout::OnePerLine
+    );
+
+    let mut vec_writer = Vec::new();
+    compile_options_command()
+        .print(&style)
+        .render(60, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+target_compile_options(
+  foo
+  PRIVATE -Wall -Wextra -Wpedantic -Werror
+          -Wno-unused-parameter -Wshadow -Wconversion
+)
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_gersemi_like_preset() {
+    let document = CMakeDocument {
+        statements: vec![CMakeStatement::Function(CMakeFunctionStatement {
+            group: CMakeCommandGroup {
+                clause: vec![CMakeValue::StringLiteral(
+                    CMakeArgument::from("foo"),
+                    Span::default(),
+                )],
+                body: vec![
+                    CMakeStatement::Newline(Span::default()),
+                    CMakeStatement::Command(CMakeCommand {
+                        name: "MESSAGE".into(),
+                        args: vec![
+                            CMakeValue::ArgumentSpecifier("STATUS".into(), Span::default()),
+                            CMakeValue::QuotedString(CMakeArgument::from("hello"), Span::default()),
+                        ],
+                        span: Span::default(),
+                    }),
+                    CMakeStatement::Newline(Span::default()),
+                ],
+                end_clause: vec![],
+                span: Span::default(),
+            },
+        })],
+    };
+    let mut vec_writer = Vec::new();
+    document
+        .print_with_style(&Preset::GersemiLike.style())
+        .render(80, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+function(foo)
+    message(STATUS "hello")
+endfunction()
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_parens_do_not_dangle_unless_there_is_a_trailing_comment() {
+    let style = Style {
+        dangle_parens: false,
+        ..Default::default()
+    };
+    let mut command = CMakeCommand {
+        name: "foo".into(),
+        args: vec![
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                Span::default(),
+            ),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                Span::default(),
+            ),
+        ],
+        span: Span::default(),
+    };
+    let mut vec_writer = Vec::new();
+    command.print(&style).render(80, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        "foo(\n  aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\n  bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb)"
+    );
+
+    command
+        .args
+        .push(CMakeValue::Comment(" comment".into(), Span::default()));
+    let mut vec_writer = Vec::new();
+    command.print(&style).render(80, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        "foo(\n  aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\n  bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\n  # comment\n)"
+    );
+}
+
+#[test]
+fn test_max_blank_lines() {
+    let style = Style {
+        max_blank_lines: 1,
+        ..Default::default()
+    };
+    let document = CMakeDocument {
+        statements: vec![
+            CMakeStatement::Command(CMakeCommand {
+                name: "foo".into(),
+                args: vec![],
+                span: Span::default(),
+            }),
+            CMakeStatement::Newline(Span::default()),
+            CMakeStatement::Newline(Span::default()),
+            CMakeStatement::Newline(Span::default()),
+            CMakeStatement::Newline(Span::default()),
+            CMakeStatement::Command(CMakeCommand {
+                name: "bar".into(),
+                args: vec![],
+                span: Span::default(),
+            }),
+        ],
+    };
+    let mut vec_writer = Vec::new();
+    document
+        .print_with_style(&style)
+        .render(80, &mut vec_writer)
+        .unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(str, "foo()\n\nbar()");
+}
+
+#[test]
+fn test_never_break_command_style() {
+    let mut style = Style::default();
+    style.command_style_mut("foo").never_break = Some(true);
+    let command = CMakeCommand {
+        name: "foo".into(),
+        args: vec![
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"),
+                Span::default(),
+            ),
+            CMakeValue::StringLiteral(
+                CMakeArgument::from("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"),
+                Span::default(),
+            ),
+        ],
+        span: Span::default(),
+    };
+    let mut vec_writer = Vec::new();
+    command.print(&style).render(80, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        "foo(aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb)"
+    );
+}
+
+#[test]
+fn test_function_name_stays_on_the_opening_line() {
+    let document = CMakeDocument {
+        statements: vec![CMakeStatement::Function(CMakeFunctionStatement {
+            group: CMakeCommandGroup {
+                clause: vec![
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("my_very_long_function_name"),
+                        Span::default(),
+                    ),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("first_parameter"),
+                        Span::default(),
+                    ),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("second_parameter"),
+                        Span::default(),
+                    ),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("third_parameter"),
+                        Span::default(),
+                    ),
+                ],
+                body: vec![CMakeStatement::Newline(Span::default())],
+                end_clause: vec![],
+                span: Span::default(),
+            },
+        })],
+    };
+    let mut vec_writer = Vec::new();
+    document.print().render(60, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+function(my_very_long_function_name
+  first_parameter second_parameter third_parameter
+)
+endfunction()
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_foreach_loop_variable_stays_on_the_opening_line() {
+    let document = CMakeDocument {
+        statements: vec![CMakeStatement::For(CMakeForEachStatement {
+            group: CMakeCommandGroup {
+                clause: vec![
+                    CMakeValue::StringLiteral(CMakeArgument::from("source_file"), Span::default()),
+                    CMakeValue::ArgumentSpecifier("IN".into(), Span::default()),
+                    CMakeValue::ArgumentSpecifier("LISTS".into(), Span::default()),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("project_cxx_source_files"),
+                        Span::default(),
+                    ),
+                    CMakeValue::StringLiteral(
+                        CMakeArgument::from("project_c_source_files"),
+                        Span::default(),
+                    ),
+                ],
+                body: vec![CMakeStatement::Newline(Span::default())],
+                end_clause: vec![],
+                span: Span::default(),
+            },
+        })],
+    };
+    let mut vec_writer = Vec::new();
+    document.print().render(60, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+foreach(source_file IN LISTS
+  project_cxx_source_files project_c_source_files
+)
+endforeach()
+    "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_print_while_statement() {
+    let document = CMakeDocument {
+        statements: vec![CMakeStatement::While(CMakeWhileStatement {
+            condition: CMakeCondition::UnaryLogicalOperator {
+                operator: "NOT".into(),
+                value: Box::new(CMakeCondition::Value(CMakeValue::StringLiteral(
+                    CMakeArgument::from("done"),
+                    Span::default(),
+                ))),
+                span: Span::default(),
+            },
+            body: vec![
+                CMakeStatement::Newline(Span::default()),
+                CMakeStatement::Command(CMakeCommand {
+                    name: "continue".into(),
+                    args: vec![],
+                    span: Span::default(),
+                }),
+                CMakeStatement::Newline(Span::default()),
+            ],
+            span: Span::default(),
+        })],
+    };
+    let mut vec_writer = Vec::new();
+    document.print().render(80, &mut vec_writer).unwrap();
+    let str = String::from_utf8(vec_writer).unwrap();
+    assert_eq!(
+        str,
+        r#"
+while(NOT done)
+  continue()
+endwhile()
+    "#
+        .trim()
+    );
+}
+
+fn format_source(source: &str, width: usize) -> String {
+    let (_, document) = nom::combinator::all_consuming(crate::parser::cmake_parser)(
+        crate::parser::Input::new(source),
+    )
+    .unwrap();
+    let mut vec_writer = Vec::new();
+    document.print().render(width, &mut vec_writer).unwrap();
+    String::from_utf8(vec_writer).unwrap()
+}
+
+#[test]
+fn test_long_conditions_break_at_the_loosest_operator() {
+    let source = "if(A OR B AND C OR NOT D)\nendif()";
+    assert_eq!(format_source(source, 80), source);
+    assert_eq!(
+        format_source(source, 20),
+        r#"
+if(A
+   OR B AND C
+   OR NOT D)
+endif()
+        "#
+        .trim()
+    );
+    assert_eq!(
+        format_source("while((A AND B) OR C)\nendwhile()", 12),
+        r#"
+while((A
+       AND B)
+      OR C)
+endwhile()
+        "#
+        .trim()
+    );
+}
+
+#[test]
+fn test_comments_in_conditions_stay_on_their_own_line() {
+    let source = "if(A\n   AND # why\n   B)\nendif()";
+    assert_eq!(format_source(source, 80), source);
+}
+
+#[test]
+fn test_unparsable_regions_are_printed_unchanged() {
+    let source = "foo(  a )\nif(A)\n  bar(b\n    c\n  baz(   d)\nendif()";
+    let (_, document) =
+        crate::parser::cmake_parser_recovering(crate::parser::Input::new(source)).unwrap();
+    let mut vec_writer = Vec::new();
+    document.print().render(80, &mut vec_writer).unwrap();
+    assert_eq!(
+        String::from_utf8(vec_writer).unwrap(),
+        "foo(a)\nif(A)\n  bar(b\n    c\n  baz(d)\nendif()"
+    );
+}
+
+#[test]
+fn test_comments_before_condition_operators_stay_in_front() {
+    for source in [
+        "if(DEFINED ENV{CI} # running in CI\n   AND NOT DEFINED CACHE{SKIP_TESTS})\nendif()",
+        "if(A\n   # own line\n   AND B)\nendif()",
+        "if(A # a\n   STREQUAL B)\nendif()",
+        "while(A #[[a]] OR B)\nendwhile()",
+        "if(# c\n   A)\nendif()",
+    ] {
+        assert_eq!(format_source(source, 80), source);
+    }
+}
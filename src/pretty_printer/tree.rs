@@ -0,0 +1,347 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2023 Pedro Tacla Yamada
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! The layout document as indented text, for finding out why the printer
+//! laid a statement out the way it did.
+//!
+//! [`to_tree`] walks the document the way `pretty` renders it and writes a
+//! line for every node, with its children indented by two spaces. `line`,
+//! `line_` and `softline` are shown with what they became. Every `group`
+//! says whether it was printed flat or broken and, when its parent was
+//! broken, where the line would end if it were flat, counted in columns from
+//! the start of the line:
+//!
+//! ```text
+//! group break (would end at column 94)
+//!   text "target_link_libraries"
+//!   text "("
+//!   group break (would end at column 94)
+//!     nest 2 (indent 2)
+//!       line_ (newline)
+//! ```
+//!
+//! A group is broken when that is past the width, or when it contains a
+//! `hardline`.
+
+use std::fmt::Write;
+
+use pretty::{Doc, RcDoc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Break,
+    Flat,
+}
+
+/// How a group fits on the current line when it is flat.
+enum Fit {
+    /// The line ends at this column.
+    EndsAt(usize),
+    Hardline,
+    Fail,
+}
+
+/// A document still to be printed, with its indentation and mode.
+type Command<'a> = (usize, Mode, RcDoc<'a, ()>);
+
+struct Tree<'a> {
+    width: usize,
+    /// The column of the next character.
+    column: usize,
+    /// The documents after the one being printed, the next one last.
+    rest: Vec<Command<'a>>,
+    output: String,
+    depth: usize,
+}
+
+/// The text of a text document.
+fn text<'d, 'a>(doc: &'d Doc<'a, RcDoc<'a, ()>, ()>) -> Option<&'d str> {
+    match doc {
+        Doc::OwnedText(text) => Some(text),
+        Doc::BorrowedText(text) => Some(text),
+        Doc::SmallText(text) => Some(text),
+        Doc::RenderLen(_, doc) => text(doc),
+        _ => None,
+    }
+}
+
+/// Whether `doc` is a `line`, a space when flat, or a `line_`, nothing when
+/// flat.
+fn line<'a>(doc: &Doc<'a, RcDoc<'a, ()>, ()>) -> Option<&'static str> {
+    match doc {
+        Doc::FlatAlt(broken, flat) if matches!(**broken, Doc::Hardline) => match &**flat {
+            Doc::Nil => Some("line_"),
+            flat if text(flat) == Some(" ") => Some("line"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The documents appended together in `doc`, in order.
+fn concatenated<'a>(doc: &RcDoc<'a, ()>) -> Vec<RcDoc<'a, ()>> {
+    let mut output = Vec::new();
+    let mut stack = vec![doc.clone()];
+    while let Some(doc) = stack.pop() {
+        match &*doc {
+            Doc::Append(left, right) => {
+                stack.push(right.clone());
+                stack.push(left.clone());
+            }
+            Doc::Nil => {}
+            _ => output.push(doc),
+        }
+    }
+    output
+}
+
+impl<'a> Tree<'a> {
+    fn node(&mut self, label: impl AsRef<str>) {
+        let _ = writeln!(self.output, "{}{}", "  ".repeat(self.depth), label.as_ref());
+    }
+
+    fn children(&mut self, doc: &RcDoc<'a, ()>, indent: usize, mode: Mode) {
+        self.depth += 1;
+        self.walk(doc, indent, mode);
+        self.depth -= 1;
+    }
+
+    /// Moves to the next line, which is indented as the next document.
+    fn newline(&mut self, indent: usize) {
+        self.column = self.rest.last().map_or(indent, |(indent, _, _)| *indent);
+    }
+
+    /// Where the line ends if `doc` is printed flat, followed by the rest of
+    /// the document up to its next line break, as `pretty` checks it.
+    fn fitting(&self, doc: &RcDoc<'a, ()>, indent: usize) -> Fit {
+        let mut column = self.column;
+        let mut stack = vec![(Mode::Flat, doc.clone())];
+        let mut rest = self.rest.iter().rev();
+        loop {
+            let (mode, mut doc) = match stack.pop() {
+                Some(command) => command,
+                None => match rest.next() {
+                    Some((_, _, doc)) => (Mode::Break, doc.clone()),
+                    None => return Fit::EndsAt(column),
+                },
+            };
+            loop {
+                match &*doc {
+                    Doc::Nil => {}
+                    Doc::Append(left, right) => {
+                        stack.push((mode, right.clone()));
+                        doc = left.clone();
+                        continue;
+                    }
+                    Doc::Hardline if mode == Mode::Break => return Fit::EndsAt(column),
+                    Doc::Hardline => return Fit::Hardline,
+                    Doc::RenderLen(len, _) => column += len,
+                    Doc::OwnedText(_) | Doc::BorrowedText(_) | Doc::SmallText(_) => {
+                        column += text(&doc).map_or(0, str::len)
+                    }
+                    Doc::FlatAlt(broken, flat) => {
+                        doc = match mode {
+                            Mode::Break => broken.clone(),
+                            Mode::Flat => flat.clone(),
+                        };
+                        continue;
+                    }
+                    Doc::Column(f) => {
+                        doc = f(column);
+                        continue;
+                    }
+                    Doc::Nesting(f) => {
+                        doc = f(indent);
+                        continue;
+                    }
+                    Doc::Nest(_, next)
+                    | Doc::Group(next)
+                    | Doc::Annotated(_, next)
+                    | Doc::Union(_, next) => {
+                        doc = next.clone();
+                        continue;
+                    }
+                    Doc::Fail => return Fit::Fail,
+                }
+                break;
+            }
+        }
+    }
+
+    fn walk(&mut self, doc: &RcDoc<'a, ()>, indent: usize, mode: Mode) {
+        match &**doc {
+            Doc::Nil => {}
+            Doc::Append(..) => {
+                let docs = concatenated(doc);
+                for doc in docs.iter().skip(1).rev() {
+                    self.rest.push((indent, mode, doc.clone()));
+                }
+                if let Some(first) = docs.first() {
+                    self.walk(first, indent, mode);
+                }
+                for _ in 1..docs.len() {
+                    let (indent, mode, doc) = self.rest.pop().expect("pushed above");
+                    self.walk(&doc, indent, mode);
+                }
+            }
+            Doc::Group(inner) => {
+                let (label, mode) = match mode {
+                    Mode::Flat => ("group flat".to_string(), Mode::Flat),
+                    Mode::Break => match self.fitting(inner, indent) {
+                        Fit::EndsAt(column) if column <= self.width => (
+                            format!("group flat (ends at column {})", column),
+                            Mode::Flat,
+                        ),
+                        Fit::EndsAt(column) => (
+                            format!("group break (would end at column {})", column),
+                            Mode::Break,
+                        ),
+                        Fit::Hardline => ("group break (has a hardline)".to_string(), Mode::Break),
+                        Fit::Fail => ("group break (fails flat)".to_string(), Mode::Break),
+                    },
+                };
+                match line(inner) {
+                    Some(name) => self.line(&format!("soft{}", name), inner, indent, mode),
+                    None => {
+                        self.node(label);
+                        self.children(inner, indent, mode);
+                    }
+                }
+            }
+            Doc::FlatAlt(broken, flat) => match line(doc) {
+                Some(name) => self.line(name, doc, indent, mode),
+                None => {
+                    let (label, doc) = match mode {
+                        Mode::Break => ("flat_alt (broken)", broken),
+                        Mode::Flat => ("flat_alt (flat)", flat),
+                    };
+                    self.node(label);
+                    self.children(doc, indent, mode);
+                }
+            },
+            Doc::Nest(offset, inner) => {
+                let indent = indent.saturating_add_signed(*offset);
+                self.node(format!("nest {} (indent {})", offset, indent));
+                self.children(inner, indent, mode);
+            }
+            Doc::Hardline => {
+                self.node("hardline");
+                self.newline(indent);
+            }
+            Doc::RenderLen(len, _) => {
+                self.node(format!("text {:?}", text(doc).unwrap_or_default()));
+                self.column += len;
+            }
+            Doc::OwnedText(_) | Doc::BorrowedText(_) | Doc::SmallText(_) => {
+                let text = text(doc).unwrap_or_default();
+                self.node(format!("text {:?}", text));
+                self.column += text.len();
+            }
+            Doc::Annotated(_, inner) => {
+                self.node("annotated");
+                self.children(inner, indent, mode);
+            }
+            Doc::Union(inner, _) => {
+                self.node("union");
+                self.children(inner, indent, mode);
+            }
+            Doc::Column(f) => {
+                self.node(format!("column (at {})", self.column));
+                self.children(&f(self.column), indent, mode);
+            }
+            Doc::Nesting(f) => {
+                self.node(format!("nesting (at {})", indent));
+                self.children(&f(indent), indent, mode);
+            }
+            Doc::Fail => self.node("fail"),
+        }
+    }
+
+    /// A `line` or `line_`, which is `name` with its group if it has one.
+    fn line(&mut self, name: &str, doc: &RcDoc<'a, ()>, indent: usize, mode: Mode) {
+        match mode {
+            Mode::Break => {
+                self.node(format!("{} (newline)", name));
+                self.newline(indent);
+            }
+            Mode::Flat => {
+                let text = match &**doc {
+                    Doc::FlatAlt(_, flat) => text(flat).unwrap_or_default(),
+                    _ => "",
+                };
+                self.node(format!("{} ({:?})", name, text));
+                self.column += text.len();
+            }
+        }
+    }
+}
+
+/// Writes `doc` as an indented tree, laid out for `width` columns.
+pub fn to_tree(doc: &RcDoc<'_, ()>, width: usize) -> String {
+    let mut tree = Tree {
+        width,
+        column: 0,
+        rest: Vec::new(),
+        output: String::new(),
+        depth: 0,
+    };
+    tree.walk(doc, 0, Mode::Break);
+    tree.output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::format::parse_document;
+
+    #[test]
+    fn test_to_tree() {
+        let document = parse_document("target_link_libraries(foo PRIVATE a b)\n").unwrap();
+        assert_eq!(
+            to_tree(&document.print(), 20),
+            [
+                "group break (would end at column 38)",
+                "  text \"target_link_libraries\"",
+                "  text \"(\"",
+                "  group break (would end at column 38)",
+                "    nest 2 (indent 2)",
+                "      line_ (newline)",
+                "      text \"foo\"",
+                "      line (newline)",
+                "      group flat (ends at column 13)",
+                "        text \"PRIVATE\"",
+                "        line (\" \")",
+                "        text \"a\"",
+                "        line (\" \")",
+                "        text \"b\"",
+                "      line_ (newline)",
+                "  text \")\"",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            to_tree(&RcDoc::text("a").append(RcDoc::softline()).group(), 80),
+            "group flat (ends at column 2)\n  text \"a\"\n  softline (\" \")\n"
+        );
+    }
+}
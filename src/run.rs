@@ -20,41 +20,76 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
-use nom_supreme::final_parser::final_parser;
+use std::io::Write;
 
-use crate::options::Options;
+use crate::errors;
+use crate::format::{parse_document, print_document};
+use crate::options::{Emit, InputFormat, Options};
+use crate::parser::diagnostics::{self, Severity};
+use crate::parser::{json, lexer, tree};
+use crate::pretty_printer;
 use crate::writer::DefaultWriter;
-use crate::{errors, parser};
 
 pub fn run_cmakefmt(opts: Options) {
     let Options {
-        verbose,
         inplace,
+        emit,
+        input_format,
         input_file,
-        width,
+        format_options,
     } = opts;
 
     let file_contents = std::fs::read_to_string(&input_file).expect("Failed to open file");
-    let mut parser = final_parser(parser::cmake_parser);
 
-    if verbose {
-        println!("{file_contents:#?}");
+    if emit == Emit::Tokens && input_format != InputFormat::Cmake {
+        eprintln!("error: --emit tokens needs --input-format cmake");
+        std::process::exit(1);
     }
-    match parser(&file_contents) {
-        Ok(contents) => {
-            if verbose {
-                println!("{contents:#?}");
+
+    let (document, diagnostics) = match input_format {
+        InputFormat::Cmake => match parse_document(&file_contents) {
+            Ok(document) => {
+                let diagnostics = diagnostics::check(&document);
+                (Some(document), diagnostics)
+            }
+            Err(diagnostic) => (None, vec![*diagnostic]),
+        },
+        InputFormat::AstJson => match json::from_json(&file_contents) {
+            Ok(document) => (Some(document), Vec::new()),
+            Err(err) => {
+                eprintln!("error: {}: {}", input_file, err);
+                std::process::exit(1);
             }
+        },
+    };
+    for diagnostic in &diagnostics {
+        errors::print_diagnostic(&input_file, file_contents.as_str(), diagnostic);
+    }
+    let has_errors = diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error);
 
-            let mut writer = DefaultWriter::new(inplace, input_file.as_str());
-            contents
-                .print()
-                .render(width, &mut writer)
-                .expect("Failed to format file");
-        }
-        Err(err) => {
-            errors::print_error(&input_file, file_contents.as_str(), &err);
-            std::process::exit(1);
-        }
+    let output = match (document, emit) {
+        (Some(document), Emit::Formatted) => print_document(&document, &format_options),
+        (Some(document), Emit::AstJson) => json::to_json(&document) + "\n",
+        (Some(document), Emit::AstTree) => tree::to_tree(&document),
+        (Some(document), Emit::Doc) => pretty_printer::tree::to_tree(
+            &document.print_with_style(&format_options.style),
+            format_options.width,
+        ),
+        (_, Emit::Tokens) => lexer::to_text(&file_contents),
+        // The recovering parser keeps anything it can't parse as text
+        (None, Emit::Formatted) => file_contents,
+        (None, _) => std::process::exit(1),
     };
+
+    let mut writer = DefaultWriter::new(inplace, input_file.as_str());
+    writer
+        .write_all(output.as_bytes())
+        .expect("Failed to format file");
+    writer.flush().expect("Failed to write file");
+
+    if has_errors {
+        std::process::exit(1);
+    }
 }
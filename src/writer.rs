@@ -58,6 +58,6 @@ impl Write for DefaultWriter {
         if !buf.is_empty() {
             let _ = self.imp.write(buf.as_ref());
         }
-        return Ok(());
+        Ok(())
     }
 }
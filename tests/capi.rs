@@ -0,0 +1,56 @@
+//! Builds the C library, then compiles `tests/capi/test.c` against it and
+//! runs it.
+#![cfg(all(feature = "capi", target_os = "linux"))]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn run(command: &mut Command, what: &str) {
+    let status = command
+        .status()
+        .unwrap_or_else(|error| panic!("failed to {what}: {error}"));
+    assert!(status.success(), "failed to {what}");
+}
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A target directory of its own, so the library isn't replaced by
+    // builds without the feature
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("capi");
+    run(
+        Command::new(env!("CARGO"))
+            .args(["rustc", "--lib", "--features", "capi"])
+            .args(["--crate-type", "cdylib"])
+            .arg("--manifest-path")
+            .arg(manifest_dir.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir),
+        "build the C library",
+    );
+
+    let library_dir = target_dir.join("debug");
+    let program = target_dir.join("capi-test");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    run(
+        Command::new(compiler)
+            .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+            .arg("-I")
+            .arg(manifest_dir.join("include"))
+            .arg(manifest_dir.join("tests/capi/test.c"))
+            .arg("-o")
+            .arg(&program)
+            .arg("-L")
+            .arg(&library_dir)
+            .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+            .arg("-lcmakefmt"),
+        "compile tests/capi/test.c",
+    );
+    // cargo points this at its own deps directory, which comes before the
+    // rpath
+    run(
+        Command::new(&program).env_remove("LD_LIBRARY_PATH"),
+        "run tests/capi/test.c",
+    );
+}
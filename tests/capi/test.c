@@ -0,0 +1,108 @@
+/* Exercises the C interface. Run by tests/capi.rs. */
+#include <stdio.h>
+#include <string.h>
+
+#include "cmakefmt.h"
+
+#define CHECK(condition)                                                     \
+  do {                                                                       \
+    if (!(condition)) {                                                      \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,       \
+              #condition);                                                   \
+      return 1;                                                              \
+    }                                                                        \
+  } while (0)
+
+static cmakefmt_status format(const char *source, const cmakefmt_options *options,
+                              cmakefmt_result *result) {
+  return cmakefmt_format(source, strlen(source), options, result);
+}
+
+static int test_format(void) {
+  cmakefmt_result result;
+  CHECK(format("project( foo )\n", NULL, &result) == CMAKEFMT_OK);
+  CHECK(strcmp(result.output, "project(foo)") == 0);
+  CHECK(result.output_len == strlen("project(foo)"));
+  CHECK(result.diagnostics_len == 0);
+  cmakefmt_result_free(&result);
+  CHECK(result.output == NULL);
+  CHECK(result.diagnostics == NULL);
+  return 0;
+}
+
+static int test_options(void) {
+  cmakefmt_options options;
+  CHECK(cmakefmt_options_init(&options, CMAKEFMT_PRESET_DEFAULT) == CMAKEFMT_OK);
+  CHECK(options.width == 80);
+  CHECK(options.indent == 2);
+  options.command_case = CMAKEFMT_COMMAND_CASE_UPPER;
+  options.width = 20;
+
+  cmakefmt_result result;
+  CHECK(format("add_library(foo a.cpp b.cpp)", &options, &result) == CMAKEFMT_OK);
+  CHECK(strcmp(result.output, "ADD_LIBRARY(\n  foo a.cpp b.cpp\n)") == 0);
+  cmakefmt_result_free(&result);
+  return 0;
+}
+
+static int test_parse_error(void) {
+  const char *source = "project(foo)\nset(x\n";
+  cmakefmt_result result;
+  CHECK(format(source, NULL, &result) == CMAKEFMT_PARSE_ERROR);
+  CHECK(strcmp(result.output, "project(foo)\nset(x") == 0);
+  CHECK(result.diagnostics_len > 0);
+  const cmakefmt_diagnostic *error = &result.diagnostics[0];
+  CHECK(error->severity == CMAKEFMT_SEVERITY_ERROR);
+  CHECK(strlen(error->message) > 0);
+  /* The command is missing its `)`, so the error is at the end */
+  CHECK(error->span.start.offset == strlen(source));
+  CHECK(error->span.start.line == 3);
+  CHECK(error->span.start.column == 1);
+  cmakefmt_result_free(&result);
+  return 0;
+}
+
+static int test_invalid_utf8(void) {
+  cmakefmt_result result;
+  CHECK(format("set(x \"\xff\")", NULL, &result) == CMAKEFMT_INVALID_UTF8);
+  CHECK(result.output == NULL);
+  CHECK(result.diagnostics_len == 1);
+  CHECK(result.diagnostics[0].span.start.offset == 7);
+  CHECK(result.diagnostics[0].span.start.column == 8);
+  CHECK(result.diagnostics[0].span.end.offset == 8);
+  cmakefmt_result_free(&result);
+  return 0;
+}
+
+static int test_invalid_arguments(void) {
+  cmakefmt_result result;
+  CHECK(cmakefmt_format(NULL, 1, NULL, &result) == CMAKEFMT_INVALID_ARGUMENT);
+  cmakefmt_result_free(&result);
+  CHECK(cmakefmt_format("", 0, NULL, NULL) == CMAKEFMT_INVALID_ARGUMENT);
+  CHECK(cmakefmt_options_init(NULL, CMAKEFMT_PRESET_DEFAULT) == CMAKEFMT_INVALID_ARGUMENT);
+  cmakefmt_options options;
+  CHECK(cmakefmt_options_init(&options, 4) == CMAKEFMT_INVALID_ARGUMENT);
+
+  /* Settings that aren't one of their enum's values are rejected */
+  CHECK(cmakefmt_options_init(&options, CMAKEFMT_PRESET_DEFAULT) == CMAKEFMT_OK);
+  options.command_case = 7;
+  CHECK(format("project(foo)", &options, &result) == CMAKEFMT_INVALID_ARGUMENT);
+  CHECK(result.output == NULL);
+  cmakefmt_result_free(&result);
+  options.command_case = CMAKEFMT_COMMAND_CASE_LOWER;
+  options.list_layout = UINT32_MAX;
+  CHECK(format("project(foo)", &options, &result) == CMAKEFMT_INVALID_ARGUMENT);
+  cmakefmt_result_free(&result);
+  cmakefmt_result_free(NULL);
+
+  /* An empty source may be null */
+  CHECK(cmakefmt_format(NULL, 0, NULL, &result) != CMAKEFMT_INVALID_ARGUMENT);
+  cmakefmt_result_free(&result);
+  return 0;
+}
+
+int main(void) {
+  int failed = test_format() || test_options() || test_parse_error() ||
+               test_invalid_utf8() || test_invalid_arguments();
+  return failed;
+}